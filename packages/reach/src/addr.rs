@@ -0,0 +1,83 @@
+use anyhow::{Result, anyhow};
+use socket2::{SockRef, TcpKeepalive};
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::{TcpStream, lookup_host};
+
+/// Resolves `input` - a literal `ip:port`, a `host:port`, or a bare `host`
+/// when `default_port` is given - to every address DNS returns (A and AAAA
+/// alike), via tokio's resolver instead of `str::parse::<SocketAddr>`. Shared
+/// by `NetworkManager::connect_to_peer` and the CLI `/connect` command so
+/// hostnames work the same way everywhere a peer address is typed in.
+pub async fn resolve_addresses(input: &str, default_port: Option<u16>) -> Result<Vec<SocketAddr>> {
+    let has_port = input.rsplit_once(':').is_some_and(|(_, port)| port.parse::<u16>().is_ok());
+
+    let candidate = if has_port {
+        input.to_string()
+    } else if let Some(port) = default_port {
+        format!("{}:{}", input, port)
+    } else {
+        return Err(anyhow!("'{}' has no port and no default port was given", input));
+    };
+
+    let addrs: Vec<SocketAddr> = lookup_host(&candidate)
+        .await
+        .map_err(|e| anyhow!("Could not resolve '{}': {}", candidate, e))?
+        .collect();
+
+    if addrs.is_empty() {
+        return Err(anyhow!("'{}' did not resolve to any address", candidate));
+    }
+
+    Ok(addrs)
+}
+
+/// Resolves `input` and connects to the first address that accepts a TCP
+/// connection within `Config::connect_timeout_secs`, falling back through
+/// the remaining A/AAAA records instead of giving up after the first one
+/// refuses or times out.
+pub async fn connect_tcp(input: &str, default_port: Option<u16>) -> Result<(TcpStream, SocketAddr)> {
+    let addrs = resolve_addresses(input, default_port).await?;
+    let connect_timeout = Duration::from_secs(
+        crate::config::load_config_cached()
+            .map(|config| config.connect_timeout_secs)
+            .unwrap_or(10),
+    );
+
+    let mut last_err = None;
+    for addr in addrs {
+        match tokio::time::timeout(connect_timeout, TcpStream::connect(addr)).await {
+            Ok(Ok(stream)) => {
+                apply_socket_tuning(&stream);
+                return Ok((stream, addr));
+            }
+            Ok(Err(e)) => last_err = Some(e.to_string()),
+            Err(_) => last_err = Some(format!("timed out after {:?}", connect_timeout)),
+        }
+    }
+
+    Err(anyhow!(
+        "Could not connect to '{}' on any resolved address: {}",
+        input,
+        last_err.unwrap_or_else(|| "no addresses tried".to_string())
+    ))
+}
+
+/// Applies `Config::tcp_nodelay`/`tcp_keepalive_secs` to a peer `TcpStream`,
+/// accepted or dialed - see `NetworkManager::spawn_accept_loop` and
+/// `connect_tcp`. Failures are logged and otherwise ignored; a connection
+/// that can't be tuned is still usable, just not optimally.
+pub fn apply_socket_tuning(stream: &TcpStream) {
+    let config = crate::config::load_config_cached().unwrap_or_default();
+
+    if let Err(e) = stream.set_nodelay(config.tcp_nodelay) {
+        log::warn!("Failed to set TCP_NODELAY: {}", e);
+    }
+
+    if let Some(secs) = config.tcp_keepalive_secs {
+        let keepalive = TcpKeepalive::new().with_time(Duration::from_secs(secs));
+        if let Err(e) = SockRef::from(stream).set_tcp_keepalive(&keepalive) {
+            log::warn!("Failed to set TCP keepalive: {}", e);
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// A remembered peer, persisted so the next session doesn't need the address
+/// retyped - see `AddressBook::remember` and `ReachEngine::connect_known_peers`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KnownPeer {
+    pub id: String,
+    pub public_key: String,
+    pub nickname: String,
+    /// Addresses this peer has been reached at, most recently used last.
+    pub last_addresses: Vec<String>,
+}
+
+/// A local registry of previously-connected peers, keyed by peer id and
+/// stored as `address_book.json` under the config dir - mirrors
+/// `cli::UserManager`'s `users.json` registry.
+pub struct AddressBook {
+    file: PathBuf,
+}
+
+impl AddressBook {
+    pub fn new() -> Result<Self> {
+        let config_dir = crate::config::get_config_dir()?;
+        Ok(AddressBook {
+            file: config_dir.join("address_book.json"),
+        })
+    }
+
+    /// Records (or updates) a peer's entry, moving `address` to the end of
+    /// its `last_addresses`; the next `connect_known_peers` pass dials only
+    /// that last, most recently used address.
+    pub fn remember(&self, id: &str, public_key: &str, nickname: &str, address: &str) -> Result<()> {
+        let mut entries = self.load()?;
+
+        let entry = entries.entry(id.to_string()).or_insert_with(|| KnownPeer {
+            id: id.to_string(),
+            public_key: public_key.to_string(),
+            nickname: nickname.to_string(),
+            last_addresses: Vec::new(),
+        });
+
+        entry.public_key = public_key.to_string();
+        entry.nickname = nickname.to_string();
+        entry.last_addresses.retain(|existing| existing != address);
+        entry.last_addresses.push(address.to_string());
+
+        self.save(&entries)
+    }
+
+    pub fn list(&self) -> Result<Vec<KnownPeer>> {
+        Ok(self.load()?.into_values().collect())
+    }
+
+    pub fn remove(&self, id: &str) -> Result<()> {
+        let mut entries = self.load()?;
+        entries.remove(id);
+        self.save(&entries)
+    }
+
+    fn load(&self) -> Result<HashMap<String, KnownPeer>> {
+        if !self.file.exists() {
+            return Ok(HashMap::new());
+        }
+
+        let contents = fs::read_to_string(&self.file)?;
+        Ok(serde_json::from_str(&contents).unwrap_or_default())
+    }
+
+    fn save(&self, entries: &HashMap<String, KnownPeer>) -> Result<()> {
+        let contents = serde_json::to_string_pretty(entries)?;
+        fs::write(&self.file, contents)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,125 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// How many bytes of an encrypted `Channel::Chat` frame fit in one physical
+/// write before `network::PeerConnection::send_message` splits it into
+/// numbered `ChatChunkFrame`s instead of sending it whole - see
+/// `receive_message`'s reassembly. Comfortably under
+/// `transport::TcpStream::read_frame`'s 4096-byte read buffer (leaving room
+/// for this frame's own bincode/tag overhead), so a single chunk always
+/// arrives in one read instead of the buffer silently truncating a long
+/// pasted message.
+pub const CHAT_CHUNK_SIZE: usize = 3072;
+
+/// Sane upper bound on a reassembled oversized chat frame, far more than any
+/// real paste or inline image would need - see `MAX_CHAT_CHUNKS` and
+/// `ChatReassembly::new`.
+const MAX_CHAT_MESSAGE_BYTES: usize = 64 * 1024 * 1024;
+
+/// Highest `ChatChunkFrame::total` `ChatReassembly::new` will accept, derived
+/// from `MAX_CHAT_MESSAGE_BYTES`. `total` is bincode-deserialized straight
+/// off the wire from a peer we don't otherwise trust, so it has to be capped
+/// before it's used as a `Vec` length - an attacker claiming `total =
+/// u32::MAX` would otherwise force a roughly hundred-gigabyte allocation.
+pub const MAX_CHAT_CHUNKS: u32 = (MAX_CHAT_MESSAGE_BYTES / CHAT_CHUNK_SIZE) as u32;
+
+/// One piece of a chat frame too big to fit in a single `Channel::Chat`
+/// write - bincode-serialized, not JSON, for the same reason as
+/// `file_transfer::FileChunkFrame`: this is wire framing underneath
+/// `protocol::encode_message`, not part of its own format.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatChunkFrame {
+    /// Groups chunks belonging to the same oversized message - a fresh id
+    /// per message, not tied to the `Message::id` it eventually decodes to.
+    pub manifest_id: Uuid,
+    pub index: u32,
+    pub total: u32,
+    pub data: Vec<u8>,
+}
+
+impl ChatChunkFrame {
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        Ok(bincode::serialize(self)?)
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        Ok(bincode::deserialize(bytes)?)
+    }
+}
+
+/// Accumulates `ChatChunkFrame`s for one in-flight oversized message until
+/// every index has arrived - see `network::PeerConnection::receive_message`.
+/// Frames for a single manifest arrive in order (one TCP connection, one
+/// sender loop writing them back to back), but indexing by position rather
+/// than appending costs nothing and means an out-of-order arrival wouldn't
+/// corrupt the reassembled bytes either.
+#[derive(Debug)]
+pub struct ChatReassembly {
+    total: u32,
+    received: u32,
+    chunks: Vec<Option<Vec<u8>>>,
+}
+
+impl ChatReassembly {
+    /// Returns `None` if `total` exceeds `MAX_CHAT_CHUNKS` rather than
+    /// allocating a `chunks` vec sized off an untrusted wire value - see
+    /// `MAX_CHAT_CHUNKS`. Callers should drop the frame that reported it.
+    pub fn new(total: u32) -> Option<Self> {
+        if total == 0 || total > MAX_CHAT_CHUNKS {
+            return None;
+        }
+
+        Some(ChatReassembly {
+            total,
+            received: 0,
+            chunks: vec![None; total as usize],
+        })
+    }
+
+    /// Records one chunk; returns the fully reassembled message once every
+    /// chunk for this manifest has arrived.
+    pub fn add(&mut self, index: u32, data: Vec<u8>) -> Option<Vec<u8>> {
+        let slot = self.chunks.get_mut(index as usize)?;
+        if slot.is_none() {
+            *slot = Some(data);
+            self.received += 1;
+        }
+
+        if self.received < self.total {
+            return None;
+        }
+
+        Some(
+            self.chunks
+                .iter_mut()
+                .filter_map(|slot| slot.take())
+                .flatten()
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_a_malicious_total_instead_of_allocating() {
+        assert!(ChatReassembly::new(u32::MAX).is_none());
+        assert!(ChatReassembly::new(MAX_CHAT_CHUNKS + 1).is_none());
+    }
+
+    #[test]
+    fn rejects_a_zero_total() {
+        assert!(ChatReassembly::new(0).is_none());
+    }
+
+    #[test]
+    fn accepts_a_reasonable_total_and_reassembles_in_order() {
+        let mut assembly = ChatReassembly::new(3).expect("within MAX_CHAT_CHUNKS");
+        assert!(assembly.add(1, vec![2]).is_none());
+        assert!(assembly.add(0, vec![1]).is_none());
+        assert_eq!(assembly.add(2, vec![3]), Some(vec![1, 2, 3]));
+    }
+}
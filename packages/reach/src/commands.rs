@@ -0,0 +1,801 @@
+use anyhow::{Result, anyhow};
+use uuid::Uuid;
+
+use crate::conversation::Conversation;
+use crate::directory::DirectoryEntry;
+use crate::message::Message;
+use crate::outbox::OutboxEntry;
+use crate::peer::{Capabilities, Peer};
+use crate::scheduled::ScheduledMessage;
+use crate::session::SessionManager;
+use crate::stats::NetworkStats;
+
+/// A parsed slash command from the chat REPL, shared between front-ends so
+/// `rus` and any future UI (a TUI, say) agree on syntax and argument
+/// validation instead of each hand-parsing `/command arg arg` strings with
+/// its own quirks. Parse with `parse`; list the grammar with `help_lines`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Help,
+    Connect { address: String },
+    Peers,
+    Conversations { include_archived: bool },
+    Capabilities { peer_id: String },
+    Info,
+    Topic { text: Option<String> },
+    History { limit: usize },
+    Star { message_id: Uuid },
+    Pin { message_id: Uuid },
+    Unpin { message_id: Uuid },
+    Pins { peer_id: String },
+    TemplateSave { name: String, content: String },
+    SendTemplate { name: String },
+    Archive { peer_id: String },
+    Unarchive { peer_id: String },
+    Pending,
+    Accept { peer_id: String },
+    Reject { peer_id: String },
+    TranslateOn { source_lang: String, target_lang: String, peer_id: Option<String> },
+    TranslateOff { peer_id: Option<String> },
+    Publish { endpoints: Vec<String> },
+    Lookup { user_id: Uuid },
+    Dnd { enabled: Option<bool> },
+    ReadOnly { enabled: Option<bool> },
+    Stats,
+    Broadcast { content: String },
+    Outbox,
+    OutboxRetry { id: Uuid },
+    OutboxDiscard { id: Uuid },
+    NotifySound { sound: Option<String> },
+    NotifyPeer { peer_id: String, sound: String },
+    Mute { peer_id: String, duration: chrono::Duration },
+    Unmute { peer_id: String },
+    Typing { peer_id: String },
+    FileSend { peer_id: String, path: String },
+    FileAccept { transfer_id: Uuid, dest_path: String },
+    FileReject { transfer_id: Uuid },
+    MarkRead { peer_id: String },
+    EditMessage { message_id: Uuid, text: String },
+    RetractMessage { message_id: Uuid },
+    React { message_id: Uuid, emoji: String },
+    Unreact { message_id: Uuid, emoji: String },
+    Schedule { delay: chrono::Duration, content: String },
+    Scheduled,
+    Unschedule { id: Uuid },
+    Markdown { content: String },
+    Code { lang: String, text: String },
+    SendImage { peer_id: String, path: String },
+    Forward { message_id: Uuid, peer_id: String },
+    Verify { message_id: Uuid },
+    LogLevel { level: Option<String> },
+    Quit,
+}
+
+/// One line of `/help` text per command, in the order they should be shown.
+/// `parse` matches command names itself and does not read this table, so the
+/// two are kept in step by hand.
+const HELP: &[(&str, &str)] = &[
+    ("/connect <host:port|user@domain>", "Connect to a peer (hostnames and _rustalk._tcp DNS handles are resolved)"),
+    ("/peers", "List connected peers"),
+    ("/conversations [archived]", "List conversations, grouped per peer, with unread counts (archived ones are hidden unless asked for)"),
+    ("/capabilities <peer_id>", "Show what features a connected peer advertised in its handshake"),
+    ("/info", "Show session info"),
+    ("/topic [text]", "Show, or set and announce to every peer, this session's topic"),
+    ("/history [limit]", "Show recent messages"),
+    ("/archive <peer_id>", "Hide a conversation from the active sidebar"),
+    ("/unarchive <peer_id>", "Restore an archived conversation to the active sidebar"),
+    ("/pending", "List inbound connections awaiting approval (Config::auto_accept_connections is off)"),
+    ("/accept <peer_id>", "Approve a pending connection"),
+    ("/reject <peer_id>", "Decline a pending connection"),
+    ("/star <message_id>", "Flag a message for the starred review view"),
+    ("/pin <message_id>", "Pin a message to highlight it within its conversation"),
+    ("/unpin <message_id>", "Unpin a message"),
+    ("/pins <peer_id>", "List pinned messages in the conversation with a peer"),
+    ("/template save <name> <text>", "Save a canned response"),
+    ("/t <name>", "Send a saved canned response"),
+    ("/translate on <lang1->lang2> [peer_id]", "Translate a conversation's incoming messages"),
+    ("/translate off [peer_id]", "Disable translation for a conversation"),
+    ("/publish <host:port...>", "Publish our endpoints to Config::directory_address"),
+    ("/lookup <user_id>", "Look up a user's endpoints on Config::directory_address"),
+    ("/dnd [on|off]", "Show or toggle Do Not Disturb (mentions still notify)"),
+    ("/readonly [on|off]", "Show or toggle read-only mode, blocking accidental sends"),
+    ("/stats", "Show network traffic, message, reconnect and RTT counters"),
+    ("/all <msg>", "Broadcast a message to every connected peer at once"),
+    ("/outbox", "List messages that failed to send and are queued, retrying, or failed"),
+    ("/retry <id>", "Immediately retry an outbox entry"),
+    ("/discard <id>", "Drop an outbox entry without retrying it again"),
+    ("/notify sound <bell|none|path>", "Set the global notification sound"),
+    ("/notify peer <peer_id> <bell|none|path>", "Override the notification sound for one peer"),
+    ("/mute <peer_id> <2h|30m|1d|45s>", "Snooze notifications from a peer for a while (mentions still notify)"),
+    ("/unmute <peer_id>", "Cancel an active /mute early"),
+    ("/typing <peer_id>", "Send a low-latency typing notice to a peer over UDP"),
+    ("/file send <peer_id> <path>", "Offer a file to a peer over the encrypted file-transfer channel"),
+    ("/file accept <transfer_id> <dest_path>", "Accept a pending file offer and write it to dest_path"),
+    ("/file reject <transfer_id>", "Decline a pending file offer"),
+    ("/read <peer_id>", "Mark a conversation read, announcing it to the peer if read receipts are enabled"),
+    ("/edit <message_id> <text>", "Replace the text of a message you sent and notify its recipient(s)"),
+    ("/retract <message_id>", "Delete a message you sent and notify its recipient(s)"),
+    ("/react <message_id> <emoji>", "Add an emoji reaction to a message"),
+    ("/unreact <message_id> <emoji>", "Remove your emoji reaction from a message"),
+    ("/schedule <2h|30m|1d|45s> <msg>", "Hold a message locally and send it once the delay elapses"),
+    ("/scheduled", "List messages queued by /schedule that haven't been sent yet"),
+    ("/unschedule <id>", "Cancel a pending /schedule'd message"),
+    ("/md <text>", "Send markdown-formatted text to every connected peer"),
+    ("/code <lang> <code>", "Send a code snippet to every connected peer"),
+    ("/image <peer_id> <path>", "Send an image file inline to one peer"),
+    ("/forward <message_id> <peer_id>", "Re-send a message from history to another peer, with provenance"),
+    ("/verify <message_id>", "Check a message's signature against its sender's known signing key"),
+    ("/loglevel [trace|debug|info|warn|error|off]", "Show or change this process's log filter"),
+    ("/quit", "Exit chat"),
+    ("/help", "Show this help"),
+];
+
+/// `(usage, description)` pairs for every command, in display order.
+pub fn help_lines() -> &'static [(&'static str, &'static str)] {
+    HELP
+}
+
+fn parse_on_off(value: Option<&str>) -> Option<bool> {
+    match value {
+        Some("on") => Some(true),
+        Some("off") => Some(false),
+        _ => None,
+    }
+}
+
+/// Parses one line of chat REPL input (expected to start with `/`) into a
+/// typed `Command`. On failure - malformed arguments or an unrecognized
+/// command name - returns an error whose message is a user-facing usage
+/// string, safe to print directly.
+pub fn parse(input: &str) -> Result<Command> {
+    let parts: Vec<&str> = input.split_whitespace().collect();
+
+    match parts.first().copied() {
+        Some("/help") => Ok(Command::Help),
+        Some("/connect") => parts
+            .get(1)
+            .map(|address| Command::Connect { address: address.to_string() })
+            .ok_or_else(|| anyhow!("Usage: /connect <host:port|user@domain>")),
+        Some("/peers") => Ok(Command::Peers),
+        Some("/conversations") => {
+            let include_archived = parts.get(1).is_some_and(|arg| *arg == "archived");
+            Ok(Command::Conversations { include_archived })
+        }
+        Some("/capabilities") => parts
+            .get(1)
+            .ok_or_else(|| anyhow!("Usage: /capabilities <peer_id>"))
+            .map(|peer_id| Command::Capabilities { peer_id: peer_id.to_string() }),
+        Some("/info") => Ok(Command::Info),
+        Some("/topic") => {
+            let text = parts[1.min(parts.len())..].join(" ");
+            Ok(Command::Topic { text: if text.is_empty() { None } else { Some(text) } })
+        }
+        Some("/history") => {
+            let limit = parts.get(1).and_then(|s| s.parse::<usize>().ok()).unwrap_or(10);
+            Ok(Command::History { limit })
+        }
+        Some("/star") => parts
+            .get(1)
+            .and_then(|s| Uuid::parse_str(s).ok())
+            .map(|message_id| Command::Star { message_id })
+            .ok_or_else(|| anyhow!("Usage: /star <message_id>")),
+        Some("/pin") => parts
+            .get(1)
+            .and_then(|s| Uuid::parse_str(s).ok())
+            .map(|message_id| Command::Pin { message_id })
+            .ok_or_else(|| anyhow!("Usage: /pin <message_id>")),
+        Some("/unpin") => parts
+            .get(1)
+            .and_then(|s| Uuid::parse_str(s).ok())
+            .map(|message_id| Command::Unpin { message_id })
+            .ok_or_else(|| anyhow!("Usage: /unpin <message_id>")),
+        Some("/pins") => parts
+            .get(1)
+            .map(|peer_id| Command::Pins { peer_id: peer_id.to_string() })
+            .ok_or_else(|| anyhow!("Usage: /pins <peer_id>")),
+        Some("/template") => {
+            if parts.get(1).copied() != Some("save") {
+                return Err(anyhow!("Usage: /template save <name> <text>"));
+            }
+            let name = parts.get(2).ok_or_else(|| anyhow!("Usage: /template save <name> <text>"))?;
+            let content = parts[3.min(parts.len())..].join(" ");
+            if content.is_empty() {
+                return Err(anyhow!("Usage: /template save <name> <text>"));
+            }
+            Ok(Command::TemplateSave { name: name.to_string(), content })
+        }
+        Some("/t") => parts
+            .get(1)
+            .map(|name| Command::SendTemplate { name: name.to_string() })
+            .ok_or_else(|| anyhow!("Usage: /t <name>")),
+        Some("/archive") => parts
+            .get(1)
+            .map(|peer_id| Command::Archive { peer_id: peer_id.to_string() })
+            .ok_or_else(|| anyhow!("Usage: /archive <peer_id>")),
+        Some("/unarchive") => parts
+            .get(1)
+            .map(|peer_id| Command::Unarchive { peer_id: peer_id.to_string() })
+            .ok_or_else(|| anyhow!("Usage: /unarchive <peer_id>")),
+        Some("/pending") => Ok(Command::Pending),
+        Some("/accept") => parts
+            .get(1)
+            .map(|peer_id| Command::Accept { peer_id: peer_id.to_string() })
+            .ok_or_else(|| anyhow!("Usage: /accept <peer_id>")),
+        Some("/reject") => parts
+            .get(1)
+            .map(|peer_id| Command::Reject { peer_id: peer_id.to_string() })
+            .ok_or_else(|| anyhow!("Usage: /reject <peer_id>")),
+        Some("/translate") => match parts.get(1).copied() {
+            Some("on") => {
+                let pair = parts
+                    .get(2)
+                    .ok_or_else(|| anyhow!("Usage: /translate on <lang1->lang2> [peer_id]"))?;
+                let (source_lang, target_lang) = crate::translation::TranslationHook::parse_language_pair(pair)?;
+                Ok(Command::TranslateOn {
+                    source_lang,
+                    target_lang,
+                    peer_id: parts.get(3).map(|s| s.to_string()),
+                })
+            }
+            Some("off") => Ok(Command::TranslateOff { peer_id: parts.get(2).map(|s| s.to_string()) }),
+            _ => Err(anyhow!(
+                "Usage: /translate on <lang1->lang2> [peer_id] | /translate off [peer_id]"
+            )),
+        },
+        Some("/publish") => {
+            let endpoints: Vec<String> = parts[1..].iter().map(|s| s.to_string()).collect();
+            if endpoints.is_empty() {
+                return Err(anyhow!("Usage: /publish <host:port...>"));
+            }
+            Ok(Command::Publish { endpoints })
+        }
+        Some("/lookup") => parts
+            .get(1)
+            .and_then(|s| Uuid::parse_str(s).ok())
+            .map(|user_id| Command::Lookup { user_id })
+            .ok_or_else(|| anyhow!("Usage: /lookup <user_id>")),
+        Some("/dnd") => Ok(Command::Dnd { enabled: parse_on_off(parts.get(1).copied()) }),
+        Some("/readonly") => Ok(Command::ReadOnly { enabled: parse_on_off(parts.get(1).copied()) }),
+        Some("/stats") => Ok(Command::Stats),
+        Some("/all") => {
+            let content = parts[1.min(parts.len())..].join(" ");
+            if content.is_empty() {
+                return Err(anyhow!("Usage: /all <msg>"));
+            }
+            Ok(Command::Broadcast { content })
+        }
+        Some("/outbox") => Ok(Command::Outbox),
+        Some("/retry") => parts
+            .get(1)
+            .and_then(|s| Uuid::parse_str(s).ok())
+            .map(|id| Command::OutboxRetry { id })
+            .ok_or_else(|| anyhow!("Usage: /retry <id>")),
+        Some("/discard") => parts
+            .get(1)
+            .and_then(|s| Uuid::parse_str(s).ok())
+            .map(|id| Command::OutboxDiscard { id })
+            .ok_or_else(|| anyhow!("Usage: /discard <id>")),
+        Some("/notify") => match parts.get(1).copied() {
+            Some("sound") => {
+                let value = parts
+                    .get(2)
+                    .ok_or_else(|| anyhow!("Usage: /notify sound <bell|none|path-to-audio-file>"))?;
+                let sound = if *value == "none" { None } else { Some(value.to_string()) };
+                Ok(Command::NotifySound { sound })
+            }
+            Some("peer") => {
+                let (peer_id, value) = match (parts.get(2), parts.get(3)) {
+                    (Some(peer_id), Some(value)) => (peer_id, value),
+                    _ => return Err(anyhow!(
+                        "Usage: /notify peer <peer_id> <bell|none|path-to-audio-file>"
+                    )),
+                };
+                Ok(Command::NotifyPeer { peer_id: peer_id.to_string(), sound: value.to_string() })
+            }
+            _ => Err(anyhow!(
+                "Usage: /notify sound <bell|none|path> | /notify peer <peer_id> <bell|none|path>"
+            )),
+        },
+        Some("/mute") => {
+            let peer_id = parts.get(1).ok_or_else(|| anyhow!("Usage: /mute <peer_id> <duration>"))?;
+            let duration_str = parts.get(2).ok_or_else(|| anyhow!("Usage: /mute <peer_id> <duration>"))?;
+            let duration = crate::notify::parse_duration(duration_str)?;
+            Ok(Command::Mute { peer_id: peer_id.to_string(), duration })
+        }
+        Some("/unmute") => parts
+            .get(1)
+            .map(|peer_id| Command::Unmute { peer_id: peer_id.to_string() })
+            .ok_or_else(|| anyhow!("Usage: /unmute <peer_id>")),
+        Some("/typing") => parts
+            .get(1)
+            .map(|peer_id| Command::Typing { peer_id: peer_id.to_string() })
+            .ok_or_else(|| anyhow!("Usage: /typing <peer_id>")),
+        Some("/file") => match parts.get(1).copied() {
+            Some("send") => {
+                let (peer_id, path) = match (parts.get(2), parts.get(3)) {
+                    (Some(peer_id), Some(path)) => (peer_id, path),
+                    _ => return Err(anyhow!("Usage: /file send <peer_id> <path>")),
+                };
+                Ok(Command::FileSend { peer_id: peer_id.to_string(), path: path.to_string() })
+            }
+            Some("accept") => {
+                let (transfer_id, dest_path) = match (parts.get(2), parts.get(3)) {
+                    (Some(transfer_id), Some(dest_path)) => (transfer_id, dest_path),
+                    _ => return Err(anyhow!("Usage: /file accept <transfer_id> <dest_path>")),
+                };
+                let transfer_id = Uuid::parse_str(transfer_id)
+                    .map_err(|_| anyhow!("Usage: /file accept <transfer_id> <dest_path>"))?;
+                Ok(Command::FileAccept { transfer_id, dest_path: dest_path.to_string() })
+            }
+            Some("reject") => {
+                let transfer_id = parts
+                    .get(2)
+                    .and_then(|s| Uuid::parse_str(s).ok())
+                    .ok_or_else(|| anyhow!("Usage: /file reject <transfer_id>"))?;
+                Ok(Command::FileReject { transfer_id })
+            }
+            _ => Err(anyhow!(
+                "Usage: /file send <peer_id> <path> | /file accept <transfer_id> <dest_path> | /file reject <transfer_id>"
+            )),
+        },
+        Some("/read") => parts
+            .get(1)
+            .map(|peer_id| Command::MarkRead { peer_id: peer_id.to_string() })
+            .ok_or_else(|| anyhow!("Usage: /read <peer_id>")),
+        Some("/edit") => {
+            let message_id = parts
+                .get(1)
+                .and_then(|s| Uuid::parse_str(s).ok())
+                .ok_or_else(|| anyhow!("Usage: /edit <message_id> <text>"))?;
+            let text = parts[2.min(parts.len())..].join(" ");
+            if text.is_empty() {
+                return Err(anyhow!("Usage: /edit <message_id> <text>"));
+            }
+            Ok(Command::EditMessage { message_id, text })
+        }
+        Some("/retract") => parts
+            .get(1)
+            .and_then(|s| Uuid::parse_str(s).ok())
+            .map(|message_id| Command::RetractMessage { message_id })
+            .ok_or_else(|| anyhow!("Usage: /retract <message_id>")),
+        Some("/react") => {
+            let (message_id, emoji) = match (parts.get(1), parts.get(2)) {
+                (Some(message_id), Some(emoji)) => (message_id, emoji),
+                _ => return Err(anyhow!("Usage: /react <message_id> <emoji>")),
+            };
+            let message_id = Uuid::parse_str(message_id).map_err(|_| anyhow!("Usage: /react <message_id> <emoji>"))?;
+            Ok(Command::React { message_id, emoji: emoji.to_string() })
+        }
+        Some("/unreact") => {
+            let (message_id, emoji) = match (parts.get(1), parts.get(2)) {
+                (Some(message_id), Some(emoji)) => (message_id, emoji),
+                _ => return Err(anyhow!("Usage: /unreact <message_id> <emoji>")),
+            };
+            let message_id = Uuid::parse_str(message_id).map_err(|_| anyhow!("Usage: /unreact <message_id> <emoji>"))?;
+            Ok(Command::Unreact { message_id, emoji: emoji.to_string() })
+        }
+        Some("/schedule") => {
+            let delay_str = parts.get(1).ok_or_else(|| anyhow!("Usage: /schedule <2h|30m|1d|45s> <msg>"))?;
+            let delay = crate::notify::parse_duration(delay_str)?;
+            let content = parts[2.min(parts.len())..].join(" ");
+            if content.is_empty() {
+                return Err(anyhow!("Usage: /schedule <2h|30m|1d|45s> <msg>"));
+            }
+            Ok(Command::Schedule { delay, content })
+        }
+        Some("/scheduled") => Ok(Command::Scheduled),
+        Some("/unschedule") => parts
+            .get(1)
+            .and_then(|s| Uuid::parse_str(s).ok())
+            .map(|id| Command::Unschedule { id })
+            .ok_or_else(|| anyhow!("Usage: /unschedule <id>")),
+        Some("/md") => {
+            let content = parts[1.min(parts.len())..].join(" ");
+            if content.is_empty() {
+                return Err(anyhow!("Usage: /md <text>"));
+            }
+            Ok(Command::Markdown { content })
+        }
+        Some("/code") => {
+            let lang = parts.get(1).ok_or_else(|| anyhow!("Usage: /code <lang> <code>"))?;
+            let text = parts[2.min(parts.len())..].join(" ");
+            if text.is_empty() {
+                return Err(anyhow!("Usage: /code <lang> <code>"));
+            }
+            Ok(Command::Code { lang: lang.to_string(), text })
+        }
+        Some("/image") => {
+            let (peer_id, path) = match (parts.get(1), parts.get(2)) {
+                (Some(peer_id), Some(path)) => (peer_id, path),
+                _ => return Err(anyhow!("Usage: /image <peer_id> <path>")),
+            };
+            Ok(Command::SendImage { peer_id: peer_id.to_string(), path: path.to_string() })
+        }
+        Some("/forward") => {
+            let (message_id, peer_id) = match (parts.get(1), parts.get(2)) {
+                (Some(message_id), Some(peer_id)) => (message_id, peer_id),
+                _ => return Err(anyhow!("Usage: /forward <message_id> <peer_id>")),
+            };
+            let message_id =
+                Uuid::parse_str(message_id).map_err(|_| anyhow!("Usage: /forward <message_id> <peer_id>"))?;
+            Ok(Command::Forward { message_id, peer_id: peer_id.to_string() })
+        }
+        Some("/verify") => parts
+            .get(1)
+            .and_then(|s| Uuid::parse_str(s).ok())
+            .map(|message_id| Command::Verify { message_id })
+            .ok_or_else(|| anyhow!("Usage: /verify <message_id>")),
+        Some("/loglevel") => Ok(Command::LogLevel { level: parts.get(1).map(|s| s.to_string()) }),
+        Some("/quit") | Some("/exit") => Ok(Command::Quit),
+        _ => Err(anyhow!("Unknown command: {}", input)),
+    }
+}
+
+/// The outcome of running a `Command`, as structured data rather than a
+/// preformatted string - so a plain-text CLI, a future TUI, the napi
+/// bindings, and any gateway can each render it in whatever way fits that
+/// front-end, instead of every front-end re-parsing another front-end's
+/// display strings.
+#[derive(Debug, Clone)]
+pub enum CommandResult {
+    Help(&'static [(&'static str, &'static str)]),
+    Connected { address: String },
+    /// Paired with each peer's unread count and, if the user has added one,
+    /// its contact roster label - see `SessionManager::unread_count`/`contact_label`.
+    PeerList(Vec<(Peer, usize, Option<String>)>),
+    ConversationList(Vec<Conversation>),
+    PeerCapabilities { peer_id: String, capabilities: Capabilities },
+    SessionInfo { session_id: String, port: u16, peer_count: usize, topic: Option<String>, stats: crate::stats::SessionStats },
+    Topic { topic: Option<String> },
+    HistoryPage(Vec<Message>),
+    Starred { message_id: Uuid },
+    Pinned { message_id: Uuid },
+    Unpinned { message_id: Uuid },
+    PinnedList(Vec<Message>),
+    TemplateSaved { name: String },
+    TemplateSent { name: String, content: String },
+    Archived { peer_id: String },
+    Unarchived { peer_id: String },
+    PendingList(Vec<Peer>),
+    Accepted { peer_id: String },
+    Rejected { peer_id: String },
+    Translating { source_lang: String, target_lang: String },
+    TranslationDisabled,
+    Published,
+    LookupResult(DirectoryEntry),
+    Dnd { enabled: bool },
+    ReadOnly { enabled: bool },
+    Stats(NetworkStats),
+    Broadcast { delivered: usize },
+    OutboxList(Vec<OutboxEntry>),
+    OutboxRetried { id: Uuid },
+    OutboxDiscarded { id: Uuid },
+    NotifySoundSet { sound: Option<String> },
+    NotifyPeerSoundSet { peer_id: String, sound: String },
+    Muted { peer_id: String, until: chrono::DateTime<chrono::Utc> },
+    Unmuted { peer_id: String },
+    TypingSent { peer_id: String },
+    FileOffered { peer_id: String, transfer_id: Uuid },
+    FileAccepted { transfer_id: Uuid, dest_path: String },
+    FileRejected { transfer_id: Uuid },
+    ConversationRead { peer_id: String, count: usize },
+    MessageEdited { message_id: Uuid },
+    MessageRetracted { message_id: Uuid },
+    Reacted { message_id: Uuid, emoji: String },
+    Unreacted { message_id: Uuid, emoji: String },
+    Scheduled { id: Uuid, deliver_at: chrono::DateTime<chrono::Utc> },
+    ScheduledList(Vec<ScheduledMessage>),
+    Unscheduled { id: Uuid },
+    MarkdownSent,
+    CodeSent,
+    ImageSent { peer_id: String },
+    Forwarded { message_id: Uuid, peer_id: String },
+    Verified { message_id: Uuid, valid: bool },
+    LogLevel { level: String },
+    Quit,
+    Error(String),
+}
+
+/// Runs a parsed `Command` against `session_manager` and returns a
+/// `CommandResult` - the UI-agnostic counterpart to `parse`, so front-ends
+/// don't each re-implement how a command actually affects session state.
+pub async fn execute(command: Command, session_manager: &SessionManager) -> CommandResult {
+    fn err(e: anyhow::Error) -> CommandResult {
+        CommandResult::Error(e.to_string())
+    }
+
+    match command {
+        Command::Help => CommandResult::Help(help_lines()),
+        Command::Connect { address } => match session_manager.connect_to_peer(&address).await {
+            Ok(()) => CommandResult::Connected { address },
+            Err(e) => err(e),
+        },
+        Command::Peers => {
+            let peers = session_manager.get_active_peers().await;
+            let conversations = session_manager.conversations(false).await;
+            let peers_with_unread = peers
+                .into_iter()
+                .map(|peer| {
+                    let unread = conversations
+                        .iter()
+                        .find(|conversation| conversation.peer_id == peer.id.to_string())
+                        .map_or(0, |conversation| conversation.unread_count);
+                    let label = session_manager.contact_label(&peer.id.to_string());
+                    (peer, unread, label)
+                })
+                .collect();
+            CommandResult::PeerList(peers_with_unread)
+        }
+        Command::Conversations { include_archived } => {
+            CommandResult::ConversationList(session_manager.conversations(include_archived).await)
+        }
+        Command::Capabilities { peer_id } => {
+            let peers = session_manager.get_active_peers().await;
+            match peers.into_iter().find(|peer| peer.id.to_string() == peer_id) {
+                Some(peer) => CommandResult::PeerCapabilities { peer_id, capabilities: peer.capabilities },
+                None => CommandResult::Error(format!("Not connected to peer {}", peer_id)),
+            }
+        }
+        Command::Info => match session_manager.get_session_info().await {
+            Some((session_id, port, peer_count)) => CommandResult::SessionInfo {
+                session_id,
+                port,
+                peer_count,
+                topic: session_manager.get_topic().await,
+                stats: session_manager.session_stats().await.unwrap_or_default(),
+            },
+            None => CommandResult::Error("No active session".to_string()),
+        },
+        Command::Topic { text: Some(text) } => match session_manager.set_topic(text).await {
+            Ok(topic) => CommandResult::Topic { topic: Some(topic) },
+            Err(e) => err(e),
+        },
+        Command::Topic { text: None } => {
+            CommandResult::Topic { topic: session_manager.get_topic().await }
+        }
+        Command::History { limit } => {
+            CommandResult::HistoryPage(session_manager.list_recent_messages(limit).await)
+        }
+        Command::Star { message_id } => match session_manager.star_message(message_id).await {
+            Ok(()) => CommandResult::Starred { message_id },
+            Err(e) => err(e),
+        },
+        Command::Pin { message_id } => match session_manager.pin_message(message_id).await {
+            Ok(()) => CommandResult::Pinned { message_id },
+            Err(e) => err(e),
+        },
+        Command::Unpin { message_id } => match session_manager.unpin_message(message_id).await {
+            Ok(()) => CommandResult::Unpinned { message_id },
+            Err(e) => err(e),
+        },
+        Command::Pins { peer_id } => {
+            CommandResult::PinnedList(session_manager.get_pinned_messages(&peer_id).await)
+        }
+        Command::TemplateSave { name, content } => {
+            match session_manager.save_template(name.clone(), content).await {
+                Ok(()) => CommandResult::TemplateSaved { name },
+                Err(e) => err(e),
+            }
+        }
+        Command::SendTemplate { name } => match crate::config::load_template(&name) {
+            Ok(Some(content)) => match session_manager.send_message(content.clone(), None).await {
+                Ok(()) => CommandResult::TemplateSent { name, content },
+                Err(e) => err(e),
+            },
+            Ok(None) => CommandResult::Error(format!("No template named '{}'", name)),
+            Err(e) => err(e),
+        },
+        Command::Archive { peer_id } => match session_manager.archive_conversation(&peer_id).await {
+            Ok(()) => CommandResult::Archived { peer_id },
+            Err(e) => err(e),
+        },
+        Command::Unarchive { peer_id } => match session_manager.unarchive_conversation(&peer_id).await {
+            Ok(()) => CommandResult::Unarchived { peer_id },
+            Err(e) => err(e),
+        },
+        Command::Pending => CommandResult::PendingList(session_manager.pending_peers().await),
+        Command::Accept { peer_id } => match session_manager.accept_peer(&peer_id).await {
+            Ok(()) => CommandResult::Accepted { peer_id },
+            Err(e) => err(e),
+        },
+        Command::Reject { peer_id } => match session_manager.reject_peer(&peer_id).await {
+            Ok(()) => CommandResult::Rejected { peer_id },
+            Err(e) => err(e),
+        },
+        Command::TranslateOn { source_lang, target_lang, peer_id } => {
+            let translate_command = crate::config::load_config_cached()
+                .map(|c| c.translation_command)
+                .unwrap_or_else(|_| "trans".to_string());
+            let peer_ids = match peer_id {
+                Some(peer_id) => vec![peer_id],
+                None => session_manager
+                    .get_active_peers()
+                    .await
+                    .into_iter()
+                    .map(|p| p.id.to_string())
+                    .collect(),
+            };
+            if peer_ids.is_empty() {
+                return CommandResult::Error("No connected peers to translate".to_string());
+            }
+            for peer_id in peer_ids {
+                let hook = crate::translation::TranslationHook::new(
+                    source_lang.clone(),
+                    target_lang.clone(),
+                    translate_command.clone(),
+                );
+                if let Err(e) = session_manager.set_translation_hook(peer_id, hook).await {
+                    return err(e);
+                }
+            }
+            CommandResult::Translating { source_lang, target_lang }
+        }
+        Command::TranslateOff { peer_id } => {
+            let peer_ids = match peer_id {
+                Some(peer_id) => vec![peer_id],
+                None => session_manager
+                    .get_active_peers()
+                    .await
+                    .into_iter()
+                    .map(|p| p.id.to_string())
+                    .collect(),
+            };
+            for peer_id in peer_ids {
+                if let Err(e) = session_manager.disable_translation_hook(&peer_id).await {
+                    return err(e);
+                }
+            }
+            CommandResult::TranslationDisabled
+        }
+        Command::Publish { endpoints } => match session_manager.publish_to_directory(endpoints).await {
+            Ok(()) => CommandResult::Published,
+            Err(e) => err(e),
+        },
+        Command::Lookup { user_id } => match session_manager.lookup_in_directory(user_id).await {
+            Ok(entry) => CommandResult::LookupResult(entry),
+            Err(e) => err(e),
+        },
+        Command::Dnd { enabled: Some(enabled) } => {
+            session_manager.set_dnd(enabled).await;
+            CommandResult::Dnd { enabled }
+        }
+        Command::Dnd { enabled: None } => CommandResult::Dnd { enabled: session_manager.is_dnd().await },
+        Command::ReadOnly { enabled: Some(enabled) } => {
+            match session_manager.set_read_only(enabled).await {
+                Ok(()) => CommandResult::ReadOnly { enabled },
+                Err(e) => err(e),
+            }
+        }
+        Command::ReadOnly { enabled: None } => {
+            CommandResult::ReadOnly { enabled: session_manager.is_read_only().await }
+        }
+        Command::Stats => CommandResult::Stats(session_manager.get_stats().await),
+        Command::Broadcast { content } => match session_manager.broadcast_message(content).await {
+            Ok(delivered) => CommandResult::Broadcast { delivered },
+            Err(e) => err(e),
+        },
+        Command::Outbox => CommandResult::OutboxList(session_manager.list_outbox().await),
+        Command::OutboxRetry { id } => match session_manager.retry_outbox_entry(id).await {
+            Ok(()) => CommandResult::OutboxRetried { id },
+            Err(e) => err(e),
+        },
+        Command::OutboxDiscard { id } => match session_manager.discard_outbox_entry(id).await {
+            Ok(()) => CommandResult::OutboxDiscarded { id },
+            Err(e) => err(e),
+        },
+        Command::NotifySound { sound } => {
+            match session_manager.set_notification_sound(sound.clone()).await {
+                Ok(()) => CommandResult::NotifySoundSet { sound },
+                Err(e) => err(e),
+            }
+        }
+        Command::NotifyPeer { peer_id, sound } => {
+            match session_manager
+                .set_peer_notification_sound(peer_id.clone(), sound.clone())
+                .await
+            {
+                Ok(()) => CommandResult::NotifyPeerSoundSet { peer_id, sound },
+                Err(e) => err(e),
+            }
+        }
+        Command::Mute { peer_id, duration } => {
+            match session_manager.mute_conversation(peer_id.clone(), duration).await {
+                Ok(until) => CommandResult::Muted { peer_id, until },
+                Err(e) => err(e),
+            }
+        }
+        Command::Unmute { peer_id } => match session_manager.unmute_conversation(peer_id.clone()).await {
+            Ok(()) => CommandResult::Unmuted { peer_id },
+            Err(e) => err(e),
+        },
+        Command::Typing { peer_id } => match session_manager.send_typing_indicator(peer_id.clone()).await {
+            Ok(()) => CommandResult::TypingSent { peer_id },
+            Err(e) => err(e),
+        },
+        Command::FileSend { peer_id, path } => {
+            match session_manager.offer_file(&peer_id, std::path::Path::new(&path)).await {
+                Ok(transfer_id) => CommandResult::FileOffered { peer_id, transfer_id },
+                Err(e) => err(e),
+            }
+        }
+        Command::FileAccept { transfer_id, dest_path } => {
+            match session_manager
+                .accept_file(transfer_id, std::path::Path::new(&dest_path))
+                .await
+            {
+                Ok(dest_path) => CommandResult::FileAccepted { transfer_id, dest_path: dest_path.display().to_string() },
+                Err(e) => err(e),
+            }
+        }
+        Command::FileReject { transfer_id } => match session_manager.reject_file(transfer_id).await {
+            Ok(()) => CommandResult::FileRejected { transfer_id },
+            Err(e) => err(e),
+        },
+        Command::MarkRead { peer_id } => match session_manager.mark_conversation_read(&peer_id).await {
+            Ok(count) => CommandResult::ConversationRead { peer_id, count },
+            Err(e) => err(e),
+        },
+        Command::EditMessage { message_id, text } => match session_manager.edit_message(message_id, text).await {
+            Ok(()) => CommandResult::MessageEdited { message_id },
+            Err(e) => err(e),
+        },
+        Command::RetractMessage { message_id } => match session_manager.retract_message(message_id).await {
+            Ok(()) => CommandResult::MessageRetracted { message_id },
+            Err(e) => err(e),
+        },
+        Command::React { message_id, emoji } => {
+            match session_manager.add_reaction(message_id, emoji.clone()).await {
+                Ok(()) => CommandResult::Reacted { message_id, emoji },
+                Err(e) => err(e),
+            }
+        }
+        Command::Unreact { message_id, emoji } => {
+            match session_manager.remove_reaction(message_id, emoji.clone()).await {
+                Ok(()) => CommandResult::Unreacted { message_id, emoji },
+                Err(e) => err(e),
+            }
+        }
+        Command::Schedule { delay, content } => {
+            let deliver_at = chrono::Utc::now() + delay;
+            let id = session_manager.schedule_message(content, None, deliver_at).await;
+            CommandResult::Scheduled { id, deliver_at }
+        }
+        Command::Scheduled => CommandResult::ScheduledList(session_manager.list_schedule().await),
+        Command::Unschedule { id } => match session_manager.cancel_schedule(id).await {
+            Ok(()) => CommandResult::Unscheduled { id },
+            Err(e) => err(e),
+        },
+        Command::Markdown { content } => match session_manager.send_markdown(content, None).await {
+            Ok(()) => CommandResult::MarkdownSent,
+            Err(e) => err(e),
+        },
+        Command::Code { lang, text } => match session_manager.send_code(lang, text, None).await {
+            Ok(()) => CommandResult::CodeSent,
+            Err(e) => err(e),
+        },
+        Command::SendImage { peer_id, path } => {
+            match session_manager.send_image(peer_id.clone(), std::path::Path::new(&path)).await {
+                Ok(()) => CommandResult::ImageSent { peer_id },
+                Err(e) => err(e),
+            }
+        }
+        Command::Forward { message_id, peer_id } => {
+            match session_manager.forward_message(message_id, peer_id.clone()).await {
+                Ok(()) => CommandResult::Forwarded { message_id, peer_id },
+                Err(e) => err(e),
+            }
+        }
+        Command::Verify { message_id } => match session_manager.verify_message(message_id).await {
+            Ok(valid) => CommandResult::Verified { message_id, valid },
+            Err(e) => err(e),
+        },
+        Command::LogLevel { level: Some(level) } => match crate::logging::set_level(&level) {
+            Ok(filter) => CommandResult::LogLevel { level: filter.to_string() },
+            Err(e) => err(e),
+        },
+        Command::LogLevel { level: None } => CommandResult::LogLevel {
+            level: crate::logging::current_level().to_string(),
+        },
+        Command::Quit => CommandResult::Quit,
+    }
+}
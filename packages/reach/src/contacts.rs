@@ -0,0 +1,116 @@
+//! A local contact roster, persisted as `contacts.json` under the config dir,
+//! mirroring `addressbook::AddressBook`'s storage shape but a different
+//! concern: `AddressBook` remembers addresses so `connect_known_peers` can
+//! redial a peer, while a `Contact` is a human-maintained label (name, email,
+//! notes, whether the user has verified the peer's identity out of band) that
+//! sessions consult to show something friendlier than a raw peer id.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// A roster entry for one peer, keyed by `peer_id` - see `ContactBook`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Contact {
+    pub peer_id: String,
+    pub display_name: String,
+    pub email: Option<String>,
+    pub public_key: Option<String>,
+    pub notes: Option<String>,
+    /// Whether the user has confirmed this peer's public key out of band
+    /// (in person, over a second channel, etc.) rather than just trusting
+    /// whatever the handshake presented.
+    #[serde(default)]
+    pub verified: bool,
+}
+
+/// A local registry of contacts, stored as `contacts.json` under the config
+/// dir - mirrors `addressbook::AddressBook`.
+pub struct ContactBook {
+    file: PathBuf,
+}
+
+impl ContactBook {
+    pub fn new() -> Result<Self> {
+        let config_dir = crate::config::get_config_dir()?;
+        Ok(ContactBook { file: config_dir.join("contacts.json") })
+    }
+
+    /// Adds or updates the contact for `peer_id`, returning the stored entry.
+    pub fn add(
+        &self,
+        peer_id: &str,
+        display_name: &str,
+        email: Option<String>,
+        public_key: Option<String>,
+        notes: Option<String>,
+    ) -> Result<Contact> {
+        let mut entries = self.load()?;
+        let contact = Contact {
+            peer_id: peer_id.to_string(),
+            display_name: display_name.to_string(),
+            email,
+            public_key,
+            notes,
+            verified: entries.get(peer_id).is_some_and(|existing| existing.verified),
+        };
+        entries.insert(peer_id.to_string(), contact.clone());
+        self.save(&entries)?;
+        Ok(contact)
+    }
+
+    /// Renames an existing contact, leaving every other field untouched.
+    pub fn rename(&self, peer_id: &str, display_name: String) -> Result<Contact> {
+        let mut entries = self.load()?;
+        let contact = entries
+            .get_mut(peer_id)
+            .ok_or_else(|| anyhow::anyhow!("No contact for peer '{}'", peer_id))?;
+        contact.display_name = display_name;
+        let contact = contact.clone();
+        self.save(&entries)?;
+        Ok(contact)
+    }
+
+    pub fn remove(&self, peer_id: &str) -> Result<()> {
+        let mut entries = self.load()?;
+        entries.remove(peer_id);
+        self.save(&entries)
+    }
+
+    pub fn get(&self, peer_id: &str) -> Result<Option<Contact>> {
+        Ok(self.load()?.remove(peer_id))
+    }
+
+    pub fn list(&self) -> Result<Vec<Contact>> {
+        Ok(self.load()?.into_values().collect())
+    }
+
+    /// Merges `contacts` into the roster, overwriting any existing entry
+    /// with the same peer id - see `CliOperations::import_identity`.
+    pub fn import_all(&self, contacts: Vec<Contact>) -> Result<usize> {
+        let mut entries = self.load()?;
+        let count = contacts.len();
+        for contact in contacts {
+            entries.insert(contact.peer_id.clone(), contact);
+        }
+        self.save(&entries)?;
+        Ok(count)
+    }
+
+    fn load(&self) -> Result<HashMap<String, Contact>> {
+        if !self.file.exists() {
+            return Ok(HashMap::new());
+        }
+
+        let contents = fs::read_to_string(&self.file)?;
+        Ok(serde_json::from_str(&contents).unwrap_or_default())
+    }
+
+    fn save(&self, entries: &HashMap<String, Contact>) -> Result<()> {
+        let contents = serde_json::to_string_pretty(entries)?;
+        fs::write(&self.file, contents)?;
+        Ok(())
+    }
+}
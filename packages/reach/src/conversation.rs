@@ -0,0 +1,28 @@
+//! A per-peer view over `ChatSession::message_history` - see
+//! `ChatSession::conversations`/`SessionManager::conversations`. Not a
+//! separate store: it's assembled on demand from the flat history plus the
+//! draft/settings maps `ChatSession` keeps per peer, so nothing about
+//! `message_history` itself (storage, export, search) has to change.
+
+use serde::{Deserialize, Serialize};
+
+use crate::Message;
+
+/// Per-conversation preferences that aren't part of a `Peer`'s connection
+/// state - see `ChatSession::conversation_settings`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConversationSettings {
+    pub muted: bool,
+}
+
+/// One peer's slice of a session: its messages, how many are unread, the
+/// draft in progress (if any), and its settings - see
+/// `SessionManager::conversations`.
+#[derive(Debug, Clone)]
+pub struct Conversation {
+    pub peer_id: String,
+    pub history: Vec<Message>,
+    pub unread_count: usize,
+    pub draft: String,
+    pub settings: ConversationSettings,
+}
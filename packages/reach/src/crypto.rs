@@ -21,7 +21,7 @@ impl KeyPair {
 
         // For simplicity, derive public key from private key using SHA256
         let mut hasher = Sha256::new();
-        hasher.update(&private_key);
+        hasher.update(private_key);
         let public_key: [u8; 32] = hasher.finalize().into();
 
         KeyPair {
@@ -32,7 +32,7 @@ impl KeyPair {
 
     pub fn from_private_key(private_key: [u8; 32]) -> Self {
         let mut hasher = Sha256::new();
-        hasher.update(&private_key);
+        hasher.update(private_key);
         let public_key: [u8; 32] = hasher.finalize().into();
 
         KeyPair {
@@ -60,6 +60,12 @@ impl KeyPair {
 
 pub struct CryptoEngine;
 
+impl Default for CryptoEngine {
+    fn default() -> Self {
+        CryptoEngine
+    }
+}
+
 impl CryptoEngine {
     pub fn new() -> Self {
         CryptoEngine
@@ -69,6 +75,14 @@ impl CryptoEngine {
         KeyPair::generate()
     }
 
+    /// A fresh random AES-256-GCM key, unrelated to any peer's shared secret
+    /// - see `network::NetworkManager`'s sender-key broadcast encryption.
+    pub fn generate_symmetric_key() -> [u8; 32] {
+        let mut key = [0u8; 32];
+        OsRng.fill_bytes(&mut key);
+        key
+    }
+
     pub fn generate_shared_secret(our_private: &[u8; 32], their_public: &[u8; 32]) -> [u8; 32] {
         // Simple shared secret generation using XOR and hash
         // In production, use proper ECDH
@@ -77,7 +91,7 @@ impl CryptoEngine {
         combined[32..].copy_from_slice(their_public);
 
         let mut hasher = Sha256::new();
-        hasher.update(&combined);
+        hasher.update(combined);
         hasher.finalize().into()
     }
 
@@ -128,4 +142,15 @@ impl CryptoEngine {
         hasher.update(password.as_bytes());
         hex::encode(hasher.finalize())
     }
+
+    /// Derives an AES-256-GCM key for encrypting data at rest (see
+    /// `storage::MessageStore`) from the identity's private key, rather than
+    /// reusing that key directly - the domain-separation label keeps this key
+    /// useless for anything else the private key is used for.
+    pub fn derive_storage_key(identity_private_key: &[u8; 32]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(b"rustalk-message-store-key-v1");
+        hasher.update(identity_private_key);
+        hasher.finalize().into()
+    }
 }
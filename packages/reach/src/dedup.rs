@@ -0,0 +1,63 @@
+use std::collections::{HashMap, VecDeque};
+
+use uuid::Uuid;
+
+/// How many recent message IDs to remember per peer before the oldest is
+/// evicted - see `Dedup`. Bounds memory per connection instead of growing
+/// forever for a long-lived, chatty peer.
+const PER_PEER_CAPACITY: usize = 256;
+
+/// Tracks recently seen message IDs per peer so a message retransmitted
+/// after a dropped ack, or replayed by a reconnect catch-up, doesn't get
+/// added to history, receipts, or notifications twice - see
+/// `network::NetworkManager::spawn_reader`.
+///
+/// Kept in memory only, bounded per peer rather than persisted to disk:
+/// duplicate delivery in this codebase arises from retransmission within a
+/// single reconnect window, not across a full process restart, so there's
+/// nothing worth surviving a restart for.
+#[derive(Debug, Default)]
+pub struct Dedup {
+    seen: HashMap<Uuid, VecDeque<Uuid>>,
+}
+
+impl Dedup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records `message_id` as seen for `peer_id` and returns whether it had
+    /// already been seen - i.e. whether the caller should drop it.
+    pub fn is_duplicate(&mut self, peer_id: Uuid, message_id: Uuid) -> bool {
+        let ids = self.seen.entry(peer_id).or_default();
+        if ids.contains(&message_id) {
+            return true;
+        }
+
+        ids.push_back(message_id);
+        if ids.len() > PER_PEER_CAPACITY {
+            ids.pop_front();
+        }
+        false
+    }
+
+    /// Carries a peer's seen-id history from `from` to `to` - see
+    /// `network::NetworkManager::handle_incoming_connection`'s same-public-key
+    /// migration handling, where a peer reconnects under a new `peer_id`. The
+    /// reconnect itself can still be carrying in-flight retransmits from the
+    /// old connection; forgetting what was already seen under the old id
+    /// would let those through as if they were new.
+    pub fn migrate(&mut self, from: Uuid, to: Uuid) {
+        if let Some(old_ids) = self.seen.remove(&from) {
+            let new_ids = self.seen.entry(to).or_default();
+            for id in old_ids {
+                if !new_ids.contains(&id) {
+                    new_ids.push_back(id);
+                }
+            }
+            while new_ids.len() > PER_PEER_CAPACITY {
+                new_ids.pop_front();
+            }
+        }
+    }
+}
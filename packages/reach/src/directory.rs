@@ -0,0 +1,321 @@
+use anyhow::{Result, anyhow};
+use base64::{Engine as _, engine::general_purpose};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use log::{debug, info, warn};
+use rand::{RngCore, rngs::OsRng};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+/// Where a published entry claims a user can be reached, and the key that
+/// signed it. Unlike the peer-to-peer handshake's shared secret (see
+/// `crypto::CryptoEngine::generate_shared_secret`), a directory lookup has no
+/// prior relationship with the publisher to derive a shared secret from -
+/// verifying it needs a real public-key signature, so this is the one place
+/// in the codebase using `ed25519-dalek` instead of the crate's usual
+/// "simplified" crypto.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectoryEntry {
+    pub user_id: Uuid,
+    pub endpoints: Vec<String>,
+    /// Base64-encoded Ed25519 verifying key the entry was signed with.
+    pub signing_key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SignedEntry {
+    entry: DirectoryEntry,
+    /// Base64-encoded signature over the JSON-serialized `entry`.
+    signature: String,
+}
+
+/// Generates a fresh Ed25519 keypair for publishing to a directory server,
+/// base64-encoded for storage in `Config::directory_signing_key`.
+pub fn generate_signing_key() -> String {
+    let mut seed = [0u8; 32];
+    OsRng.fill_bytes(&mut seed);
+    let signing_key = SigningKey::from_bytes(&seed);
+    general_purpose::STANDARD.encode(signing_key.to_bytes())
+}
+
+fn decode_signing_key(encoded: &str) -> Result<SigningKey> {
+    let bytes = general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|e| anyhow!("Failed to decode directory signing key: {}", e))?;
+    let bytes: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| anyhow!("Directory signing key must be 32 bytes"))?;
+    Ok(SigningKey::from_bytes(&bytes))
+}
+
+fn decode_verifying_key(encoded: &str) -> Result<VerifyingKey> {
+    let bytes = general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|e| anyhow!("Failed to decode directory signing key: {}", e))?;
+    let bytes: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| anyhow!("Directory signing key must be 32 bytes"))?;
+    VerifyingKey::from_bytes(&bytes).map_err(|e| anyhow!("Invalid directory signing key: {}", e))
+}
+
+/// Publishes our endpoints to a self-hostable directory server at
+/// `directory_address` (`host:port`), signed with `signing_key_base64` (see
+/// `generate_signing_key`) so a later `lookup` can verify it came from us -
+/// see `Config::directory_address`.
+pub async fn publish(
+    directory_address: &str,
+    signing_key_base64: &str,
+    user_id: Uuid,
+    endpoints: Vec<String>,
+) -> Result<()> {
+    let signing_key = decode_signing_key(signing_key_base64)?;
+
+    let entry = DirectoryEntry {
+        user_id,
+        endpoints,
+        signing_key: general_purpose::STANDARD.encode(signing_key.verifying_key().to_bytes()),
+    };
+
+    let payload = serde_json::to_vec(&entry)
+        .map_err(|e| anyhow!("Failed to serialize directory entry: {}", e))?;
+    let signature = signing_key.sign(&payload);
+
+    let signed = SignedEntry {
+        entry,
+        signature: general_purpose::STANDARD.encode(signature.to_bytes()),
+    };
+
+    http_post(directory_address, "/v1/publish", &signed).await
+}
+
+/// Looks up `user_id`'s published endpoints on a directory server at
+/// `directory_address`, rejecting the response if its signature doesn't
+/// verify against the verifying key it claims - see `Config::directory_address`.
+pub async fn lookup(directory_address: &str, user_id: Uuid) -> Result<DirectoryEntry> {
+    let path = format!("/v1/lookup/{}", user_id);
+    let signed: SignedEntry = http_get(directory_address, &path).await?;
+
+    let verifying_key = decode_verifying_key(&signed.entry.signing_key)?;
+    let signature_bytes = general_purpose::STANDARD
+        .decode(&signed.signature)
+        .map_err(|e| anyhow!("Failed to decode directory entry signature: {}", e))?;
+    let signature_bytes: [u8; 64] = signature_bytes
+        .try_into()
+        .map_err(|_| anyhow!("Directory entry signature must be 64 bytes"))?;
+    let signature = Signature::from_bytes(&signature_bytes);
+
+    let payload = serde_json::to_vec(&signed.entry)
+        .map_err(|e| anyhow!("Failed to serialize directory entry: {}", e))?;
+    verifying_key
+        .verify(&payload, &signature)
+        .map_err(|_| anyhow!("Directory entry for {} failed signature verification", user_id))?;
+
+    Ok(signed.entry)
+}
+
+/// Minimal HTTP/1.1 client used to talk to the directory server: plaintext,
+/// one request per connection, `Content-Length`-framed JSON bodies only - no
+/// TLS, chunked transfer, or redirects. This is enough since the directory
+/// wire protocol is one this project defines itself rather than needing to
+/// interoperate with arbitrary HTTP servers, similar in spirit to
+/// `quic::QuicTransport` being a prototype rather than real QUIC.
+async fn http_request(
+    address: &str,
+    method: &str,
+    path: &str,
+    body: Option<&[u8]>,
+) -> Result<Vec<u8>> {
+    let mut stream = TcpStream::connect(address).await?;
+
+    let mut request = format!(
+        "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n",
+        method, path, address
+    );
+    if let Some(body) = body {
+        request.push_str("Content-Type: application/json\r\n");
+        request.push_str(&format!("Content-Length: {}\r\n", body.len()));
+    }
+    request.push_str("\r\n");
+
+    stream.write_all(request.as_bytes()).await?;
+    if let Some(body) = body {
+        stream.write_all(body).await?;
+    }
+    stream.flush().await?;
+    // Half-close so a server that reads the request to EOF (see
+    // `DirectoryServer::handle_client`) sees where it ends.
+    stream.shutdown().await?;
+
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).await?;
+
+    let header_end = find_subslice(&response, b"\r\n\r\n")
+        .ok_or_else(|| anyhow!("Directory server at {} sent a malformed HTTP response", address))?;
+    let header_text = String::from_utf8_lossy(&response[..header_end]);
+    let mut lines = header_text.split("\r\n");
+
+    let status_line = lines
+        .next()
+        .ok_or_else(|| anyhow!("Directory server at {} sent an empty response", address))?;
+    let status_code: u16 = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| anyhow!("Directory server at {} sent a malformed status line", address))?;
+
+    let body_start = header_end + 4;
+    if status_code != 200 {
+        return Err(anyhow!(
+            "Directory server at {} returned HTTP {}",
+            address,
+            status_code
+        ));
+    }
+
+    Ok(response[body_start..].to_vec())
+}
+
+async fn http_post<T: Serialize>(address: &str, path: &str, body: &T) -> Result<()> {
+    let payload =
+        serde_json::to_vec(body).map_err(|e| anyhow!("Failed to serialize request body: {}", e))?;
+    http_request(address, "POST", path, Some(&payload)).await?;
+    Ok(())
+}
+
+async fn http_get<T: serde::de::DeserializeOwned>(address: &str, path: &str) -> Result<T> {
+    let body = http_request(address, "GET", path, None).await?;
+    serde_json::from_slice(&body).map_err(|e| anyhow!("Failed to parse directory response: {}", e))
+}
+
+fn find_subslice(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Rendezvous/directory server for `rus rendezvous` mode: the listening half
+/// of `publish`/`lookup` above. Entries are held in memory only - restarting
+/// the server loses them, and publishers are expected to re-publish
+/// periodically rather than this codebase implementing lease expiry.
+pub struct DirectoryServer {
+    port: u16,
+}
+
+impl DirectoryServer {
+    pub fn new(port: u16) -> Self {
+        DirectoryServer { port }
+    }
+
+    pub async fn run(self) -> Result<()> {
+        let addr = format!("0.0.0.0:{}", self.port);
+        let listener = TcpListener::bind(&addr).await?;
+        info!("Rendezvous/directory server listening on {}", addr);
+
+        let entries: Arc<RwLock<HashMap<Uuid, SignedEntry>>> = Arc::new(RwLock::new(HashMap::new()));
+
+        loop {
+            let (stream, peer_addr) = listener.accept().await?;
+            let entries = entries.clone();
+            tokio::spawn(async move {
+                if let Err(e) = Self::handle_client(stream, entries).await {
+                    debug!("Rendezvous client {} failed: {}", peer_addr, e);
+                }
+            });
+        }
+    }
+
+    async fn handle_client(mut stream: TcpStream, entries: Arc<RwLock<HashMap<Uuid, SignedEntry>>>) -> Result<()> {
+        let mut buf = Vec::new();
+        stream.read_to_end(&mut buf).await?;
+
+        let header_end = find_subslice(&buf, b"\r\n\r\n").ok_or_else(|| anyhow!("Malformed HTTP request"))?;
+        let header_text = String::from_utf8_lossy(&buf[..header_end]).to_string();
+        let mut lines = header_text.split("\r\n");
+        let request_line = lines.next().ok_or_else(|| anyhow!("Empty HTTP request"))?;
+        let mut parts = request_line.split_whitespace();
+        let method = parts.next().ok_or_else(|| anyhow!("Malformed request line"))?;
+        let path = parts.next().ok_or_else(|| anyhow!("Malformed request line"))?;
+        let body = &buf[header_end + 4..];
+
+        let response = match (method, path.strip_prefix("/v1/lookup/"), path) {
+            (&_, Some(user_id), _) if method == "GET" => match Uuid::parse_str(user_id) {
+                Ok(user_id) => match entries.read().await.get(&user_id) {
+                    Some(entry) => json_response(200, entry),
+                    None => text_response(404, "Not found"),
+                },
+                Err(_) => text_response(400, "Invalid user id"),
+            },
+            ("POST", _, "/v1/publish") => match serde_json::from_slice::<SignedEntry>(body) {
+                Ok(signed) => match Self::verify(&signed) {
+                    Ok(()) => {
+                        let user_id = signed.entry.user_id;
+                        entries.write().await.insert(user_id, signed);
+                        text_response(200, "OK")
+                    }
+                    Err(e) => {
+                        warn!("Rejected directory publish: {}", e);
+                        text_response(403, "Signature verification failed")
+                    }
+                },
+                Err(_) => text_response(400, "Invalid publish body"),
+            },
+            _ => text_response(404, "Not found"),
+        };
+
+        stream.write_all(&response).await?;
+        stream.flush().await?;
+        Ok(())
+    }
+
+    /// Verifies a publish's signature against the verifying key it carries,
+    /// the same check `lookup` applies to what it gets back - see `lookup`.
+    fn verify(signed: &SignedEntry) -> Result<()> {
+        let verifying_key = decode_verifying_key(&signed.entry.signing_key)?;
+        let signature_bytes = general_purpose::STANDARD
+            .decode(&signed.signature)
+            .map_err(|e| anyhow!("Failed to decode signature: {}", e))?;
+        let signature_bytes: [u8; 64] = signature_bytes
+            .try_into()
+            .map_err(|_| anyhow!("Signature must be 64 bytes"))?;
+        let signature = Signature::from_bytes(&signature_bytes);
+
+        let payload = serde_json::to_vec(&signed.entry)
+            .map_err(|e| anyhow!("Failed to serialize entry: {}", e))?;
+        verifying_key
+            .verify(&payload, &signature)
+            .map_err(|_| anyhow!("Signature does not match entry"))
+    }
+}
+
+fn json_response<T: Serialize>(status: u16, body: &T) -> Vec<u8> {
+    let payload = serde_json::to_vec(body).unwrap_or_default();
+    http_response(status, "application/json", &payload)
+}
+
+fn text_response(status: u16, message: &str) -> Vec<u8> {
+    http_response(status, "text/plain", message.as_bytes())
+}
+
+fn http_response(status: u16, content_type: &str, body: &[u8]) -> Vec<u8> {
+    let status_text = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        _ => "Error",
+    };
+    let mut response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        status_text,
+        content_type,
+        body.len()
+    )
+    .into_bytes();
+    response.extend_from_slice(body);
+    response
+}
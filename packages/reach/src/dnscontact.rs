@@ -0,0 +1,68 @@
+use anyhow::{Result, anyhow};
+use hickory_resolver::TokioResolver;
+use hickory_resolver::proto::rr::RData;
+
+/// What `resolve_contact` finds published under a domain for a `/connect
+/// alice@example.com`-style handle.
+pub struct DnsContact {
+    /// `host:port` pulled from the `_rustalk._tcp` SRV record's target and port.
+    pub address: String,
+    /// The peer's public key, if a TXT record carries a `pubkey=<...>` entry.
+    pub public_key: Option<String>,
+}
+
+/// Resolves a `user@domain` (or bare `domain`) contact handle via DNS:
+/// a `_rustalk._tcp.<domain>` SRV record gives the host and port to dial,
+/// and a TXT record under the same name may carry `pubkey=<base64 key>` for
+/// pinning the handshake. This gives anyone who controls a domain a stable
+/// connect handle without relying on a central directory - see `/connect`.
+/// The local part before `@` is accepted for a mail-style handle but isn't
+/// otherwise used: the SRV/TXT records describe one peer per domain.
+pub async fn resolve_contact(handle: &str) -> Result<DnsContact> {
+    let domain = handle.rsplit_once('@').map(|(_, domain)| domain).unwrap_or(handle);
+    if domain.is_empty() {
+        return Err(anyhow!("'{}' has no domain to look up", handle));
+    }
+
+    let resolver = TokioResolver::builder_tokio()
+        .map_err(|e| anyhow!("Could not set up DNS resolver: {}", e))?
+        .build()
+        .map_err(|e| anyhow!("Could not set up DNS resolver: {}", e))?;
+
+    let srv_name = format!("_rustalk._tcp.{}", domain);
+    let srv_lookup = resolver
+        .srv_lookup(&srv_name)
+        .await
+        .map_err(|e| anyhow!("No {} SRV record found: {}", srv_name, e))?;
+
+    let srv = srv_lookup
+        .answers()
+        .iter()
+        .find_map(|record| match &record.data {
+            RData::SRV(srv) => Some(srv.clone()),
+            _ => None,
+        })
+        .ok_or_else(|| anyhow!("{} lookup returned no SRV records", srv_name))?;
+
+    let address = format!("{}:{}", srv.target.to_utf8().trim_end_matches('.'), srv.port);
+
+    let public_key = resolver
+        .txt_lookup(&srv_name)
+        .await
+        .ok()
+        .and_then(|lookup| {
+            lookup.answers().iter().find_map(|record| match &record.data {
+                RData::TXT(txt) => {
+                    let joined: String = txt
+                        .txt_data
+                        .iter()
+                        .map(|chunk| String::from_utf8_lossy(chunk))
+                        .collect();
+                    joined.strip_prefix("pubkey=").map(|key| key.to_string())
+                }
+                _ => None,
+            })
+        });
+
+    Ok(DnsContact { address, public_key })
+}
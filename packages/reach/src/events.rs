@@ -0,0 +1,33 @@
+//! Typed notifications for session activity - see `SessionManager::subscribe`.
+//! A `broadcast` channel rather than the `mpsc` `message_sender`/
+//! `message_receiver` pair already on `SessionManager`, since multiple
+//! independent subscribers (the CLI, a future TUI, napi bindings) each need
+//! their own copy of every event instead of racing to drain one queue.
+
+use uuid::Uuid;
+
+use crate::{Message, Peer};
+
+/// How many events a slow subscriber can fall behind before `recv` reports a
+/// `Lagged` gap - generous enough that a console render between messages
+/// won't trip it, without letting one forgotten subscriber hold unbounded
+/// history in memory.
+pub const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+#[derive(Debug, Clone)]
+pub enum SessionEvent {
+    MessageReceived(Box<Message>),
+    PeerConnected(Box<Peer>),
+    PeerDisconnected(String),
+    DeliveryUpdated { message_id: Uuid, read_at: chrono::DateTime<chrono::Utc> },
+    /// A message we sent was acknowledged by a `MessageType::DeliveryAck` -
+    /// see `ChatSession::apply_delivery_ack` and `Message::delivery_status`.
+    /// Distinct from `DeliveryUpdated`, which is about `/read`, an opt-in,
+    /// user-visible receipt rather than this unconditional transport-level one.
+    MessageDelivered { message_id: Uuid },
+    /// The config file changed on disk and was picked up without a restart -
+    /// see `SessionManager::start_config_watch_loop`. `changed_fields` names
+    /// which of the hot-reloadable settings (`log_level`, `max_peers`,
+    /// `auto_accept_connections`) actually differed from the previous load.
+    ConfigReloaded { changed_fields: Vec<String> },
+}
@@ -0,0 +1,171 @@
+//! Exports message history to a file for offline review or migrating to
+//! another machine - see `SessionManager::export_history`/`import_history`
+//! and `rus history export`/`rus history import`. Reads from
+//! `storage::MessageStore` directly, so export works whether or not a chat
+//! session is currently running.
+//!
+//! Only the JSON format round-trips: it's a plain `Vec<Message>`, so
+//! `read_export` can hand it straight back to `import_history`. CSV and HTML
+//! are flattened, human-readable views meant for spreadsheets and browsers,
+//! not re-import.
+
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use uuid::Uuid;
+
+use crate::Message;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+    Html,
+}
+
+impl ExportFormat {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(ExportFormat::Json),
+            "csv" => Ok(ExportFormat::Csv),
+            "html" => Ok(ExportFormat::Html),
+            other => Err(anyhow!("Unknown export format '{}' (expected json, csv, or html)", other)),
+        }
+    }
+}
+
+/// A flattened, format-agnostic view of the `Message` fields worth
+/// exporting - kept separate from `Message`'s own (de)serialization so
+/// export formats can change without touching the wire/storage representation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedMessage {
+    pub id: Uuid,
+    pub sender_id: Uuid,
+    pub sender_name: String,
+    pub recipient_id: Option<Uuid>,
+    pub message_type: String,
+    pub content: String,
+    pub timestamp: DateTime<Utc>,
+    pub delivered_late: bool,
+    pub read_at: Option<DateTime<Utc>>,
+    pub edited: bool,
+    pub retracted: bool,
+}
+
+impl From<&Message> for ExportedMessage {
+    fn from(message: &Message) -> Self {
+        ExportedMessage {
+            id: message.id,
+            sender_id: message.sender_id,
+            sender_name: message.sender_name.clone(),
+            recipient_id: message.recipient_id,
+            message_type: format!("{:?}", message.message_type),
+            content: message.content.clone(),
+            timestamp: message.timestamp,
+            delivered_late: message.delivered_late,
+            read_at: message.read_at,
+            edited: message.edited,
+            retracted: message.retracted,
+        }
+    }
+}
+
+/// Writes `messages` to `out` in `format`. Overwrites `out` if it already exists.
+pub fn write_export(messages: &[Message], format: ExportFormat, out: &Path) -> Result<()> {
+    match format {
+        ExportFormat::Json => write_json(messages, out),
+        ExportFormat::Csv => write_csv(&rows(messages), out),
+        ExportFormat::Html => write_html(&rows(messages), out),
+    }
+}
+
+fn rows(messages: &[Message]) -> Vec<ExportedMessage> {
+    messages.iter().map(ExportedMessage::from).collect()
+}
+
+/// Written as plain `Message` objects (the same shape `storage::MessageStore`
+/// persists) rather than the flattened `ExportedMessage`, so `read_export`
+/// can hand them back to `import_history` without losing anything.
+fn write_json(messages: &[Message], out: &Path) -> Result<()> {
+    let contents = serde_json::to_string_pretty(messages)?;
+    std::fs::write(out, contents)?;
+    Ok(())
+}
+
+/// Reads back a JSON export written by `write_export` - see `import_history`.
+pub fn read_export(path: &Path) -> Result<Vec<Message>> {
+    let contents = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&contents)?)
+}
+
+fn write_csv(rows: &[ExportedMessage], out: &Path) -> Result<()> {
+    let mut contents = String::from("id,timestamp,sender_id,sender_name,recipient_id,message_type,delivered_late,read_at,edited,retracted,content\n");
+    for row in rows {
+        contents.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{},{},{}\n",
+            row.id,
+            row.timestamp.to_rfc3339(),
+            row.sender_id,
+            csv_field(&row.sender_name),
+            row.recipient_id.map(|id| id.to_string()).unwrap_or_default(),
+            row.message_type,
+            row.delivered_late,
+            row.read_at.map(|t| t.to_rfc3339()).unwrap_or_default(),
+            row.edited,
+            row.retracted,
+            csv_field(&row.content),
+        ));
+    }
+    std::fs::write(out, contents)?;
+    Ok(())
+}
+
+/// Quotes `field` for CSV if it contains a comma, quote, or newline, doubling
+/// any embedded quotes - the minimal RFC 4180 escaping this export needs.
+fn csv_field(field: &str) -> String {
+    if field.contains(',') || field.contains('"') || field.contains('\n') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn write_html(rows: &[ExportedMessage], out: &Path) -> Result<()> {
+    let mut contents = String::from(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>Rustalk history export</title></head><body>\n<table border=\"1\" cellpadding=\"4\" cellspacing=\"0\">\n<tr><th>Timestamp</th><th>Sender</th><th>Type</th><th>Content</th><th>State</th></tr>\n",
+    );
+    for row in rows {
+        let mut state = Vec::new();
+        if row.delivered_late {
+            state.push("delivered late");
+        }
+        if row.read_at.is_some() {
+            state.push("read");
+        }
+        if row.edited {
+            state.push("edited");
+        }
+        if row.retracted {
+            state.push("retracted");
+        }
+        contents.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            html_escape(&row.timestamp.to_rfc3339()),
+            html_escape(&row.sender_name),
+            html_escape(&row.message_type),
+            html_escape(&row.content),
+            html_escape(&state.join(", ")),
+        ));
+    }
+    contents.push_str("</table>\n</body></html>\n");
+    std::fs::write(out, contents)?;
+    Ok(())
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,158 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt, SeekFrom};
+use uuid::Uuid;
+
+/// How much file data goes in one `Channel::FileTransfer` frame. Small enough
+/// that a single chunk never dominates the bounded queue `send_file_chunk`
+/// writes into (see `network::FILE_TRANSFER_QUEUE_DEPTH`), large enough that
+/// per-chunk framing/encryption overhead stays negligible.
+pub const CHUNK_SIZE: u64 = 64 * 1024;
+
+/// Number of `CHUNK_SIZE` chunks a file of `file_size` bytes splits into -
+/// shared by the sender (to know when it's sent the last one) and the
+/// receiver (to know when it's received the last one).
+pub fn chunk_count(file_size: u64) -> u64 {
+    file_size.div_ceil(CHUNK_SIZE).max(1)
+}
+
+/// Offer/accept negotiation payload, carried as JSON in a
+/// `MessageType::FileOffer` message's `content` - see `message::Message::file_offer_message`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileOffer {
+    pub transfer_id: Uuid,
+    pub file_name: String,
+    pub file_size: u64,
+    /// Hex-encoded SHA-256 of the whole file, computed before the offer is
+    /// sent - `accept_file`'s receiver checks a received file against this
+    /// once every chunk has arrived, the same way `handshake_message`'s
+    /// public key is verified out of band rather than trusted blindly.
+    pub sha256: String,
+}
+
+/// One `Channel::FileTransfer` frame - bincode-serialized, not JSON, since
+/// this travels on the binary channel alongside raw chunk bytes rather than
+/// through `protocol::encode_message`. `ciphertext` is the base64 output of
+/// `CryptoEngine::encrypt_message` run on the chunk's (base64-encoded, since
+/// that function takes text) bytes, so a chunk is exactly as protected as a
+/// chat message on the same connection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileChunkFrame {
+    pub transfer_id: Uuid,
+    pub index: u64,
+    pub ciphertext: String,
+}
+
+impl FileChunkFrame {
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        Ok(bincode::serialize(self)?)
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        Ok(bincode::deserialize(bytes)?)
+    }
+}
+
+/// An offer we made that's still waiting on the peer's `FileAccept`/`FileReject`,
+/// or that's actively being streamed out - see `network::NetworkManager::offer_file`.
+pub struct OutgoingTransfer {
+    pub peer_id: Uuid,
+    pub path: PathBuf,
+    pub offer: FileOffer,
+}
+
+/// A transfer we've accepted and are writing to disk - see
+/// `network::NetworkManager::accept_file`. Chunks can arrive out of order
+/// (nothing about `Channel::FileTransfer` guarantees otherwise), so each one
+/// is written at its own offset rather than appended, which is also what
+/// makes a chunk safe to re-send: writing the same index twice just
+/// overwrites the same bytes.
+pub struct IncomingTransfer {
+    /// Peer that offered the file - `network::spawn_reader` needs this once a
+    /// transfer finishes to address the `file_complete_message` reply, since
+    /// by then the matching `pending_offers` entry has already been consumed.
+    pub peer_id: Uuid,
+    pub offer: FileOffer,
+    pub dest_path: PathBuf,
+    pub file: File,
+    pub received_chunks: u64,
+    pub total_chunks: u64,
+}
+
+impl IncomingTransfer {
+    /// Writes `data` at the position `index` owns, updates the chunk count,
+    /// and reports whether that was the last chunk this transfer needed.
+    pub async fn write_chunk(&mut self, index: u64, data: &[u8]) -> Result<bool> {
+        self.file.seek(SeekFrom::Start(index * CHUNK_SIZE)).await?;
+        self.file.write_all(data).await?;
+        self.received_chunks += 1;
+        Ok(self.received_chunks >= self.total_chunks)
+    }
+
+    /// Flushes the destination file and verifies it against
+    /// `FileOffer::sha256` - called once `write_chunk` reports every chunk in.
+    pub async fn finish(mut self) -> Result<bool> {
+        self.file.flush().await?;
+        let actual = hash_file(&self.dest_path).await?;
+        Ok(actual == self.offer.sha256)
+    }
+}
+
+/// Typed metadata for a blob of bytes carried alongside a message - a
+/// filename, its size, a MIME type, and a hex-encoded SHA-256 checksum -
+/// shared by `message::ImagePayload` so an inline image carries the same
+/// kind of provenance a `FileOffer` does instead of just a bare `mime`
+/// string. `FileOffer` itself is left as-is: it already has its own
+/// `sha256`/`file_name`/`file_size` fields and a multi-message accept/reject
+/// handshake around them, so wrapping it in `Attachment` too would just be
+/// two names for the same three fields.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attachment {
+    pub filename: String,
+    pub size: u64,
+    pub mime: String,
+    pub checksum: String,
+}
+
+impl Attachment {
+    /// Builds an `Attachment` describing `data`, hashing it up front the same
+    /// way `hash_file` does for a file on disk.
+    pub fn new(filename: String, mime: String, data: &[u8]) -> Self {
+        Self {
+            filename,
+            size: data.len() as u64,
+            mime,
+            checksum: hex::encode(Sha256::digest(data)),
+        }
+    }
+
+    /// Checks `data` against this attachment's recorded size and checksum -
+    /// see `network::spawn_reader`'s `MessageType::Image` handling, which
+    /// calls this on receipt the same way `IncomingTransfer::finish` checks a
+    /// completed file transfer against `FileOffer::sha256`.
+    pub fn verify(&self, data: &[u8]) -> bool {
+        data.len() as u64 == self.size && hex::encode(Sha256::digest(data)) == self.checksum
+    }
+}
+
+/// Streams `path` through SHA-256 in `CHUNK_SIZE` pieces rather than reading
+/// it into memory whole, so hashing a large file before offering it (or after
+/// receiving it, to verify) doesn't scale with file size.
+pub async fn hash_file(path: &Path) -> Result<String> {
+    let mut file = File::open(path).await?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; CHUNK_SIZE as usize];
+
+    loop {
+        let n = file.read(&mut buffer).await?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+
+    Ok(hex::encode(hasher.finalize()))
+}
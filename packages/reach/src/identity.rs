@@ -1,5 +1,6 @@
 use crate::crypto::{CryptoEngine, KeyPair};
 use anyhow::{Result, anyhow};
+use ed25519_dalek::{Signer, SigningKey};
 use hex;
 use rand::{RngCore, rngs::OsRng};
 use serde::{Deserialize, Serialize};
@@ -19,6 +20,21 @@ pub struct Identity {
     pub display_name: Option<String>,
     pub keypair: KeyPairSerialized,
     pub password_hash: String,
+    /// Hex-encoded salt mixed into `password_hash` at creation - see
+    /// `verify_password`. `#[serde(default)]` for identities saved before
+    /// this field existed; their original salt was never persisted, so
+    /// there's no way to verify their password retroactively - `verify_password`
+    /// always returns `false` for them rather than matching against a salt
+    /// that was never actually used to create the hash.
+    #[serde(default)]
+    pub password_salt: String,
+    /// Base64-encoded Ed25519 signing key seed used to sign outgoing
+    /// messages - see `Message::sign`/`verify_signature`. `None` for
+    /// identities saved before this field existed; their messages go out
+    /// unsigned rather than the load failing, the same tolerance
+    /// `Config::directory_signing_key` gets for peers without one.
+    #[serde(default)]
+    pub signing_key: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,29 +48,28 @@ impl Identity {
         let user_id = Uuid::new_v4();
         let keypair = KeyPair::generate();
 
-        // Generate salt for password hashing
-        let mut salt = [0u8; 16];
-        OsRng.fill_bytes(&mut salt);
-
-        let salted_password = format!("{}{}", credentials.password, hex::encode(&salt));
-        let password_hash = CryptoEngine::hash_password(&salted_password);
-        let password_hash_b64 =
-            base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &password_hash);
+        let (password_hash_b64, salt_hex) = Self::hash_password(&credentials.password);
 
         let keypair_serialized = KeyPairSerialized {
             private_key: base64::Engine::encode(
                 &base64::engine::general_purpose::STANDARD,
-                &keypair.private_key,
+                keypair.private_key,
             ),
             public_key: keypair.public_key_base64(),
         };
 
+        let mut seed = [0u8; 32];
+        OsRng.fill_bytes(&mut seed);
+        let signing_key = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, seed);
+
         Ok(Identity {
             user_id,
             email: credentials.email,
             display_name: Some(credentials.name),
             keypair: keypair_serialized,
             password_hash: password_hash_b64,
+            password_salt: salt_hex,
+            signing_key: Some(signing_key),
         })
     }
 
@@ -64,6 +79,8 @@ impl Identity {
         display_name: Option<String>,
         keypair: KeyPairSerialized,
         password_hash: String,
+        password_salt: String,
+        signing_key: Option<String>,
     ) -> Self {
         Identity {
             user_id,
@@ -71,9 +88,95 @@ impl Identity {
             display_name,
             keypair,
             password_hash,
+            password_salt,
+            signing_key,
         }
     }
 
+    /// Hashes `password` under a freshly generated salt, returning
+    /// `(password_hash_b64, salt_hex)` ready to store on an `Identity` -
+    /// shared by `Identity::new` and `set_password`.
+    fn hash_password(password: &str) -> (String, String) {
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+
+        let salt_hex = hex::encode(salt);
+        let salted_password = format!("{}{}", password, salt_hex);
+        let password_hash = CryptoEngine::hash_password(&salted_password);
+        let password_hash_b64 =
+            base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &password_hash);
+
+        (password_hash_b64, salt_hex)
+    }
+
+    /// Re-hashes this identity's password under a fresh salt - used both for
+    /// a deliberate password change and, via `needs_password_migration`, to
+    /// give identities saved before `password_salt` existed a one-time path
+    /// back into `verify_password` instead of being locked out forever.
+    ///
+    /// Taking the migration path here proves nothing about who's calling it,
+    /// since there's no old password left to check it against (see
+    /// `needs_password_migration`), so callers reaching this through that
+    /// path should `log::warn!` loudly when they do, not just tell the user
+    /// performing it. Anyone with local access to a config file in this
+    /// state can claim the identity with a brand-new password and zero
+    /// verification; see `cli::CliOperations::start_chat_session`.
+    pub fn set_password(&mut self, new_password: &str) {
+        let (password_hash_b64, salt_hex) = Self::hash_password(new_password);
+        self.password_hash = password_hash_b64;
+        self.password_salt = salt_hex;
+    }
+
+    /// True for identities saved before `password_salt` was persisted -
+    /// their original salt is gone, so `verify_password` can never succeed
+    /// for them no matter what's typed in. Callers should prompt these
+    /// identities to set a new password via `set_password` rather than
+    /// reporting a normal "incorrect password".
+    ///
+    /// That migration is an unauthenticated identity claim, not just a UX
+    /// nicety: whoever calls `set_password` next, while this is still true,
+    /// walks away owning the identity with no proof they're its original
+    /// owner - see `set_password`'s doc comment. The practical exposure is
+    /// low since the private key already sits in plaintext in the same
+    /// config file this salt lives in (this password never gated it), but
+    /// it's still a real silent takeover of the account-facing identity
+    /// (display name, contacts see this `user_id` as "them" going forward).
+    pub fn needs_password_migration(&self) -> bool {
+        self.password_salt.is_empty()
+    }
+
+    fn decoded_signing_key(&self) -> Result<SigningKey> {
+        let encoded = self
+            .signing_key
+            .as_ref()
+            .ok_or_else(|| anyhow!("This identity has no signing key"))?;
+        let bytes = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, encoded)
+            .map_err(|e| anyhow!("Failed to decode signing key: {}", e))?;
+        let bytes: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| anyhow!("Signing key must be 32 bytes"))?;
+        Ok(SigningKey::from_bytes(&bytes))
+    }
+
+    /// Base64-encoded Ed25519 verifying key matching `signing_key`, advertised
+    /// to peers during the handshake - see `Message::handshake_message` and
+    /// `Peer::signing_key`. `None` if this identity has no signing key.
+    pub fn verifying_key_base64(&self) -> Option<String> {
+        self.decoded_signing_key().ok().map(|key| {
+            base64::Engine::encode(&base64::engine::general_purpose::STANDARD, key.verifying_key().to_bytes())
+        })
+    }
+
+    /// Signs `data` with this identity's Ed25519 signing key, base64-encoded
+    /// - see `Message::sign`. `None` if this identity has no signing key.
+    pub fn sign(&self, data: &[u8]) -> Option<String> {
+        let signing_key = self.decoded_signing_key().ok()?;
+        Some(base64::Engine::encode(
+            &base64::engine::general_purpose::STANDARD,
+            signing_key.sign(data).to_bytes(),
+        ))
+    }
+
     pub fn set_display_name(&mut self, name: String) {
         self.display_name = Some(name);
     }
@@ -110,11 +213,15 @@ impl Identity {
         Ok(key_bytes)
     }
 
+    /// Checks `password` against the hash made at `Identity::new` time from
+    /// `password + password_salt`. Always `false` for identities saved
+    /// before `password_salt` was persisted - see its doc comment.
     pub fn verify_password(&self, password: &str) -> bool {
-        // In a real implementation, you'd store the salt and verify properly
-        // This is a simplified version
-        let salt = [0u8; 16]; // You'd store this with the identity
-        let salted_password = format!("{}{}", password, hex::encode(salt));
+        if self.password_salt.is_empty() {
+            return false;
+        }
+
+        let salted_password = format!("{}{}", password, self.password_salt);
         let hash = CryptoEngine::hash_password(&salted_password);
         let hash_b64 = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &hash);
 
@@ -127,3 +234,67 @@ impl Identity {
             .unwrap_or_else(|| format!("User_{}", &self.user_id.to_string()[..8]))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn credentials(password: &str) -> UserCredentials {
+        UserCredentials {
+            email: "user@example.com".to_string(),
+            name: "Tester".to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    #[test]
+    fn verify_password_accepts_the_correct_password() {
+        let identity = Identity::new(credentials("hunter2")).expect("identity creation");
+        assert!(identity.verify_password("hunter2"));
+        assert!(!identity.verify_password("wrong"));
+    }
+
+    #[test]
+    fn pre_salt_identity_needs_migration_and_cannot_be_verified() {
+        // Simulates an identity loaded from a config saved before
+        // `password_salt` existed: `#[serde(default)]` leaves it empty.
+        let identity = Identity::from_existing(
+            Uuid::new_v4(),
+            "user@example.com".to_string(),
+            None,
+            KeyPairSerialized {
+                private_key: String::new(),
+                public_key: String::new(),
+            },
+            "some-old-hash".to_string(),
+            String::new(),
+            None,
+        );
+
+        assert!(identity.needs_password_migration());
+        assert!(!identity.verify_password("whatever-the-old-password-was"));
+    }
+
+    #[test]
+    fn set_password_clears_the_migration_flag_and_verifies_afterward() {
+        let mut identity = Identity::from_existing(
+            Uuid::new_v4(),
+            "user@example.com".to_string(),
+            None,
+            KeyPairSerialized {
+                private_key: String::new(),
+                public_key: String::new(),
+            },
+            "some-old-hash".to_string(),
+            String::new(),
+            None,
+        );
+        assert!(identity.needs_password_migration());
+
+        identity.set_password("new-password");
+
+        assert!(!identity.needs_password_migration());
+        assert!(identity.verify_password("new-password"));
+        assert!(!identity.verify_password("whatever-the-old-password-was"));
+    }
+}
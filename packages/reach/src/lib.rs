@@ -1,20 +1,70 @@
+pub mod addr;
+pub mod addressbook;
+pub mod chat_chunk;
 pub mod cli;
+pub mod commands;
 pub mod config;
+pub mod contacts;
+pub mod conversation;
 pub mod crypto;
+pub mod dedup;
+pub mod directory;
+pub mod dnscontact;
+pub mod events;
+pub mod export;
+pub mod file_transfer;
 pub mod identity;
+pub mod logging;
 pub mod message;
+pub mod multiplex;
+pub mod nat;
 pub mod network;
+pub mod noise;
+pub mod notify;
+pub mod outbox;
 pub mod peer;
+pub mod persist;
+pub mod portmap;
+pub mod protocol;
+pub mod quic;
+pub mod ratelimit;
+pub mod relay;
+pub mod scheduled;
 pub mod session;
+pub mod socks5;
+pub mod stats;
+pub mod storage;
+pub mod theme;
+pub mod throttle;
+pub mod time;
+pub mod transport;
+pub mod translation;
+pub mod websocket;
 
+pub use addressbook::{AddressBook, KnownPeer};
 pub use cli::{CliOperations, PathManager, UserManager};
 pub use config::{Config, config_exists, get_config_file, load_config, save_config};
+pub use contacts::{Contact, ContactBook};
+pub use conversation::{Conversation, ConversationSettings};
 pub use crypto::{CryptoEngine, KeyPair};
+pub use events::SessionEvent;
 pub use identity::{Identity, UserCredentials};
-pub use message::{Message, MessageType};
+pub use message::{DeliveryStatus, Message, MessageType, SystemEvent};
 pub use network::{NetworkManager, PeerConnection};
-pub use peer::{Peer, PeerPingStatus, PeerStatus};
-pub use session::{ChatSession, SessionManager};
+pub use outbox::{Outbox, OutboxEntry, OutboxStatus};
+pub use peer::{Capabilities, Peer, PeerPingStatus, PeerStatus, TransportKind};
+pub use persist::WriteBehindQueue;
+pub use portmap::PortMapping;
+pub use quic::QuicTransport;
+pub use directory::DirectoryServer;
+pub use file_transfer::FileOffer;
+pub use relay::RelayServer;
+pub use scheduled::{ScheduledMessage, ScheduledQueue};
+pub use session::{guess_mime, ChatSession, SessionManager};
+pub use stats::{NetworkStats, PeerStats};
+pub use time::format_timestamp;
+pub use transport::Transport;
+pub use translation::TranslationHook;
 
 use anyhow::Result;
 use std::collections::HashMap;
@@ -49,7 +99,41 @@ impl ReachEngine {
 
     pub async fn start_server(&mut self, port: u16) -> Result<()> {
         let network = self.network.write().await;
-        network.start_listening(port).await
+        network.start_listening(port, &self.config.bind_address).await?;
+        network.start_heartbeat_monitor(
+            std::time::Duration::from_secs(15),
+            chrono::Duration::seconds(45),
+        );
+        Ok(())
+    }
+
+    /// Tries to re-establish a connection to every peer in the local
+    /// `AddressBook`, using each one's most recently used address. Individual
+    /// failures (peer offline, address changed) are logged and skipped rather
+    /// than aborting the rest - this is a best-effort startup convenience,
+    /// not a guarantee every known peer comes back online.
+    pub async fn connect_known_peers(&self) -> Result<Vec<Peer>> {
+        let address_book = AddressBook::new()?;
+        let known_peers = address_book.list()?;
+
+        let mut reconnected = Vec::new();
+        for known_peer in known_peers {
+            let Some(address) = known_peer.last_addresses.last() else {
+                continue;
+            };
+
+            match self.connect_to_peer(address).await {
+                Ok(peer) => reconnected.push(peer),
+                Err(e) => log::warn!(
+                    "Failed to reconnect to known peer {} ({}): {}",
+                    known_peer.nickname,
+                    address,
+                    e
+                ),
+            }
+        }
+
+        Ok(reconnected)
     }
 
     pub async fn connect_to_peer(&self, address: &str) -> Result<Peer> {
@@ -60,6 +144,14 @@ impl ReachEngine {
         let mut peers = self.peers.write().await;
         peers.insert(peer.id.to_string(), peer.clone());
 
+        // Remember this peer so `connect_known_peers` can reconnect to it in
+        // a future session - see `addressbook::AddressBook`.
+        if let Ok(address_book) = AddressBook::new()
+            && let Err(e) = address_book.remember(&peer.id.to_string(), &peer.public_key, &peer.display_name, address)
+        {
+            log::warn!("Failed to update address book for peer {}: {}", peer.id, e);
+        }
+
         Ok(peer)
     }
 
@@ -73,6 +165,12 @@ impl ReachEngine {
         network.ping_peer(peer_id).await
     }
 
+    /// Per-peer and global traffic/reconnect/RTT counters - see `stats::NetworkStats`.
+    pub async fn network_stats(&self) -> stats::NetworkStats {
+        let network = self.network.read().await;
+        network.get_stats().await
+    }
+
     pub async fn set_nickname(&self, nickname: String) -> Result<()> {
         let mut network = self.network.write().await;
         network.set_nickname(nickname).await
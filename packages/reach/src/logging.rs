@@ -0,0 +1,59 @@
+use anyhow::{Result, anyhow};
+use log::LevelFilter;
+
+/// Initializes logging from `Config::log_level`, which `env_logger` parses as
+/// a full filter spec - a bare level (`"debug"`) or per-module directives
+/// (`"network=debug,crypto=warn"`), same syntax as `RUST_LOG`. `RUST_LOG`
+/// still wins when set, for the usual ad-hoc-debugging override. Falls back
+/// to `"info"` if neither is available, which covers first run before any
+/// config file exists - see `configured_log_level`.
+///
+/// The global ceiling (`log::set_max_level`) is left fully open (`Trace`) so
+/// `env_logger`'s own filter, built here from that spec, does the filtering
+/// at start. `set_level`/`/loglevel` later moves only the ceiling - see its
+/// doc comment: it can make logging quieter than the spec, not more verbose.
+///
+/// Note: there's no long-running daemon in this codebase (see
+/// `config::load_config_cached`'s doc comment) to expose an IPC command on,
+/// so `/loglevel` changing this process's own filter is the full extent of
+/// "runtime" here - there's no separate daemon process to reach into.
+pub fn init() {
+    let spec = std::env::var("RUST_LOG")
+        .ok()
+        .or_else(configured_log_level)
+        .unwrap_or_else(|| "info".to_string());
+
+    env_logger::Builder::new().parse_filters(&spec).init();
+    log::set_max_level(LevelFilter::Trace);
+}
+
+/// Reads `Config::log_level` without ever creating a config file - `init`
+/// runs before commands like `rus setup` get a chance to run, and eagerly
+/// creating a default config here would preempt that flow. Only consults
+/// the config once one is already known to exist on disk.
+fn configured_log_level() -> Option<String> {
+    if !crate::config::config_exists() {
+        return None;
+    }
+    crate::config::load_config_cached().ok().map(|config| config.log_level)
+}
+
+/// Changes the global log ceiling at runtime - see `/loglevel`. Every record
+/// must also pass `env_logger`'s own filter, which `init` builds once from
+/// `Config::log_level` (or `RUST_LOG`) and which stays fixed for the life of
+/// the process. So this can lower verbosity below that spec (`/loglevel warn`
+/// under an `info` spec hides `info` records) but not raise it: `/loglevel
+/// debug` under an `info` spec still shows no `debug` records, and modules
+/// are not re-targeted independently. `current_level` reports the ceiling
+/// set here, not that combined effect.
+pub fn set_level(level: &str) -> Result<LevelFilter> {
+    let filter: LevelFilter = level
+        .parse()
+        .map_err(|_| anyhow!("Unknown log level '{}' (expected trace|debug|info|warn|error|off)", level))?;
+    log::set_max_level(filter);
+    Ok(filter)
+}
+
+pub fn current_level() -> LevelFilter {
+    log::max_level()
+}
@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,6 +11,185 @@ pub enum MessageType {
     Handshake,
     KeyExchange,
     System,
+    Heartbeat,
+    /// Sent to every authenticated connection at once - see
+    /// `network::NetworkManager::broadcast` and `/all`.
+    Broadcast,
+    /// Announces a new `ChatSession::topic` to every connected peer - see
+    /// `network::NetworkManager::broadcast_topic` and `/topic`.
+    Topic,
+    /// A short, latency-sensitive notice (presence, typing) sent over the
+    /// unreliable UDP `quic` transport - see `network::NetworkManager::send_ephemeral`.
+    Typing,
+    /// Application-level acknowledgment of an `Ack`-requiring datagram,
+    /// carrying the acknowledged message's id as `content` - see
+    /// `network::NetworkManager::send_ephemeral`. Never sent over TCP.
+    Ack,
+    /// Proposes sending a file, carrying a JSON-encoded `file_transfer::FileOffer`
+    /// as `content` - see `network::NetworkManager::offer_file`. The actual
+    /// file data travels separately over `Channel::FileTransfer`, not as messages.
+    FileOffer,
+    /// Accepts a `FileOffer`, carrying the offer's `transfer_id` as `content` -
+    /// see `network::NetworkManager::accept_file`.
+    FileAccept,
+    /// Declines a `FileOffer`, carrying the offer's `transfer_id` as `content` -
+    /// see `network::NetworkManager::reject_file`.
+    FileReject,
+    /// Reports whether a completed transfer's checksum matched, carrying
+    /// `"<transfer_id> <true|false>"` as `content` - sent by the receiver once
+    /// `file_transfer::IncomingTransfer::finish` returns.
+    FileComplete,
+    /// Opt-in notice that the local user viewed a conversation, carrying a
+    /// JSON-encoded `ReadReceiptPayload` as `content` - see
+    /// `network::NetworkManager::send_read_receipt`, `Config::read_receipts_enabled`
+    /// and `/read`. Never recorded in history itself; `SessionManager::merge_message`
+    /// routes it into `ChatSession::apply_read_receipt` instead.
+    ReadReceipt,
+    /// Republishes an earlier message with new text, carrying a JSON-encoded
+    /// `EditPayload` as `content` - see `network::NetworkManager::send_edit`,
+    /// `ChatSession::apply_edit` and `/edit`. Like `ReadReceipt`, this isn't
+    /// added to history itself; it mutates the message it references.
+    Edit,
+    /// Asks peers to tombstone a previously sent message, carrying its id as
+    /// `content` - see `network::NetworkManager::send_retraction`,
+    /// `ChatSession::apply_retraction` and `/retract`. Same single-id
+    /// `content` convention as `Ack`/`FileAccept`; like `Edit`, this isn't
+    /// added to history itself.
+    Retract,
+    /// Adds or removes an emoji reaction on a message, carrying a
+    /// JSON-encoded `ReactionPayload` as `content` - see
+    /// `network::NetworkManager::send_reaction`, `ChatSession::apply_reaction`,
+    /// `/react` and `/unreact`. Unlike `Edit`/`Retract`, any peer may react
+    /// to a message, not just its original sender.
+    Reaction,
+    /// Distributes the sender's current broadcast encryption key to one
+    /// peer, carrying it base64-encoded as `content` - see
+    /// `network::NetworkManager::broadcast`'s sender-key encryption and
+    /// `SessionManager::merge_message`, which stores it instead of adding it
+    /// to history. Relies entirely on this connection's own transport
+    /// encryption for confidentiality, same as every other control message
+    /// in this codebase.
+    SenderKey,
+    /// Like `Text`, but `content` is markdown source rather than plain text -
+    /// see `/md`. A renderer that doesn't understand markdown can still show
+    /// `content` as-is; it's valid to read as plain text too.
+    Markdown,
+    /// An inline image, carrying a JSON-encoded `ImagePayload` as `content` -
+    /// see `network::NetworkManager::send_image` and `/image`. Unlike
+    /// `FileOffer`, there's no accept/reject handshake - the whole image
+    /// travels in the message itself, so this is only for images small
+    /// enough that doing so is reasonable.
+    Image,
+    /// A syntax-highlightable code snippet, carrying a JSON-encoded
+    /// `CodePayload` as `content` - see `network::NetworkManager::send_code`
+    /// and `/code`.
+    Code,
+    /// Sent automatically by the receiver the moment it decrypts a
+    /// `MessageType::Text`, carrying the acknowledged message's id as
+    /// `content` - same single-id convention as `Ack`/`Retract`. Unlike
+    /// `Ack`, this travels over the regular TCP path (not just the
+    /// unreliable `quic` datagram transport) and drives `Message::delivery_status`
+    /// via `ChatSession::apply_delivery_ack` rather than the ephemeral-send
+    /// retry loop. Not sent for `Broadcast`, which has no single recipient
+    /// to report delivery back to.
+    DeliveryAck,
+}
+
+/// Payload for a `MessageType::ReadReceipt` message - the ids of every
+/// message the recipient marked read in one go, and when. Batched per
+/// `/read` call rather than one receipt per message; a long list is split
+/// across several receipts - see `network::NetworkManager::send_read_receipt`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadReceiptPayload {
+    pub message_ids: Vec<Uuid>,
+    pub read_at: DateTime<Utc>,
+}
+
+/// Payload for a `MessageType::Edit` message - the id of the message being
+/// replaced and its new text.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EditPayload {
+    pub message_id: Uuid,
+    pub new_content: String,
+}
+
+/// Payload for a `MessageType::Reaction` message - which message, which
+/// emoji, and whether this adds or removes the sender's reaction.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReactionPayload {
+    pub message_id: Uuid,
+    pub emoji: String,
+    pub add: bool,
+}
+
+/// Payload for a `MessageType::Image` message - the image bytes, base64
+/// encoded the same way `crypto::CryptoEngine::encrypt_message` base64-encodes
+/// its ciphertext, plus an `Attachment` describing them (filename, size, MIME
+/// type, checksum) so a renderer knows how to decode them and a receiver can
+/// verify they arrived intact - see `network::spawn_reader`'s
+/// `MessageType::Image` handling.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImagePayload {
+    pub attachment: crate::file_transfer::Attachment,
+    pub data: String,
+}
+
+/// Payload for a `MessageType::Code` message - the snippet and the language
+/// it's written in, so a renderer can pick a syntax highlighter.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodePayload {
+    pub lang: String,
+    pub text: String,
+}
+
+/// Structured payload for a `MessageType::System` message, so the TUI, NAPI
+/// layer, and logs can render/translate/filter system events instead of
+/// pattern-matching the free-text `content` string.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SystemEvent {
+    PeerConnected { display_name: String },
+    PeerTimedOut { display_name: String },
+    PeerLeft { display_name: String, reason: String },
+    /// A handshake finished but `Config::auto_accept_connections` is `false`,
+    /// so the connection is held in `NetworkManager::pending_connections`
+    /// until `/accept <peer_id>` (or `rejectPeer` over napi) decides its fate.
+    ConnectionPending { peer_id: Uuid, display_name: String },
+}
+
+impl SystemEvent {
+    /// Renders the event as the human-readable text stored in `Message::content`.
+    pub fn render(&self) -> String {
+        match self {
+            SystemEvent::PeerConnected { display_name } => format!("Connected to {}", display_name),
+            SystemEvent::PeerTimedOut { display_name } => {
+                format!("{} timed out (no heartbeat)", display_name)
+            }
+            SystemEvent::PeerLeft { display_name, reason } => {
+                format!("{} left ({})", display_name, reason)
+            }
+            SystemEvent::ConnectionPending { peer_id, display_name } => {
+                format!("{} ({}) wants to connect - run /accept {} to allow", display_name, peer_id, peer_id)
+            }
+        }
+    }
+}
+
+/// Per-message delivery state for a message *we* sent - see
+/// `ChatSession::apply_delivery_ack`, `SessionManager::send_message` and
+/// `/history`. Stays at the default `Sent` for messages we received, which
+/// never transition through this at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum DeliveryStatus {
+    /// Handed to the network successfully; no `DeliveryAck` back yet.
+    #[default]
+    Sent,
+    /// A target acknowledged it with a `MessageType::DeliveryAck` - the first
+    /// ack marks it, so on a send to several peers this doesn't mean all of
+    /// them have it (see `ChatSession::apply_delivery_ack`).
+    Delivered,
+    /// Every target rejected the send outright - see `outbox::Outbox`. The
+    /// outbox may still retry it later, same as before this field existed.
+    Failed,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,6 +201,94 @@ pub struct Message {
     pub content: String,
     pub timestamp: DateTime<Utc>,
     pub sender_name: String,
+    /// Per-message flag set via `/star <id>` for later review across conversations.
+    pub starred: bool,
+    /// Per-message flag set via `/pin <id>` to highlight it in its conversation -
+    /// see `ChatSession::get_pinned_messages`. Unlike `starred`, which collects
+    /// important messages across every conversation into one review view,
+    /// pins are meant to be read per-conversation (e.g. "what's pinned in this
+    /// chat"), so `get_pinned_messages` takes a peer id to filter by.
+    #[serde(default)]
+    pub pinned: bool,
+    /// Populated by a conversation's translation hook, alongside the original `content`.
+    pub translated_content: Option<String>,
+    /// Structured payload for `MessageType::System` messages; `content` holds the
+    /// rendered text for callers that don't care about the structured form.
+    pub system_event: Option<SystemEvent>,
+    /// Set when this message was merged into history out of arrival order,
+    /// e.g. delivered by an offline queue flushing after a reconnect.
+    pub delivered_late: bool,
+    /// Correlation ID of the `PeerConnection`/`WsConnection` this message
+    /// arrived or was sent on, so logs and bug reports can tie a specific
+    /// message to a specific connection attempt across reconnects. `None`
+    /// for messages built before being handed to a connection (e.g. freshly
+    /// constructed outgoing messages) or for older peers that predate this field.
+    #[serde(default)]
+    pub connection_id: Option<Uuid>,
+    /// Wire protocol versions the sender supports, newest first - only
+    /// populated on `MessageType::Handshake` messages. Empty for every other
+    /// message type, and for peers that predate `protocol::negotiate`.
+    #[serde(default)]
+    pub protocol_versions: Vec<u8>,
+    /// Feature bitset the sender supports - see `peer::Capabilities`. Only
+    /// populated on `MessageType::Handshake` messages; `0` (no capabilities)
+    /// for everything else and for peers that predate this field.
+    #[serde(default)]
+    pub capabilities: u32,
+    /// When a `ReadReceipt` covering this message was applied via
+    /// `ChatSession::apply_read_receipt` - see `Config::read_receipts_enabled`
+    /// and `/read`. `None` until read, and always `None` for message types
+    /// other than `Text`/`Broadcast` that a receipt wouldn't apply to.
+    #[serde(default)]
+    pub read_at: Option<DateTime<Utc>>,
+    /// Sent/delivered/failed state for a message we sent - see `DeliveryStatus`.
+    #[serde(default)]
+    pub delivery_status: DeliveryStatus,
+    /// Set by `ChatSession::apply_edit` once this message's `content` has
+    /// been replaced by a later `MessageType::Edit` - see `/edit`.
+    #[serde(default)]
+    pub edited: bool,
+    /// Set by `ChatSession::apply_retraction` once this message has been
+    /// tombstoned by a later `MessageType::Retract` - see `/retract`.
+    /// `content` is cleared at the same time, so renderers should check this
+    /// flag rather than an empty `content` to decide whether to show
+    /// "message deleted".
+    #[serde(default)]
+    pub retracted: bool,
+    /// Emoji reactions on this message, keyed by emoji, each holding the ids
+    /// of everyone who reacted with it - see `ChatSession::apply_reaction`,
+    /// `/react` and `/unreact`. Any peer may react, not just the recipient.
+    #[serde(default)]
+    pub reactions: HashMap<String, Vec<Uuid>>,
+    /// Set when this message was relayed from another conversation via
+    /// `/forward` rather than freshly authored, carrying who actually sent it
+    /// and when - see `SessionManager::forward_message`. `sender_id`/
+    /// `sender_name` above stay set to whoever did the forwarding, the same
+    /// way a forwarded email's `From` is the forwarder, not the original author.
+    #[serde(default)]
+    pub forwarded_from: Option<ForwardedFrom>,
+    /// Base64-encoded Ed25519 verifying key the sender advertised - only
+    /// populated on `MessageType::Handshake` messages, the same convention as
+    /// `protocol_versions`/`capabilities`. Empty for everything else and for
+    /// peers that predate this field or have no signing key configured.
+    #[serde(default)]
+    pub signing_key: String,
+    /// Base64-encoded Ed25519 signature over `signable_bytes()`, set by
+    /// `sign` and checked by `verify_signature` - see
+    /// `network::spawn_reader`'s post-dedup signature check. `None` for
+    /// messages sent by an identity with no signing key, or received from a
+    /// peer that predates this field.
+    #[serde(default)]
+    pub signature: Option<String>,
+}
+
+/// Provenance kept on a message forwarded via `/forward` - see
+/// `Message::forwarded_from`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForwardedFrom {
+    pub sender_id: Uuid,
+    pub sender_name: String,
+    pub timestamp: DateTime<Utc>,
 }
 
 impl Message {
@@ -39,6 +307,22 @@ impl Message {
             content,
             timestamp: Utc::now(),
             sender_name,
+            starred: false,
+            pinned: false,
+            translated_content: None,
+            system_event: None,
+            delivered_late: false,
+            connection_id: None,
+            protocol_versions: Vec::new(),
+            capabilities: 0,
+            read_at: None,
+            delivery_status: DeliveryStatus::Sent,
+            edited: false,
+            retracted: false,
+            reactions: HashMap::new(),
+            forwarded_from: None,
+            signing_key: String::new(),
+            signature: None,
         }
     }
 
@@ -50,7 +334,7 @@ impl Message {
         target_peer: Option<String>,
     ) -> Self {
         let sender_id = Uuid::new_v4(); // This should come from identity in real usage
-        let recipient_id = target_peer.and_then(|_| Some(Uuid::new_v4())); // This should be resolved from peer lookup
+        let recipient_id = target_peer.map(|_| Uuid::new_v4()); // This should be resolved from peer lookup
 
         Message {
             id: Uuid::new_v4(),
@@ -60,6 +344,22 @@ impl Message {
             content,
             timestamp: Utc::now(),
             sender_name,
+            starred: false,
+            pinned: false,
+            translated_content: None,
+            system_event: None,
+            delivered_late: false,
+            connection_id: None,
+            protocol_versions: Vec::new(),
+            capabilities: 0,
+            read_at: None,
+            delivery_status: DeliveryStatus::Sent,
+            edited: false,
+            retracted: false,
+            reactions: HashMap::new(),
+            forwarded_from: None,
+            signing_key: String::new(),
+            signature: None,
         }
     }
 
@@ -93,14 +393,269 @@ impl Message {
         )
     }
 
-    pub fn handshake_message(sender_id: Uuid, public_key: String, sender_name: String) -> Self {
+    /// Builds a system message from a structured event, rendering `content`
+    /// from it so existing display code keeps working unchanged.
+    pub fn system_event_message(event: SystemEvent) -> Self {
+        let mut message = Self::system_message(event.render());
+        message.system_event = Some(event);
+        message
+    }
+
+    /// Built once per `NetworkManager::broadcast` call and sent identically
+    /// to every authenticated connection, rather than per-recipient like
+    /// `text_message`.
+    pub fn broadcast_message(sender_id: Uuid, sender_name: String, content: String) -> Self {
+        Self::new(sender_id, None, MessageType::Broadcast, content, sender_name)
+    }
+
+    /// Announces a new session topic to every peer - see `/topic`.
+    pub fn topic_message(sender_id: Uuid, sender_name: String, topic: String) -> Self {
+        Self::new(sender_id, None, MessageType::Topic, topic, sender_name)
+    }
+
+    /// Distributes `encoded_key` (base64) to one peer - see
+    /// `network::NetworkManager::broadcast`'s sender-key encryption. Built
+    /// with the same `(sender_id, sender_name, content)` shape as
+    /// `broadcast_message`/`topic_message` so it can be sent through the
+    /// same `fan_out` helper.
+    pub fn sender_key_message(sender_id: Uuid, sender_name: String, encoded_key: String) -> Self {
+        Self::new(sender_id, None, MessageType::SenderKey, encoded_key, sender_name)
+    }
+
+    /// Markdown source sent to `recipient_id` - see `/md`.
+    pub fn markdown_message(sender_id: Uuid, recipient_id: Uuid, sender_name: String, text: String) -> Self {
+        Self::new(sender_id, Some(recipient_id), MessageType::Markdown, text, sender_name)
+    }
+
+    /// Sends an inline image to `recipient_id` - see
+    /// `network::NetworkManager::send_image` and `/image`. `content` is JSON
+    /// since, like `file_offer_message`, an image carries more than one field.
+    pub fn image_message(sender_id: Uuid, recipient_id: Uuid, sender_name: String, payload: &ImagePayload) -> Self {
+        let content = serde_json::to_string(payload).unwrap_or_default();
+        Self::new(sender_id, Some(recipient_id), MessageType::Image, content, sender_name)
+    }
+
+    /// Sends a code snippet to `recipient_id` - see
+    /// `network::NetworkManager::send_code` and `/code`.
+    pub fn code_message(sender_id: Uuid, recipient_id: Uuid, sender_name: String, payload: &CodePayload) -> Self {
+        let content = serde_json::to_string(payload).unwrap_or_default();
+        Self::new(sender_id, Some(recipient_id), MessageType::Code, content, sender_name)
+    }
+
+    /// Renders `content` for display, decoding the JSON payload of
+    /// `MessageType::Image`/`MessageType::Code` messages into something
+    /// readable instead of showing the raw JSON - see `SystemEvent::render`
+    /// for the same idea applied to system events. Every other message type's
+    /// `content` is already display-ready as-is.
+    pub fn render_content(&self) -> String {
+        match self.message_type {
+            MessageType::Image => match serde_json::from_str::<ImagePayload>(&self.content) {
+                Ok(payload) => {
+                    format!("[image: {}, {}, {} bytes]", payload.attachment.filename, payload.attachment.mime, payload.attachment.size)
+                }
+                Err(_) => "[image: malformed]".to_string(),
+            },
+            MessageType::Code => match serde_json::from_str::<CodePayload>(&self.content) {
+                Ok(payload) => format!("```{}\n{}\n```", payload.lang, payload.text),
+                Err(_) => "[code: malformed]".to_string(),
+            },
+            _ => self.content.clone(),
+        }
+    }
+
+    /// A presence/typing notice to `recipient_id` - see
+    /// `network::NetworkManager::send_ephemeral` and `/typing`.
+    pub fn typing_message(sender_id: Uuid, recipient_id: Uuid, sender_name: String) -> Self {
+        Self::new(sender_id, Some(recipient_id), MessageType::Typing, String::new(), sender_name)
+    }
+
+    /// Acknowledges receipt of `acked_id` over the unreliable UDP path -
+    /// see `network::NetworkManager::send_ephemeral`.
+    pub fn ack_message(sender_id: Uuid, sender_name: String, acked_id: Uuid) -> Self {
+        Self::new(sender_id, None, MessageType::Ack, acked_id.to_string(), sender_name)
+    }
+
+    pub fn heartbeat_message(sender_id: Uuid, sender_name: String) -> Self {
+        Self::new(
+            sender_id,
+            None,
+            MessageType::Heartbeat,
+            "PING".to_string(),
+            sender_name,
+        )
+    }
+
+    /// Sent when a peer quits gracefully, so the other side can mark it offline
+    /// immediately instead of waiting for a read error or heartbeat timeout.
+    pub fn disconnect_message(sender_id: Uuid, sender_name: String, reason: String) -> Self {
+        Self::new(
+            sender_id,
+            None,
+            MessageType::Disconnect,
+            reason,
+            sender_name,
+        )
+    }
+
+    /// Proposes a file transfer to `recipient_id` - see
+    /// `network::NetworkManager::offer_file`. `content` is JSON since, unlike
+    /// `ack_message`'s single id, an offer carries a handful of fields.
+    pub fn file_offer_message(
+        sender_id: Uuid,
+        recipient_id: Uuid,
+        sender_name: String,
+        offer: &crate::file_transfer::FileOffer,
+    ) -> Self {
+        let content = serde_json::to_string(offer).unwrap_or_default();
+        Self::new(sender_id, Some(recipient_id), MessageType::FileOffer, content, sender_name)
+    }
+
+    /// Accepts a pending `FileOffer` - see `network::NetworkManager::accept_file`.
+    pub fn file_accept_message(sender_id: Uuid, recipient_id: Uuid, sender_name: String, transfer_id: Uuid) -> Self {
+        Self::new(
+            sender_id,
+            Some(recipient_id),
+            MessageType::FileAccept,
+            transfer_id.to_string(),
+            sender_name,
+        )
+    }
+
+    /// Declines a pending `FileOffer` - see `network::NetworkManager::reject_file`.
+    pub fn file_reject_message(sender_id: Uuid, recipient_id: Uuid, sender_name: String, transfer_id: Uuid) -> Self {
         Self::new(
+            sender_id,
+            Some(recipient_id),
+            MessageType::FileReject,
+            transfer_id.to_string(),
+            sender_name,
+        )
+    }
+
+    /// Reports the outcome of a completed transfer back to the sender.
+    pub fn file_complete_message(
+        sender_id: Uuid,
+        recipient_id: Uuid,
+        sender_name: String,
+        transfer_id: Uuid,
+        checksum_ok: bool,
+    ) -> Self {
+        Self::new(
+            sender_id,
+            Some(recipient_id),
+            MessageType::FileComplete,
+            format!("{} {}", transfer_id, checksum_ok),
+            sender_name,
+        )
+    }
+
+    /// Tells `recipient_id` that `message_ids` have been read - see
+    /// `network::NetworkManager::send_read_receipt`. `content` is JSON since,
+    /// like `file_offer_message`, a receipt carries more than one id.
+    pub fn read_receipt_message(
+        sender_id: Uuid,
+        recipient_id: Uuid,
+        sender_name: String,
+        payload: &ReadReceiptPayload,
+    ) -> Self {
+        let content = serde_json::to_string(payload).unwrap_or_default();
+        Self::new(sender_id, Some(recipient_id), MessageType::ReadReceipt, content, sender_name)
+    }
+
+    /// Announces that `payload.message_id` has been replaced with new text -
+    /// see `network::NetworkManager::send_edit`.
+    pub fn edit_message(sender_id: Uuid, recipient_id: Uuid, sender_name: String, payload: &EditPayload) -> Self {
+        let content = serde_json::to_string(payload).unwrap_or_default();
+        Self::new(sender_id, Some(recipient_id), MessageType::Edit, content, sender_name)
+    }
+
+    /// Asks `recipient_id` to tombstone `message_id` - see
+    /// `network::NetworkManager::send_retraction`.
+    pub fn retract_message(sender_id: Uuid, recipient_id: Uuid, sender_name: String, message_id: Uuid) -> Self {
+        Self::new(sender_id, Some(recipient_id), MessageType::Retract, message_id.to_string(), sender_name)
+    }
+
+    /// Acknowledges having decrypted `acked_id` over a regular (non-`quic`)
+    /// connection - see `network::spawn_reader`'s `MessageType::Text` handling.
+    pub fn delivery_ack_message(sender_id: Uuid, recipient_id: Uuid, sender_name: String, acked_id: Uuid) -> Self {
+        Self::new(sender_id, Some(recipient_id), MessageType::DeliveryAck, acked_id.to_string(), sender_name)
+    }
+
+    /// Adds or removes `sender_id`'s `emoji` reaction on `message_id` - see
+    /// `network::NetworkManager::send_reaction`.
+    pub fn reaction_message(
+        sender_id: Uuid,
+        recipient_id: Uuid,
+        sender_name: String,
+        payload: &ReactionPayload,
+    ) -> Self {
+        let content = serde_json::to_string(payload).unwrap_or_default();
+        Self::new(sender_id, Some(recipient_id), MessageType::Reaction, content, sender_name)
+    }
+
+    pub fn handshake_message(sender_id: Uuid, public_key: String, sender_name: String, signing_key: String) -> Self {
+        let mut message = Self::new(
             sender_id,
             None,
             MessageType::Handshake,
             public_key,
             sender_name,
-        )
+        );
+        message.protocol_versions = crate::protocol::SUPPORTED_VERSIONS.to_vec();
+        message.capabilities = crate::peer::Capabilities::supported().bits();
+        message.signing_key = signing_key;
+        message
+    }
+
+    /// Bytes this message's `signature` covers - the fields that identify
+    /// what was sent and by whom, fixed at send time. Deliberately excludes
+    /// fields that change after the fact (`connection_id`, `read_at`,
+    /// `reactions`, ...); those aren't part of what the sender vouched for.
+    fn signable_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(self.id.as_bytes());
+        bytes.extend_from_slice(self.sender_id.as_bytes());
+        bytes.extend_from_slice(self.recipient_id.unwrap_or(Uuid::nil()).as_bytes());
+        bytes.extend_from_slice(format!("{:?}", self.message_type).as_bytes());
+        bytes.extend_from_slice(self.content.as_bytes());
+        bytes.extend_from_slice(&self.timestamp.timestamp_micros().to_be_bytes());
+        bytes
+    }
+
+    /// Signs this message with `identity`'s signing key - see
+    /// `network::NetworkManager`'s send paths, which call this right before
+    /// handing the message off to `protocol::encode_message`. Does nothing if
+    /// `identity` has no signing key, leaving `signature` as `None`.
+    pub fn sign(&mut self, identity: &crate::identity::Identity) {
+        self.signature = identity.sign(&self.signable_bytes());
+    }
+
+    /// Checks `signature` against `verifying_key_base64` - see
+    /// `network::spawn_reader`, which calls this with the sending peer's
+    /// `Peer::signing_key`. `false` if there's no signature to check, the
+    /// key is malformed, or it simply doesn't match.
+    pub fn verify_signature(&self, verifying_key_base64: &str) -> bool {
+        let Some(signature) = &self.signature else {
+            return false;
+        };
+        let Ok(key_bytes) = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, verifying_key_base64)
+        else {
+            return false;
+        };
+        let Ok(key_bytes): Result<[u8; 32], _> = key_bytes.try_into() else {
+            return false;
+        };
+        let Ok(verifying_key) = ed25519_dalek::VerifyingKey::from_bytes(&key_bytes) else {
+            return false;
+        };
+        let Ok(sig_bytes) = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, signature) else {
+            return false;
+        };
+        let Ok(sig_bytes): Result<[u8; 64], _> = sig_bytes.try_into() else {
+            return false;
+        };
+        let signature = ed25519_dalek::Signature::from_bytes(&sig_bytes);
+        ed25519_dalek::Verifier::verify(&verifying_key, &self.signable_bytes(), &signature).is_ok()
     }
 }
 
@@ -0,0 +1,59 @@
+/// Logical channel a frame travels on within one `network::PeerConnection`,
+/// tagged with a leading byte on the wire via `frame`/`unframe`. Lets bulk
+/// file-transfer traffic share a connection with latency-sensitive chat and
+/// control traffic without queuing up in front of it - see
+/// `PeerConnection::send_on_channel`, which drains `Control`, then `Priority`,
+/// then `Chat`, then `FileTransfer`, in that order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Channel {
+    /// Handshakes - serviced first.
+    Control,
+    /// Small, latency-sensitive application messages (`MessageType::Typing`,
+    /// read receipts) that shouldn't have to wait behind a large `Chat` frame
+    /// already queued on the same connection - see `PeerConnection::send_priority_message`.
+    Priority,
+    /// Normal chat messages.
+    Chat,
+    /// Bulk file-transfer chunks - serviced last, and the only channel with
+    /// a bounded outbound queue, so a big transfer can't starve Control/Priority/Chat
+    /// traffic or buffer unboundedly if the peer can't keep up.
+    FileTransfer,
+}
+
+impl Channel {
+    fn tag(self) -> u8 {
+        match self {
+            Channel::Control => 0,
+            Channel::Chat => 1,
+            Channel::FileTransfer => 2,
+            Channel::Priority => 3,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Option<Channel> {
+        match tag {
+            0 => Some(Channel::Control),
+            1 => Some(Channel::Chat),
+            2 => Some(Channel::FileTransfer),
+            3 => Some(Channel::Priority),
+            _ => None,
+        }
+    }
+}
+
+/// Prefixes `payload` with `channel`'s wire tag.
+pub fn frame(channel: Channel, payload: &[u8]) -> Vec<u8> {
+    let mut framed = Vec::with_capacity(payload.len() + 1);
+    framed.push(channel.tag());
+    framed.extend_from_slice(payload);
+    framed
+}
+
+/// Splits a received frame back into its channel and payload. Both ends of a
+/// connection are always the same version of this codebase, so an unknown
+/// tag only means a corrupt frame - returns `None` rather than erroring, and
+/// it's on the caller to decide whether to drop it or treat it as fatal.
+pub fn unframe(raw: &[u8]) -> Option<(Channel, &[u8])> {
+    let (&tag, payload) = raw.split_first()?;
+    Channel::from_tag(tag).map(|channel| (channel, payload))
+}
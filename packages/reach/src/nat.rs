@@ -0,0 +1,101 @@
+use anyhow::{Result, anyhow};
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::{TcpStream, UdpSocket};
+use tokio::time::timeout;
+
+/// Public STUN server used when the caller doesn't configure one.
+pub const DEFAULT_STUN_SERVER: &str = "stun.l.google.com:19302";
+
+const STUN_MAGIC_COOKIE: [u8; 4] = [0x21, 0x12, 0xA4, 0x42];
+const ATTR_MAPPED_ADDRESS: u16 = 0x0001;
+const ATTR_XOR_MAPPED_ADDRESS: u16 = 0x0020;
+
+/// Discovers our public-facing `ip:port` via a STUN Binding Request
+/// (RFC 5389), so it can be shared with a peer for hole punching.
+pub async fn discover_public_address(stun_server: &str) -> Result<SocketAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    socket.connect(stun_server).await?;
+
+    let transaction_id: [u8; 12] = rand::random();
+    let mut request = Vec::with_capacity(20);
+    request.extend_from_slice(&[0x00, 0x01]); // Binding Request
+    request.extend_from_slice(&[0x00, 0x00]); // Message length (no attributes)
+    request.extend_from_slice(&STUN_MAGIC_COOKIE);
+    request.extend_from_slice(&transaction_id);
+
+    socket.send(&request).await?;
+
+    let mut buf = [0u8; 512];
+    let n = timeout(Duration::from_secs(3), socket.recv(&mut buf))
+        .await
+        .map_err(|_| anyhow!("STUN server {} did not respond within 3s", stun_server))??;
+
+    parse_mapped_address(&buf[..n], &transaction_id)
+}
+
+fn parse_mapped_address(response: &[u8], transaction_id: &[u8; 12]) -> Result<SocketAddr> {
+    if response.len() < 20 {
+        return Err(anyhow!("STUN response too short"));
+    }
+    if &response[8..20] != transaction_id {
+        return Err(anyhow!("STUN response transaction ID mismatch"));
+    }
+
+    let mut offset = 20;
+    while offset + 4 <= response.len() {
+        let attr_type = u16::from_be_bytes([response[offset], response[offset + 1]]);
+        let attr_len = u16::from_be_bytes([response[offset + 2], response[offset + 3]]) as usize;
+        let value_start = offset + 4;
+        let value_end = value_start + attr_len;
+        if value_end > response.len() {
+            break;
+        }
+        let value = &response[value_start..value_end];
+
+        if attr_type == ATTR_XOR_MAPPED_ADDRESS && value.len() >= 8 && value[1] == 0x01 {
+            let port = u16::from_be_bytes([value[2], value[3]]) ^ 0x2112;
+            let ip = [
+                value[4] ^ STUN_MAGIC_COOKIE[0],
+                value[5] ^ STUN_MAGIC_COOKIE[1],
+                value[6] ^ STUN_MAGIC_COOKIE[2],
+                value[7] ^ STUN_MAGIC_COOKIE[3],
+            ];
+            return Ok(SocketAddr::from((ip, port)));
+        } else if attr_type == ATTR_MAPPED_ADDRESS && value.len() >= 8 && value[1] == 0x01 {
+            let port = u16::from_be_bytes([value[2], value[3]]);
+            let ip = [value[4], value[5], value[6], value[7]];
+            return Ok(SocketAddr::from((ip, port)));
+        }
+
+        // Attributes are padded to a 4-byte boundary.
+        let padding = (4 - attr_len % 4) % 4;
+        offset = value_end + padding;
+    }
+
+    Err(anyhow!("STUN response missing a mapped address attribute"))
+}
+
+/// Attempts simultaneous-open TCP hole punching against a peer whose public
+/// address was learned via STUN. Both sides are expected to call this at
+/// roughly the same time.
+///
+/// This is a best-effort approximation, not a full simultaneous-open
+/// implementation: a production version needs `SO_REUSEADDR`/`SO_REUSEPORT`
+/// to dial out from the very socket already bound for listening, which the
+/// standard library doesn't expose without an extra crate. Instead we race a
+/// short burst of plain outbound connection attempts, which is enough to
+/// traverse NATs that map the outbound attempt to the same external port
+/// already opened by `start_listening`.
+pub async fn punch(peer_addr: SocketAddr, attempts: u32) -> Result<TcpStream> {
+    let mut last_err = None;
+    for attempt in 0..attempts {
+        match timeout(Duration::from_millis(500), TcpStream::connect(peer_addr)).await {
+            Ok(Ok(stream)) => return Ok(stream),
+            Ok(Err(e)) => last_err = Some(anyhow!(e)),
+            Err(_) => last_err = Some(anyhow!("connection attempt {} timed out", attempt)),
+        }
+    }
+
+    Err(last_err.unwrap_or_else(|| anyhow!("Hole punch to {} failed", peer_addr)))
+}
@@ -1,292 +1,2743 @@
 use anyhow::{Result, anyhow};
-use log::{debug, error, info};
-use std::collections::HashMap;
+use arc_swap::ArcSwap;
+use chrono::Utc;
+use log::{debug, error, info, warn};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::{RwLock, mpsc};
+use tokio::sync::{RwLock, Semaphore, mpsc};
 use uuid::Uuid;
 
 use crate::crypto::CryptoEngine;
 use crate::identity::Identity;
-use crate::message::{Message, MessageType};
-use crate::peer::Peer;
+use crate::message::{Message, MessageType, SystemEvent};
+use crate::peer::{Peer, TransportKind};
+use crate::quic::QuicTransport;
+use crate::ratelimit::ConnectionRateLimiter;
+use crate::multiplex::{self, Channel};
+use crate::throttle::TokenBucket;
+use crate::transport::Transport;
+
+/// Max accepts allowed from a single IP within `RATE_LIMIT_WINDOW`, and how
+/// many handshakes (across all IPs) may be in flight at once - see
+/// `ratelimit::ConnectionRateLimiter` and `handle_incoming_connection`.
+const RATE_LIMIT_MAX_ACCEPTS: usize = 20;
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(10);
+const MAX_CONCURRENT_HANDSHAKES: usize = 64;
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+/// How many file-transfer chunks may be queued on a connection before
+/// `send_file_chunk` starts applying backpressure - see `Channel::FileTransfer`.
+const FILE_TRANSFER_QUEUE_DEPTH: usize = 64;
+/// How many received `Message`s may sit in `NetworkManager::message_receiver`
+/// before further ones are dropped (and logged) - see `NetworkManager::deliver`.
+const MESSAGE_QUEUE_CAPACITY: usize = 256;
+/// How many times `send_ephemeral` resends an unacknowledged UDP datagram
+/// before giving up on it and falling back to TCP.
+const EPHEMERAL_MAX_RETRIES: u32 = 3;
+/// How long `send_ephemeral` waits for an `Ack` datagram before retrying.
+const EPHEMERAL_ACK_TIMEOUT: Duration = Duration::from_millis(500);
+/// How many distinct oversized-chat-message reassemblies a single connection
+/// tracks at once before the oldest incomplete one is evicted - see
+/// `PeerConnection::chat_reassembly`. `chat_chunk::MAX_CHAT_CHUNKS` caps how
+/// big any *one* reassembly can claim to be, but without this a peer could
+/// instead open one chunk each under an unbounded number of `manifest_id`s
+/// and never finish any of them - the same unbounded-growth problem spread
+/// across map entries instead of one `Vec`. Same bound-by-eviction shape as
+/// `dedup::PER_PEER_CAPACITY`.
+const MAX_CONCURRENT_CHAT_REASSEMBLIES: usize = 64;
 // Removed x25519_dalek imports - using simplified crypto
 
-pub struct PeerConnection {
-    pub peer: Peer,
-    pub stream: Arc<RwLock<TcpStream>>,
-    pub shared_secret: Option<[u8; 32]>,
-}
+type ListenerHandles = Vec<(SocketAddr, tokio::task::JoinHandle<()>)>;
+
+/// What `PeerConnection::receive_message` returns - a connection carries
+/// ordinary chat frames and file-transfer chunks on the same stream (see
+/// `multiplex::Channel`), and the two need very different handling once
+/// decrypted: a chat frame is handed to `protocol::decode_message`, while a
+/// file chunk is written straight to disk by `spawn_reader`.
+enum ReceivedFrame {
+    Chat(String),
+    FileChunk(crate::file_transfer::FileChunkFrame, Vec<u8>),
+}
+
+pub struct PeerConnection {
+    pub peer: Peer,
+    transport: Arc<RwLock<Box<dyn Transport>>>,
+    /// Derived fresh by `establish_shared_secret` on every handshake - never
+    /// carried over from a previous `PeerConnection` for the same peer, so a
+    /// migrated connection (see `activate_connection`'s stale-entry
+    /// check) always re-keys rather than reusing the old address's secret.
+    pub shared_secret: Option<[u8; 32]>,
+    /// Identifies this specific connection attempt (not the peer) across
+    /// logs, so reconnects to the same peer don't get conflated when
+    /// tracking down "which send failed".
+    pub connection_id: Uuid,
+    /// Wire format agreed on with this peer during the handshake - see
+    /// `protocol::negotiate`. Defaults to `protocol::LEGACY_VERSION` (JSON)
+    /// until the handshake sets it to whatever was actually negotiated.
+    pub protocol_version: u8,
+    /// Whether both ends advertised `Capabilities::COMPRESSION` during the
+    /// handshake - see `protocol::encode_message`. `false` until the
+    /// handshake sets it, same as `protocol_version`.
+    pub compression_enabled: bool,
+    /// Caps outgoing bytes/sec when `Config::upload_limit_bytes_per_sec` is
+    /// set - see `set_rate_limits`. `None` means unlimited.
+    upload_bucket: Option<Arc<TokenBucket>>,
+    /// Caps incoming bytes/sec when `Config::download_limit_bytes_per_sec` is set.
+    download_bucket: Option<Arc<TokenBucket>>,
+    /// Feeds the writer task spawned in `new` - see `Channel`. Control,
+    /// priority, and chat queues are unbounded (small, latency-sensitive); the
+    /// file-transfer queue is bounded so a slow peer applies backpressure to
+    /// the sender instead of this buffering an unbounded amount of data.
+    control_tx: mpsc::UnboundedSender<Vec<u8>>,
+    priority_tx: mpsc::UnboundedSender<Vec<u8>>,
+    chat_tx: mpsc::UnboundedSender<Vec<u8>>,
+    file_tx: mpsc::Sender<Vec<u8>>,
+    /// In-flight `chat_chunk::ChatChunkFrame` reassembly, keyed by manifest
+    /// id - see `send_message` and `receive_message`. Bounded by
+    /// `MAX_CONCURRENT_CHAT_REASSEMBLIES`; `chat_reassembly_order` tracks
+    /// insertion order so the oldest can be evicted once that's hit.
+    chat_reassembly: HashMap<Uuid, crate::chat_chunk::ChatReassembly>,
+    chat_reassembly_order: VecDeque<Uuid>,
+}
+
+impl PeerConnection {
+    /// `transport` carries frames over whatever's underneath it - TCP,
+    /// WebSocket, or (for tests) an in-memory duplex - see `transport::Transport`.
+    ///
+    /// Spawns a single writer task that owns all outbound I/O for this
+    /// connection, draining `control_tx`/`priority_tx`/`chat_tx`/`file_tx` in
+    /// that priority order (see `Channel`) so a backlog of file-transfer (or
+    /// bulky chat) frames can never delay a control or priority frame queued
+    /// behind it.
+    pub fn new(peer: Peer, transport: impl Transport + 'static) -> Self {
+        let transport: Arc<RwLock<Box<dyn Transport>>> = Arc::new(RwLock::new(Box::new(transport)));
+        let (control_tx, mut control_rx) = mpsc::unbounded_channel::<Vec<u8>>();
+        let (priority_tx, mut priority_rx) = mpsc::unbounded_channel::<Vec<u8>>();
+        let (chat_tx, mut chat_rx) = mpsc::unbounded_channel::<Vec<u8>>();
+        let (file_tx, mut file_rx) = mpsc::channel::<Vec<u8>>(FILE_TRANSFER_QUEUE_DEPTH);
+
+        let writer_transport = transport.clone();
+        tokio::spawn(async move {
+            loop {
+                let frame = tokio::select! {
+                    biased;
+                    frame = control_rx.recv() => frame,
+                    frame = priority_rx.recv() => frame,
+                    frame = chat_rx.recv() => frame,
+                    frame = file_rx.recv() => frame,
+                };
+                let Some(frame) = frame else { break };
+
+                let mut transport = writer_transport.write().await;
+                if let Err(e) = transport.write_frame(&frame).await {
+                    warn!("Connection writer stopped after a failed write: {}", e);
+                    break;
+                }
+            }
+        });
+
+        PeerConnection {
+            peer,
+            transport,
+            shared_secret: None,
+            connection_id: Uuid::new_v4(),
+            protocol_version: crate::protocol::LEGACY_VERSION,
+            compression_enabled: false,
+            upload_bucket: None,
+            download_bucket: None,
+            control_tx,
+            priority_tx,
+            chat_tx,
+            file_tx,
+            chat_reassembly: HashMap::new(),
+            chat_reassembly_order: VecDeque::new(),
+        }
+    }
+
+    /// Drops `manifest_id`'s in-flight reassembly, if any, from both
+    /// `chat_reassembly` and `chat_reassembly_order` - used both when a
+    /// reassembly completes normally and when it's evicted unfinished for
+    /// being over `MAX_CONCURRENT_CHAT_REASSEMBLIES`, so the two always stay
+    /// in sync.
+    fn forget_chat_reassembly(&mut self, manifest_id: &Uuid) {
+        self.chat_reassembly.remove(manifest_id);
+        self.chat_reassembly_order.retain(|id| id != manifest_id);
+    }
+
+    /// Installs token buckets for this connection from the configured
+    /// upload/download limits. Called once the connection is established -
+    /// see `handle_incoming_connection` and `complete_outgoing_connection`.
+    pub fn set_rate_limits(&mut self, upload_bytes_per_sec: Option<u64>, download_bytes_per_sec: Option<u64>) {
+        self.upload_bucket = upload_bytes_per_sec.map(|rate| Arc::new(TokenBucket::new(rate)));
+        self.download_bucket = download_bytes_per_sec.map(|rate| Arc::new(TokenBucket::new(rate)));
+    }
+
+    /// Queues `data` on `channel`'s outbound queue and returns as soon as
+    /// it's queued, not once it's written - the writer task spawned in `new`
+    /// does the actual I/O, so a slow write on one channel can't block a
+    /// send on another. A write failure surfaces to the caller on their
+    /// *next* send on this connection (the queue's receiver is dropped when
+    /// the writer task exits), not on the send that was in flight when it failed.
+    fn send_on_channel(&self, channel: Channel, data: Vec<u8>) -> Result<()> {
+        let framed = multiplex::frame(channel, &data);
+        match channel {
+            Channel::Control => self
+                .control_tx
+                .send(framed)
+                .map_err(|_| anyhow!("Connection closed")),
+            Channel::Priority => self
+                .priority_tx
+                .send(framed)
+                .map_err(|_| anyhow!("Connection closed")),
+            Channel::Chat => self
+                .chat_tx
+                .send(framed)
+                .map_err(|_| anyhow!("Connection closed")),
+            Channel::FileTransfer => self.file_tx.try_send(framed).map_err(|e| match e {
+                mpsc::error::TrySendError::Full(_) => anyhow!("File-transfer queue full, try again"),
+                mpsc::error::TrySendError::Closed(_) => anyhow!("Connection closed"),
+            }),
+        }
+    }
+
+    pub async fn send_message(&mut self, message: &str) -> Result<()> {
+        if let Some(secret) = &self.shared_secret {
+            let encrypted = CryptoEngine::encrypt_message(message, secret)?;
+            let data = format!("{}\n", encrypted).into_bytes();
+
+            if let Some(bucket) = &self.upload_bucket {
+                bucket.consume(data.len()).await;
+            }
+
+            if data.len() <= crate::chat_chunk::CHAT_CHUNK_SIZE {
+                let mut framed = Vec::with_capacity(data.len() + 1);
+                framed.push(0u8);
+                framed.extend_from_slice(&data);
+                self.send_on_channel(Channel::Chat, framed)?;
+            } else {
+                self.send_chunked_chat_frame(data)?;
+            }
+
+            debug!(
+                "[conn {}] Sent encrypted message to peer {}",
+                self.connection_id, self.peer.id
+            );
+            Ok(())
+        } else {
+            Err(anyhow!("No shared secret established"))
+        }
+    }
+
+    /// Like `send_message`, but queues on `Channel::Priority` instead of
+    /// `Channel::Chat`, so it can't get stuck behind a large chat frame
+    /// already queued on this connection - used for small, latency-sensitive
+    /// application messages (`MessageType::Typing`, read receipts), not chat
+    /// content. No chunking support, unlike `send_message`: every caller of
+    /// this method sends something well under `chat_chunk::CHAT_CHUNK_SIZE`.
+    pub async fn send_priority_message(&mut self, message: &str) -> Result<()> {
+        if let Some(secret) = &self.shared_secret {
+            let encrypted = CryptoEngine::encrypt_message(message, secret)?;
+            let data = format!("{}\n", encrypted).into_bytes();
+
+            if let Some(bucket) = &self.upload_bucket {
+                bucket.consume(data.len()).await;
+            }
+
+            self.send_on_channel(Channel::Priority, data)?;
+
+            debug!(
+                "[conn {}] Sent priority message to peer {}",
+                self.connection_id, self.peer.id
+            );
+            Ok(())
+        } else {
+            Err(anyhow!("No shared secret established"))
+        }
+    }
+
+    /// Splits an encrypted chat frame too big for one `Channel::Chat` write
+    /// into numbered `chat_chunk::ChatChunkFrame`s under one manifest id -
+    /// see `send_message` and `receive_message`'s reassembly.
+    fn send_chunked_chat_frame(&self, data: Vec<u8>) -> Result<()> {
+        let manifest_id = Uuid::new_v4();
+        let chunks: Vec<&[u8]> = data.chunks(crate::chat_chunk::CHAT_CHUNK_SIZE).collect();
+        let total = chunks.len() as u32;
+
+        debug!(
+            "[conn {}] Splitting {}-byte chat frame into {} chunks ({})",
+            self.connection_id,
+            data.len(),
+            total,
+            manifest_id
+        );
+
+        for (index, chunk) in chunks.into_iter().enumerate() {
+            let frame = crate::chat_chunk::ChatChunkFrame {
+                manifest_id,
+                index: index as u32,
+                total,
+                data: chunk.to_vec(),
+            };
+            let mut framed = Vec::with_capacity(chunk.len() + 17);
+            framed.push(1u8);
+            framed.extend_from_slice(&frame.encode()?);
+            self.send_on_channel(Channel::Chat, framed)?;
+        }
+
+        Ok(())
+    }
+
+    /// Queues a chunk of file data on the `Channel::FileTransfer` queue -
+    /// lowest priority and bounded, so a slow peer or network backs up the
+    /// sender instead of this buffering unboundedly.
+    fn send_file_chunk(&self, data: Vec<u8>) -> Result<()> {
+        self.send_on_channel(Channel::FileTransfer, data)
+    }
+
+    /// Encrypts one chunk of `transfer_id` and queues it on `Channel::FileTransfer` -
+    /// see `file_transfer::FileChunkFrame`. `CryptoEngine::encrypt_message` only
+    /// takes text, so `data` is base64-encoded before encryption the same way
+    /// `connect_via_serial`'s handshake treats a public key as text.
+    pub fn send_encrypted_file_chunk(&self, transfer_id: Uuid, index: u64, data: &[u8]) -> Result<()> {
+        let secret = self
+            .shared_secret
+            .ok_or_else(|| anyhow!("No shared secret established"))?;
+        let encoded = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, data);
+        let ciphertext = CryptoEngine::encrypt_message(&encoded, &secret)?;
+        let frame = crate::file_transfer::FileChunkFrame { transfer_id, index, ciphertext };
+        self.send_file_chunk(frame.encode()?)
+    }
+
+    async fn receive_message(&mut self) -> Result<ReceivedFrame> {
+        if let Some(secret) = self.shared_secret {
+            loop {
+                let mut transport = self.transport.write().await;
+                let frame = transport.read_frame().await?;
+                drop(transport);
+
+                let Some((channel, payload)) = multiplex::unframe(&frame) else {
+                    debug!(
+                        "[conn {}] Dropped untagged frame from peer {}",
+                        self.connection_id, self.peer.id
+                    );
+                    continue;
+                };
+
+                if let Some(bucket) = &self.download_bucket {
+                    bucket.consume(payload.len()).await;
+                }
+
+                match channel {
+                    Channel::Chat => {
+                        let Some((&tag, body)) = payload.split_first() else {
+                            debug!("[conn {}] Dropped empty chat frame from peer {}", self.connection_id, self.peer.id);
+                            continue;
+                        };
+
+                        let whole = match tag {
+                            0 => body.to_vec(),
+                            1 => {
+                                let chunk = crate::chat_chunk::ChatChunkFrame::decode(body)?;
+                                if let std::collections::hash_map::Entry::Vacant(entry) =
+                                    self.chat_reassembly.entry(chunk.manifest_id)
+                                {
+                                    match crate::chat_chunk::ChatReassembly::new(chunk.total) {
+                                        Some(assembly) => {
+                                            entry.insert(assembly);
+                                            self.chat_reassembly_order.push_back(chunk.manifest_id);
+                                            while self.chat_reassembly_order.len() > MAX_CONCURRENT_CHAT_REASSEMBLIES {
+                                                if let Some(oldest) = self.chat_reassembly_order.pop_front() {
+                                                    self.chat_reassembly.remove(&oldest);
+                                                    debug!(
+                                                        "[conn {}] Evicting stalled chat reassembly {} from peer {}: over the {} concurrent limit",
+                                                        self.connection_id, oldest, self.peer.id, MAX_CONCURRENT_CHAT_REASSEMBLIES
+                                                    );
+                                                }
+                                            }
+                                        }
+                                        None => {
+                                            debug!(
+                                                "[conn {}] Dropping chat chunk frame from peer {} claiming out-of-range total {}",
+                                                self.connection_id, self.peer.id, chunk.total
+                                            );
+                                            continue;
+                                        }
+                                    }
+                                }
+                                let assembly = self
+                                    .chat_reassembly
+                                    .get_mut(&chunk.manifest_id)
+                                    .expect("just inserted or already present above");
+                                match assembly.add(chunk.index, chunk.data) {
+                                    Some(whole) => {
+                                        self.forget_chat_reassembly(&chunk.manifest_id);
+                                        whole
+                                    }
+                                    None => continue,
+                                }
+                            }
+                            other => {
+                                debug!(
+                                    "[conn {}] Dropped chat frame with unknown tag {} from peer {}",
+                                    self.connection_id, other, self.peer.id
+                                );
+                                continue;
+                            }
+                        };
+
+                        let encrypted_data = String::from_utf8_lossy(&whole);
+                        let encrypted_data = encrypted_data.trim();
+
+                        let decrypted = CryptoEngine::decrypt_message(encrypted_data, &secret)?;
+                        debug!(
+                            "[conn {}] Received and decrypted message from peer {}",
+                            self.connection_id, self.peer.id
+                        );
+
+                        return Ok(ReceivedFrame::Chat(decrypted));
+                    }
+                    Channel::FileTransfer => {
+                        let chunk_frame = crate::file_transfer::FileChunkFrame::decode(payload)?;
+                        let encoded_chunk = CryptoEngine::decrypt_message(&chunk_frame.ciphertext, &secret)?;
+                        let data = base64::Engine::decode(
+                            &base64::engine::general_purpose::STANDARD,
+                            &encoded_chunk,
+                        )?;
+                        debug!(
+                            "[conn {}] Received file-transfer chunk {} of transfer {} from peer {} ({} bytes)",
+                            self.connection_id, chunk_frame.index, chunk_frame.transfer_id, self.peer.id, data.len()
+                        );
+                        return Ok(ReceivedFrame::FileChunk(chunk_frame, data));
+                    }
+                    Channel::Priority => {
+                        let encrypted_data = String::from_utf8_lossy(payload);
+                        let encrypted_data = encrypted_data.trim();
+                        let decrypted = CryptoEngine::decrypt_message(encrypted_data, &secret)?;
+                        debug!(
+                            "[conn {}] Received and decrypted priority message from peer {}",
+                            self.connection_id, self.peer.id
+                        );
+                        return Ok(ReceivedFrame::Chat(decrypted));
+                    }
+                    Channel::Control => {
+                        debug!(
+                            "[conn {}] Unexpected control frame from peer {} after handshake, ignoring",
+                            self.connection_id, self.peer.id
+                        );
+                    }
+                }
+            }
+        } else {
+            Err(anyhow!("No shared secret established"))
+        }
+    }
+
+    /// Sends a plaintext frame, used only for a handshake exchanged before a
+    /// shared secret exists (the WebSocket path - TCP instead runs
+    /// `noise::handshake` directly on the raw stream before wrapping it here).
+    pub async fn send_handshake(&mut self, data: &str) -> Result<()> {
+        self.send_on_channel(Channel::Control, data.as_bytes().to_vec())
+    }
+
+    /// Reads a plaintext frame, the `send_handshake` counterpart.
+    pub async fn receive_handshake(&mut self) -> Result<String> {
+        let mut transport = self.transport.write().await;
+        let frame = transport.read_frame().await?;
+        drop(transport);
+        let (_channel, payload) =
+            multiplex::unframe(&frame).ok_or_else(|| anyhow!("Received untagged handshake frame"))?;
+        Ok(String::from_utf8_lossy(payload).into_owned())
+    }
+
+    pub fn establish_shared_secret(&mut self, our_private: &[u8; 32], their_public: &[u8; 32]) {
+        self.shared_secret = Some(CryptoEngine::generate_shared_secret(
+            our_private,
+            their_public,
+        ));
+        info!(
+            "[conn {}] Shared secret established with peer {}",
+            self.connection_id, self.peer.id
+        );
+    }
+}
+
+/// Counts peers across the TCP/WebSocket connection map and the separate QUIC
+/// datagram map, for enforcing `Config::max_peers` - see
+/// `NetworkManager::connect_to_peer` and the accept loop in `start_listening`.
+async fn peer_count(
+    connections: &Arc<RwLock<HashMap<Uuid, PeerConnection>>>,
+    quic_peers: &Arc<RwLock<HashMap<Uuid, Peer>>>,
+) -> usize {
+    connections.read().await.len() + quic_peers.read().await.len()
+}
+
+/// Builds a fresh `Vec<Peer>` from the connection and QUIC peer maps, used to
+/// refresh `NetworkManager::peer_snapshot` after a membership/status change.
+async fn snapshot_peers(
+    connections: &Arc<RwLock<HashMap<Uuid, PeerConnection>>>,
+    quic_peers: &Arc<RwLock<HashMap<Uuid, Peer>>>,
+) -> Vec<Peer> {
+    let mut peers: Vec<Peer> = connections
+        .read()
+        .await
+        .values()
+        .map(|conn| conn.peer.clone())
+        .collect();
+    peers.extend(quic_peers.read().await.values().cloned());
+    peers
+}
+
+/// Delivers `message` to the local receive queue (`NetworkManager::receive_messages`
+/// drains it) without blocking the sender. The queue is bounded (see
+/// `MESSAGE_QUEUE_CAPACITY`) so a stalled consumer can't grow memory
+/// unboundedly - instead it fills up and this starts dropping messages,
+/// which is logged here since none of this function's callers are in a
+/// position to retry or otherwise react to it themselves.
+fn deliver(sender: &mpsc::Sender<Message>, message: Message) {
+    if let Err(e) = sender.try_send(message) {
+        warn!("Local message queue full or closed, dropping a message: {}", e);
+    }
+}
+
+pub struct NetworkManager {
+    identity: Identity,
+    connections: Arc<RwLock<HashMap<Uuid, PeerConnection>>>,
+    message_sender: mpsc::Sender<Message>,
+    message_receiver: Arc<RwLock<mpsc::Receiver<Message>>>,
+    /// Handle to the spawned accept loop, so `shutdown()` can actually stop accepting
+    /// new connections and release the listening port instead of leaking the task.
+    listener_handle: Arc<RwLock<Option<tokio::task::JoinHandle<()>>>>,
+    /// Handles for listeners started via `start_additional_listener`, one
+    /// per extra interface - see `Config::additional_listen_addresses`.
+    /// Stopped alongside `listener_handle` by `stop_listening`/`shutdown`.
+    extra_listener_handles: Arc<RwLock<ListenerHandles>>,
+    /// Alongside-TCP datagram transport, started on demand by `start_quic_transport`.
+    quic: Arc<RwLock<Option<Arc<QuicTransport>>>>,
+    /// Peers reached over `quic` rather than a `PeerConnection`/TcpStream.
+    quic_peers: Arc<RwLock<HashMap<Uuid, Peer>>>,
+    /// Handle to the spawned WebSocket accept loop, stopped the same way as `listener_handle`.
+    ws_listener_handle: Arc<RwLock<Option<tokio::task::JoinHandle<()>>>>,
+    /// Published snapshot of `get_connected_peers()`, refreshed on every
+    /// membership/status change so UI polling never takes a lock shared with
+    /// the message send/receive path.
+    peer_snapshot: Arc<ArcSwap<Vec<Peer>>>,
+    /// Per-IP accept rate limit for the TCP listener.
+    rate_limiter: Arc<ConnectionRateLimiter>,
+    /// Bounds how many handshakes can be in progress at once, so a burst of
+    /// connections that never finish their handshake can't pile up memory or
+    /// file descriptors indefinitely.
+    handshake_semaphore: Arc<Semaphore>,
+    /// Do Not Disturb - suppresses notification sounds for everything except
+    /// mentions while set. See `notify::notify` and `set_dnd`.
+    dnd: Arc<AtomicBool>,
+    /// Per-peer and global traffic/reconnect/RTT counters - see `get_stats`.
+    stats: Arc<RwLock<crate::stats::NetworkStats>>,
+    /// Recently seen message IDs per peer, so a retransmitted or
+    /// reconnect-replayed message is dropped before it reaches history,
+    /// receipts, or notifications - see `dedup::Dedup`.
+    dedup: Arc<RwLock<crate::dedup::Dedup>>,
+    /// Oneshot senders waiting on an application-level `MessageType::Ack`
+    /// for an in-flight `send_ephemeral` datagram, keyed by that message's
+    /// id - see `send_ephemeral`.
+    ephemeral_acks: Arc<RwLock<HashMap<Uuid, tokio::sync::oneshot::Sender<()>>>>,
+    /// Offers we've made that are still waiting on the peer's decision, or
+    /// are actively streaming - keyed by transfer id. See `offer_file`.
+    outgoing_transfers: Arc<RwLock<HashMap<Uuid, crate::file_transfer::OutgoingTransfer>>>,
+    /// Offers we've received and not yet accepted or rejected - keyed by
+    /// transfer id, with the offering peer's id alongside the offer itself.
+    /// Populated by `spawn_reader` on `MessageType::FileOffer`, consumed by
+    /// `accept_file`/`reject_file`.
+    pending_offers: Arc<RwLock<HashMap<Uuid, (Uuid, crate::file_transfer::FileOffer)>>>,
+    /// Transfers we've accepted and are writing to disk - keyed by transfer
+    /// id. See `accept_file` and `file_transfer::IncomingTransfer`.
+    incoming_transfers: Arc<RwLock<HashMap<Uuid, crate::file_transfer::IncomingTransfer>>>,
+    /// Inbound connections that finished the handshake but are waiting on
+    /// explicit approval, keyed by peer id - populated instead of
+    /// `connections` when `Config::auto_accept_connections` is `false`. See
+    /// `accept_pending`/`reject_pending` and `/accept`.
+    pending_connections: Arc<RwLock<HashMap<Uuid, PeerConnection>>>,
+    /// Our own broadcast encryption key, generated on first `broadcast` call
+    /// and handed out to peers as they're caught up by
+    /// `ensure_sender_key_distributed` - see `/all`.
+    sender_key: Arc<RwLock<Option<[u8; 32]>>>,
+    /// Peers `sender_key` has already been sent to, so `broadcast` only
+    /// distributes it to peers that joined since the last call.
+    sender_key_recipients: Arc<RwLock<HashSet<Uuid>>>,
+    /// Broadcast encryption keys received from peers via `MessageType::SenderKey`,
+    /// keyed by sender - see `decrypt_broadcast` and `SessionManager::merge_message`.
+    peer_sender_keys: Arc<RwLock<HashMap<Uuid, [u8; 32]>>>,
+}
+
+impl NetworkManager {
+    pub async fn new(identity: Identity) -> Result<Self> {
+        let (message_sender, message_receiver) = mpsc::channel(MESSAGE_QUEUE_CAPACITY);
+
+        Ok(NetworkManager {
+            identity,
+            connections: Arc::new(RwLock::new(HashMap::new())),
+            message_sender,
+            message_receiver: Arc::new(RwLock::new(message_receiver)),
+            listener_handle: Arc::new(RwLock::new(None)),
+            extra_listener_handles: Arc::new(RwLock::new(Vec::new())),
+            quic: Arc::new(RwLock::new(None)),
+            quic_peers: Arc::new(RwLock::new(HashMap::new())),
+            ws_listener_handle: Arc::new(RwLock::new(None)),
+            peer_snapshot: Arc::new(ArcSwap::from_pointee(Vec::new())),
+            rate_limiter: Arc::new(ConnectionRateLimiter::new(
+                RATE_LIMIT_MAX_ACCEPTS,
+                RATE_LIMIT_WINDOW,
+            )),
+            handshake_semaphore: Arc::new(Semaphore::new(MAX_CONCURRENT_HANDSHAKES)),
+            dnd: Arc::new(AtomicBool::new(false)),
+            stats: Arc::new(RwLock::new(crate::stats::NetworkStats::default())),
+            dedup: Arc::new(RwLock::new(crate::dedup::Dedup::new())),
+            ephemeral_acks: Arc::new(RwLock::new(HashMap::new())),
+            outgoing_transfers: Arc::new(RwLock::new(HashMap::new())),
+            pending_offers: Arc::new(RwLock::new(HashMap::new())),
+            incoming_transfers: Arc::new(RwLock::new(HashMap::new())),
+            pending_connections: Arc::new(RwLock::new(HashMap::new())),
+            sender_key: Arc::new(RwLock::new(None)),
+            sender_key_recipients: Arc::new(RwLock::new(HashSet::new())),
+            peer_sender_keys: Arc::new(RwLock::new(HashMap::new())),
+        })
+    }
+
+    /// Toggles Do Not Disturb - see the `dnd` field doc comment.
+    pub fn set_dnd(&self, enabled: bool) {
+        self.dnd.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn is_dnd(&self) -> bool {
+        self.dnd.load(Ordering::Relaxed)
+    }
+
+    /// Snapshot of accumulated traffic/reconnect/RTT counters - see `stats::NetworkStats`.
+    pub async fn get_stats(&self) -> crate::stats::NetworkStats {
+        self.stats.read().await.clone()
+    }
+
+    /// Recomputes and publishes `peer_snapshot` from the current connection
+    /// maps. Cheap to call after any membership or status change - readers
+    /// never block on it.
+    async fn publish_peer_snapshot(&self) {
+        let peers = snapshot_peers(&self.connections, &self.quic_peers).await;
+        self.peer_snapshot.store(Arc::new(peers));
+    }
+
+    /// Spawns a background task that pings every connected peer on `interval` and
+    /// marks it disconnected (emitting a system message) if it hasn't been reachable
+    /// for longer than `timeout`, so silent TCP drops don't leave ghost peers around.
+    pub fn start_heartbeat_monitor(&self, interval: std::time::Duration, timeout: chrono::Duration) {
+        let connections = self.connections.clone();
+        let quic_peers = self.quic_peers.clone();
+        let peer_snapshot = self.peer_snapshot.clone();
+        let identity = self.identity.clone();
+        let message_sender = self.message_sender.clone();
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+
+                let heartbeat = Message::heartbeat_message(identity.user_id, identity.get_display_name());
+
+                let mut dead_peers = Vec::new();
+
+                {
+                    let mut conns = connections.write().await;
+                    for (peer_id, connection) in conns.iter_mut() {
+                        let Ok(encoded) = crate::protocol::encode_message(&heartbeat, connection.protocol_version, connection.compression_enabled) else {
+                            continue;
+                        };
+                        match connection.send_message(&encoded).await {
+                            Ok(()) => connection.peer.update_last_seen(),
+                            Err(e) => {
+                                debug!("Heartbeat send to {} failed: {}", peer_id, e);
+                            }
+                        }
+
+                        if Utc::now() - connection.peer.last_seen > timeout {
+                            dead_peers.push((*peer_id, connection.peer.display_name.clone()));
+                        }
+                    }
+
+                    for (peer_id, _) in &dead_peers {
+                        if let Some(connection) = conns.get_mut(peer_id) {
+                            connection.peer.set_disconnected();
+                        }
+                    }
+                }
+
+                if !dead_peers.is_empty() {
+                    let peers = snapshot_peers(&connections, &quic_peers).await;
+                    peer_snapshot.store(Arc::new(peers));
+                }
+
+                for (peer_id, display_name) in dead_peers {
+                    warn!("Peer {} ({}) missed heartbeat window, marking disconnected", peer_id, display_name);
+                    deliver(&message_sender, Message::system_event_message(
+                        SystemEvent::PeerTimedOut { display_name },
+                    ));
+                }
+            }
+        });
+    }
+
+    /// Binds `port` and starts accepting connections, returning the port
+    /// actually bound - pass `0` for an OS-assigned ephemeral port, then use
+    /// the return value (not the `0` that was requested) for anything that
+    /// needs to tell the user or `ChatSession::current_port` which port this
+    /// is actually listening on.
+    ///
+    /// This is the primary listener - calling it again replaces whatever it
+    /// was previously listening on. To listen on additional interfaces at
+    /// the same time (e.g. a VPN interface or `127.0.0.1` for local IPC,
+    /// alongside the LAN-facing primary listener), use
+    /// `start_additional_listener` instead, configured via
+    /// `Config::additional_listen_addresses`.
+    ///
+    /// `bind_address` is the interface to bind, e.g. `0.0.0.0` for every
+    /// interface or `127.0.0.1` to keep this listener off the LAN - see
+    /// `Config::bind_address`.
+    pub async fn start_listening(&self, port: u16, bind_address: &str) -> Result<u16> {
+        // Stop any previously running accept loop before starting a new one.
+        if let Some(handle) = self.listener_handle.write().await.take() {
+            handle.abort();
+        }
+
+        let addr = format!("{}:{}", bind_address, port);
+        let listener = TcpListener::bind(&addr).await?;
+        let bound_addr = listener.local_addr()?;
+        info!("Rustalk listening on {}:{}", bind_address, bound_addr.port());
+
+        match crate::portmap::map_port(bound_addr.port()).await {
+            Ok(Some(mapping)) => {
+                info!(
+                    "Mapped external port {} via UPnP ({})",
+                    mapping.external_port,
+                    mapping.external_ip.as_deref().unwrap_or("external IP unknown")
+                );
+            }
+            Ok(None) => debug!("No UPnP gateway available, listening on LAN only"),
+            Err(e) => warn!("UPnP port mapping failed: {}", e),
+        }
+
+        let handle = self.spawn_accept_loop(listener);
+        *self.listener_handle.write().await = Some(handle);
+
+        Ok(bound_addr.port())
+    }
+
+    /// Binds an extra listener on `bind_addr` (e.g. `"127.0.0.1:5001"` or a
+    /// VPN interface's address) alongside the primary listener, so the same
+    /// `NetworkManager` can accept connections on several interfaces at
+    /// once. Unlike `start_listening`, this never replaces an existing
+    /// listener - each call adds one more, stopped together by
+    /// `stop_listening`/`shutdown`.
+    pub async fn start_additional_listener(&self, bind_addr: &str) -> Result<SocketAddr> {
+        let listener = TcpListener::bind(bind_addr).await?;
+        let bound_addr = listener.local_addr()?;
+        info!("Rustalk also listening on {}", bound_addr);
+
+        let handle = self.spawn_accept_loop(listener);
+        self.extra_listener_handles.write().await.push((bound_addr, handle));
+
+        Ok(bound_addr)
+    }
+
+    /// Spawns the accept loop shared by `start_listening` and
+    /// `start_additional_listener` - every listener enforces the same
+    /// per-IP rate limit, in-flight handshake cap, and `max_peers` limit,
+    /// regardless of which interface it's bound to.
+    fn spawn_accept_loop(&self, listener: TcpListener) -> tokio::task::JoinHandle<()> {
+        let connections = self.connections.clone();
+        let quic_peers = self.quic_peers.clone();
+        let peer_snapshot = self.peer_snapshot.clone();
+        let identity = self.identity.clone();
+        let message_sender = self.message_sender.clone();
+        let rate_limiter = self.rate_limiter.clone();
+        let handshake_semaphore = self.handshake_semaphore.clone();
+        let dnd = self.dnd.clone();
+        let stats = self.stats.clone();
+        let dedup = self.dedup.clone();
+        let outgoing_transfers = self.outgoing_transfers.clone();
+        let pending_offers = self.pending_offers.clone();
+        let incoming_transfers = self.incoming_transfers.clone();
+        let pending_connections = self.pending_connections.clone();
+
+        tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, addr)) => {
+                        crate::addr::apply_socket_tuning(&stream);
+
+                        if !rate_limiter.check(addr.ip()).await {
+                            warn!("Rate-limiting connection from {} (too many recent attempts)", addr);
+                            continue;
+                        }
+
+                        let Ok(permit) = handshake_semaphore.clone().try_acquire_owned() else {
+                            warn!(
+                                "Rejecting connection from {}: too many handshakes already in flight",
+                                addr
+                            );
+                            continue;
+                        };
+
+                        let max_peers = crate::config::load_config_cached()
+                            .map(|config| config.max_peers)
+                            .unwrap_or(usize::MAX);
+                        let current_peers = peer_count(&connections, &quic_peers).await;
+                        if current_peers >= max_peers {
+                            warn!(
+                                "Rejecting connection from {}: at max_peers limit ({}/{})",
+                                addr, current_peers, max_peers
+                            );
+                            continue;
+                        }
+
+                        info!("New connection from {}", addr);
+
+                        let connections = connections.clone();
+                        let quic_peers = quic_peers.clone();
+                        let peer_snapshot = peer_snapshot.clone();
+                        let identity = identity.clone();
+                        let message_sender = message_sender.clone();
+                        let dnd = dnd.clone();
+                        let stats = stats.clone();
+                        let dedup = dedup.clone();
+                        let outgoing_transfers = outgoing_transfers.clone();
+                        let pending_offers = pending_offers.clone();
+                        let incoming_transfers = incoming_transfers.clone();
+                        let pending_connections = pending_connections.clone();
+
+                        tokio::spawn(async move {
+                            let _permit = permit;
+                            match tokio::time::timeout(
+                                HANDSHAKE_TIMEOUT,
+                                Self::handle_incoming_connection(
+                                    stream,
+                                    addr,
+                                    connections,
+                                    quic_peers,
+                                    peer_snapshot,
+                                    identity,
+                                    message_sender,
+                                    dnd,
+                                    stats,
+                                    dedup,
+                                    outgoing_transfers,
+                                    pending_offers,
+                                    incoming_transfers,
+                                    pending_connections,
+                                ),
+                            )
+                            .await
+                            {
+                                Ok(Ok(())) => {}
+                                Ok(Err(e)) => {
+                                    error!("Error handling connection from {}: {}", addr, e);
+                                }
+                                Err(_) => {
+                                    warn!("Handshake with {} timed out", addr);
+                                }
+                            }
+                        });
+                    }
+                    Err(e) => {
+                        error!("Failed to accept connection: {}", e);
+                    }
+                }
+            }
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn handle_incoming_connection(
+        mut stream: TcpStream,
+        addr: SocketAddr,
+        connections: Arc<RwLock<HashMap<Uuid, PeerConnection>>>,
+        quic_peers: Arc<RwLock<HashMap<Uuid, Peer>>>,
+        peer_snapshot: Arc<ArcSwap<Vec<Peer>>>,
+        identity: Identity,
+        message_sender: mpsc::Sender<Message>,
+        dnd: Arc<AtomicBool>,
+        stats: Arc<RwLock<crate::stats::NetworkStats>>,
+        dedup: Arc<RwLock<crate::dedup::Dedup>>,
+        outgoing_transfers: Arc<RwLock<HashMap<Uuid, crate::file_transfer::OutgoingTransfer>>>,
+        pending_offers: Arc<RwLock<HashMap<Uuid, (Uuid, crate::file_transfer::FileOffer)>>>,
+        incoming_transfers: Arc<RwLock<HashMap<Uuid, crate::file_transfer::IncomingTransfer>>>,
+        pending_connections: Arc<RwLock<HashMap<Uuid, PeerConnection>>>,
+    ) -> Result<()> {
+        // Perform handshake
+        let handshake_msg = Message::handshake_message(
+            identity.user_id,
+            identity.keypair.public_key.clone(),
+            identity.get_display_name(),
+            identity.verifying_key_base64().unwrap_or_default(),
+        );
+
+        let outcome = crate::noise::handshake(&mut stream, &handshake_msg, &identity).await?;
+        if !outcome.authenticated {
+            debug!("Handshake with {} completed without static-key authentication", addr);
+        }
+        let peer_handshake = outcome.peer_message;
+
+        if !matches!(peer_handshake.message_type, MessageType::Handshake) {
+            return Err(anyhow!("Expected handshake message"));
+        }
+
+        // Create peer
+        // Save values before moving
+        let sender_name = peer_handshake.sender_name.clone();
+        let sender_id = peer_handshake.sender_id;
+
+        // Establish shared secret first
+        let our_private = identity.get_private_key_bytes()?;
+        let their_public_bytes = base64::Engine::decode(
+            &base64::engine::general_purpose::STANDARD,
+            &peer_handshake.content,
+        )?;
+
+        let peer = Peer::new(
+            sender_id,
+            "unknown@peer.local".to_string(), // We'll need to exchange this info
+            peer_handshake.sender_name,
+            addr,
+            peer_handshake.content, // This contains the public key
+        );
+
+        let mut connection = PeerConnection::new(peer, stream);
+        connection.protocol_version = crate::protocol::negotiate(&peer_handshake.protocol_versions);
+        connection.peer.capabilities = crate::peer::Capabilities::from_bits(peer_handshake.capabilities);
+        connection.peer.signing_key = peer_handshake.signing_key.clone();
+        connection.compression_enabled = connection.peer.capabilities.has(crate::peer::Capabilities::COMPRESSION)
+            && crate::peer::Capabilities::supported().has(crate::peer::Capabilities::COMPRESSION);
+
+        if their_public_bytes.len() != 32 {
+            return Err(anyhow!("Invalid public key length"));
+        }
+
+        let mut their_public = [0u8; 32];
+        their_public.copy_from_slice(&their_public_bytes);
+
+        connection.establish_shared_secret(&our_private, &their_public);
+        connection.peer.set_authenticated();
+        if let Ok(config) = crate::config::load_config_cached() {
+            connection.set_rate_limits(config.upload_limit_bytes_per_sec, config.download_limit_bytes_per_sec);
+        }
+
+        if crate::config::load_config_cached().map(|config| config.auto_accept_connections).unwrap_or(false) {
+            Self::activate_connection(
+                connection,
+                connections,
+                quic_peers,
+                peer_snapshot,
+                message_sender,
+                dnd,
+                stats,
+                dedup,
+                identity.user_id,
+                identity.get_display_name(),
+                outgoing_transfers,
+                pending_offers,
+                incoming_transfers,
+            )
+            .await;
+        } else {
+            let peer_id = connection.peer.id;
+            info!("[conn {}] Holding connection from {} ({}) pending approval", connection.connection_id, peer_id, sender_name);
+
+            let mut pending_message = Message::system_event_message(SystemEvent::ConnectionPending {
+                peer_id,
+                display_name: sender_name,
+            });
+            pending_message.connection_id = Some(connection.connection_id);
+            deliver(&message_sender, pending_message);
+
+            pending_connections.write().await.insert(peer_id, connection);
+        }
+
+        Ok(())
+    }
+
+    /// Moves a handshaken `connection` into `connections` and starts reading
+    /// from it - the shared tail of both the auto-accept path in
+    /// `handle_incoming_connection` and `accept_pending`, which take the
+    /// same connection through this step only after deciding (immediately,
+    /// or via `/accept`) that it should be trusted.
+    #[allow(clippy::too_many_arguments)]
+    async fn activate_connection(
+        connection: PeerConnection,
+        connections: Arc<RwLock<HashMap<Uuid, PeerConnection>>>,
+        quic_peers: Arc<RwLock<HashMap<Uuid, Peer>>>,
+        peer_snapshot: Arc<ArcSwap<Vec<Peer>>>,
+        message_sender: mpsc::Sender<Message>,
+        dnd: Arc<AtomicBool>,
+        stats: Arc<RwLock<crate::stats::NetworkStats>>,
+        dedup: Arc<RwLock<crate::dedup::Dedup>>,
+        own_user_id: Uuid,
+        own_display_name: String,
+        outgoing_transfers: Arc<RwLock<HashMap<Uuid, crate::file_transfer::OutgoingTransfer>>>,
+        pending_offers: Arc<RwLock<HashMap<Uuid, (Uuid, crate::file_transfer::FileOffer)>>>,
+        incoming_transfers: Arc<RwLock<HashMap<Uuid, crate::file_transfer::IncomingTransfer>>>,
+    ) -> Peer {
+        let peer_id = connection.peer.id;
+        let connection_id = connection.connection_id;
+        let sender_name = connection.peer.display_name.clone();
+        let addr = connection.peer.address;
+
+        if stats.read().await.has_seen(&peer_id.to_string()) {
+            stats.write().await.record_reconnect(&peer_id.to_string());
+        }
+
+        // A peer normally keeps the same `peer_id` across reconnects (it's
+        // their persistent `Identity::user_id`, not derived from the
+        // address), so a laptop switching Wi-Fi and reconnecting from a new
+        // `addr` lands on the same map key below and naturally picks up the
+        // same `ChatSession::active_peers`/`message_history` association -
+        // no separate migration step needed for those. What does need
+        // handling is the stale entry this replaces: its `spawn_reader` task
+        // is still running and would otherwise race the new connection's own
+        // reader over the same bytes (see `spawn_reader`'s supersession check).
+        //
+        // Recognizing the peer by public key (rather than trusting `peer_id`
+        // alone) guards the same case if a peer ever did show up under a
+        // different id with a key we've already seen - without this, that'd
+        // create a second `Peer` record for what's really the same contact.
+        let stale_entry = connections
+            .read()
+            .await
+            .iter()
+            .find(|(id, c)| **id != peer_id && c.peer.public_key == connection.peer.public_key)
+            .map(|(id, _)| *id);
+        if let Some(stale_id) = stale_entry {
+            info!(
+                "Peer {} reconnected as {} (same public key) from {} - migrating from stale entry",
+                stale_id, peer_id, addr
+            );
+            connections.write().await.remove(&stale_id);
+            dedup.write().await.migrate(stale_id, peer_id);
+        } else if connections.read().await.contains_key(&peer_id) {
+            info!("Peer {} reconnected from new address {}", peer_id, addr);
+        }
+
+        let peer = connection.peer.clone();
+
+        // Store connection
+        {
+            let mut conns = connections.write().await;
+            conns.insert(peer_id, connection);
+        }
+        peer_snapshot.store(Arc::new(snapshot_peers(&connections, &quic_peers).await));
+
+        // Send connection established message
+        let mut connected_message = Message::system_event_message(SystemEvent::PeerConnected {
+            display_name: sender_name.clone(),
+        });
+        connected_message.connection_id = Some(connection_id);
+        deliver(&message_sender, connected_message);
+
+        info!(
+            "[conn {}] Successfully connected to peer {} ({})",
+            connection_id, peer_id, sender_name
+        );
+
+        Self::spawn_reader(
+            peer_id,
+            connection_id,
+            connections,
+            quic_peers,
+            peer_snapshot,
+            message_sender,
+            dnd,
+            stats,
+            dedup,
+            own_user_id,
+            own_display_name,
+            outgoing_transfers,
+            pending_offers,
+            incoming_transfers,
+        );
+
+        peer
+    }
+
+    /// Approves a connection held by `handle_incoming_connection` because
+    /// `Config::auto_accept_connections` was `false` - see `/accept` and
+    /// `events::SessionEvent`'s sibling `SystemEvent::ConnectionPending`.
+    /// Returns the now-connected `Peer` so the caller can add it to the
+    /// current `ChatSession`.
+    pub async fn accept_pending(&self, peer_id: Uuid) -> Result<Peer> {
+        let connection = self
+            .pending_connections
+            .write()
+            .await
+            .remove(&peer_id)
+            .ok_or_else(|| anyhow!("No pending connection from {}", peer_id))?;
+
+        Ok(Self::activate_connection(
+            connection,
+            self.connections.clone(),
+            self.quic_peers.clone(),
+            self.peer_snapshot.clone(),
+            self.message_sender.clone(),
+            self.dnd.clone(),
+            self.stats.clone(),
+            self.dedup.clone(),
+            self.identity.user_id,
+            self.identity.get_display_name(),
+            self.outgoing_transfers.clone(),
+            self.pending_offers.clone(),
+            self.incoming_transfers.clone(),
+        )
+        .await)
+    }
+
+    /// Declines a connection held pending approval, closing it without ever
+    /// adding it to `connections` - see `accept_pending`.
+    pub async fn reject_pending(&self, peer_id: Uuid) -> Result<()> {
+        let mut connection = self
+            .pending_connections
+            .write()
+            .await
+            .remove(&peer_id)
+            .ok_or_else(|| anyhow!("No pending connection from {}", peer_id))?;
+
+        self.notify_disconnect(&mut connection, "connection request declined").await;
+        Ok(())
+    }
+
+    /// Lists inbound connections awaiting `/accept`/`/reject` - see `accept_pending`.
+    pub async fn list_pending(&self) -> Vec<Peer> {
+        self.pending_connections.read().await.values().map(|c| c.peer.clone()).collect()
+    }
+
+    /// Reads messages from a connected peer until it disconnects or a read
+    /// error occurs. A graceful `MessageType::Disconnect` marks the peer
+    /// offline immediately and renders a "peer left" system message instead
+    /// of waiting for the next heartbeat timeout. Used for both TCP and
+    /// WebSocket peers - they share the same `connections` map and `PeerConnection` type.
+    #[allow(clippy::too_many_arguments)]
+    fn spawn_reader(
+        peer_id: Uuid,
+        connection_id: Uuid,
+        connections: Arc<RwLock<HashMap<Uuid, PeerConnection>>>,
+        quic_peers: Arc<RwLock<HashMap<Uuid, Peer>>>,
+        peer_snapshot: Arc<ArcSwap<Vec<Peer>>>,
+        message_sender: mpsc::Sender<Message>,
+        dnd: Arc<AtomicBool>,
+        stats: Arc<RwLock<crate::stats::NetworkStats>>,
+        dedup: Arc<RwLock<crate::dedup::Dedup>>,
+        own_user_id: Uuid,
+        own_display_name: String,
+        outgoing_transfers: Arc<RwLock<HashMap<Uuid, crate::file_transfer::OutgoingTransfer>>>,
+        pending_offers: Arc<RwLock<HashMap<Uuid, (Uuid, crate::file_transfer::FileOffer)>>>,
+        incoming_transfers: Arc<RwLock<HashMap<Uuid, crate::file_transfer::IncomingTransfer>>>,
+    ) {
+        tokio::spawn(async move {
+            loop {
+                let (received, protocol_version) = {
+                    let mut conns = connections.write().await;
+                    match conns.get_mut(&peer_id) {
+                        // The peer reconnected from a new address (see
+                        // `activate_connection`'s migration handling)
+                        // and this task's connection was replaced in the map
+                        // before this task noticed its old stream closed -
+                        // stop here instead of racing the new connection's
+                        // own reader task for the same bytes.
+                        Some(connection) if connection.connection_id != connection_id => {
+                            debug!(
+                                "[conn {}] Superseded by a migrated connection for peer {}, stopping",
+                                connection_id, peer_id
+                            );
+                            break;
+                        }
+                        Some(connection) => (connection.receive_message().await, connection.protocol_version),
+                        None => break,
+                    }
+                };
+
+                let raw = match received {
+                    Ok(ReceivedFrame::Chat(raw)) => raw,
+                    Ok(ReceivedFrame::FileChunk(frame, data)) => {
+                        Self::handle_incoming_file_chunk(
+                            &incoming_transfers,
+                            &connections,
+                            connection_id,
+                            own_user_id,
+                            &own_display_name,
+                            &message_sender,
+                            frame,
+                            data,
+                        )
+                        .await;
+                        continue;
+                    }
+                    Err(e) => {
+                        debug!("[conn {}] Connection to peer {} closed: {}", connection_id, peer_id, e);
+                        break;
+                    }
+                };
+
+                let mut message: Message = match crate::protocol::decode_message(&raw, protocol_version) {
+                    Ok(message) => message,
+                    Err(e) => {
+                        warn!(
+                            "[conn {}] Received malformed message from peer {}: {}",
+                            connection_id, peer_id, e
+                        );
+                        continue;
+                    }
+                };
+                message.connection_id = Some(connection_id);
+
+                // `ChatSession::apply_edit`/`apply_retraction` authorize by
+                // comparing `sender_id` against the message being edited or
+                // retracted, so it can't be left at whatever this payload
+                // claims - that's fully attacker-controlled. Pin it to
+                // `peer_id`, the identity this connection was actually
+                // authenticated as, so a connected peer can only edit/retract
+                // messages attributed to themselves, not forge another
+                // contact's id to reach into their messages.
+                if matches!(message.message_type, MessageType::Edit | MessageType::Retract) {
+                    message.sender_id = peer_id;
+                }
+
+                stats
+                    .write()
+                    .await
+                    .record_received(&peer_id.to_string(), raw.len() as u64);
+
+                if dedup.write().await.is_duplicate(peer_id, message.id) {
+                    debug!(
+                        "[conn {}] Dropping duplicate message {} from peer {}",
+                        connection_id, message.id, peer_id
+                    );
+                    continue;
+                }
+
+                if message.signature.is_some() {
+                    let signing_key = connections
+                        .read()
+                        .await
+                        .get(&peer_id)
+                        .map(|connection| connection.peer.signing_key.clone())
+                        .unwrap_or_default();
+                    if !signing_key.is_empty() && !message.verify_signature(&signing_key) {
+                        warn!(
+                            "[conn {}] Dropping message {} from peer {} with a signature that doesn't verify",
+                            connection_id, message.id, peer_id
+                        );
+                        continue;
+                    }
+                }
+
+                if matches!(message.message_type, MessageType::Disconnect) {
+                    let display_name = {
+                        let mut conns = connections.write().await;
+                        match conns.get_mut(&peer_id) {
+                            Some(connection) => {
+                                connection.peer.set_disconnected();
+                                connection.peer.display_name.clone()
+                            }
+                            None => message.sender_name.clone(),
+                        }
+                    };
+
+                    peer_snapshot.store(Arc::new(snapshot_peers(&connections, &quic_peers).await));
+
+                    let mut left_message = Message::system_event_message(SystemEvent::PeerLeft {
+                        display_name,
+                        reason: message.content.clone(),
+                    });
+                    left_message.connection_id = Some(connection_id);
+                    deliver(&message_sender, left_message);
+                    break;
+                }
+
+                if matches!(message.message_type, MessageType::FileOffer) {
+                    match serde_json::from_str::<crate::file_transfer::FileOffer>(&message.content) {
+                        Ok(offer) => {
+                            info!(
+                                "[conn {}] Peer {} offered file '{}' ({} bytes, transfer {}) - use /file accept {} to accept",
+                                connection_id, peer_id, offer.file_name, offer.file_size, offer.transfer_id, offer.transfer_id
+                            );
+                            pending_offers.write().await.insert(offer.transfer_id, (peer_id, offer));
+                        }
+                        Err(e) => warn!("[conn {}] Malformed file offer from peer {}: {}", connection_id, peer_id, e),
+                    }
+                    deliver(&message_sender, message);
+                    continue;
+                }
+
+                if matches!(message.message_type, MessageType::FileAccept) {
+                    if let Ok(transfer_id) = Uuid::parse_str(message.content.trim()) {
+                        let outgoing = outgoing_transfers
+                            .read()
+                            .await
+                            .get(&transfer_id)
+                            .map(|t| (t.path.clone(), t.offer.clone()));
+                        if let Some((path, offer)) = outgoing {
+                            info!(
+                                "[conn {}] Peer {} accepted transfer {} ({}), starting upload",
+                                connection_id, peer_id, transfer_id, offer.file_name
+                            );
+                            Self::spawn_file_sender(connections.clone(), peer_id, transfer_id, path);
+                        }
+                    }
+                    deliver(&message_sender, message);
+                    continue;
+                }
+
+                if matches!(message.message_type, MessageType::FileReject) {
+                    if let Ok(transfer_id) = Uuid::parse_str(message.content.trim())
+                        && let Some(outgoing) = outgoing_transfers.write().await.remove(&transfer_id)
+                    {
+                        info!(
+                            "[conn {}] Peer {} rejected transfer {} ({})",
+                            connection_id, peer_id, transfer_id, outgoing.offer.file_name
+                        );
+                    }
+                    deliver(&message_sender, message);
+                    continue;
+                }
+
+                if matches!(message.message_type, MessageType::FileComplete) {
+                    if let Some((id_part, ok_part)) = message.content.split_once(' ')
+                        && let Ok(transfer_id) = Uuid::parse_str(id_part)
+                        && let Some(outgoing) = outgoing_transfers.write().await.remove(&transfer_id)
+                    {
+                        if ok_part.parse().unwrap_or(false) {
+                            info!(
+                                "[conn {}] Transfer {} ({}) to peer {} completed and verified",
+                                connection_id, transfer_id, outgoing.offer.file_name, peer_id
+                            );
+                        } else {
+                            warn!(
+                                "[conn {}] Transfer {} ({}) to peer {} failed checksum verification",
+                                connection_id, transfer_id, outgoing.offer.file_name, peer_id
+                            );
+                        }
+                    }
+                    deliver(&message_sender, message);
+                    continue;
+                }
+
+                if matches!(message.message_type, MessageType::Image) {
+                    match serde_json::from_str::<crate::message::ImagePayload>(&message.content) {
+                        Ok(payload) => {
+                            let valid = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, &payload.data)
+                                .is_ok_and(|data| payload.attachment.verify(&data));
+                            if !valid {
+                                warn!(
+                                    "[conn {}] Discarding image from peer {} that failed attachment verification",
+                                    connection_id, peer_id
+                                );
+                                continue;
+                            }
+                        }
+                        Err(e) => {
+                            warn!("[conn {}] Malformed image from peer {}: {}", connection_id, peer_id, e);
+                            continue;
+                        }
+                    }
+                    deliver(&message_sender, message);
+                    continue;
+                }
+
+                if matches!(message.message_type, MessageType::Text | MessageType::Broadcast)
+                    && let Ok(config) = crate::config::load_config_cached()
+                {
+                    let is_mention = crate::notify::is_mention(&message.content, &own_display_name);
+                    crate::notify::notify(&config, &peer_id.to_string(), dnd.load(Ordering::Relaxed), is_mention);
+                }
+
+                if matches!(message.message_type, MessageType::Text) {
+                    let ack = Message::delivery_ack_message(own_user_id, peer_id, own_display_name.clone(), message.id);
+                    if let Some(connection) = connections.write().await.get_mut(&peer_id)
+                        && let Ok(encoded) =
+                            crate::protocol::encode_message(&ack, connection.protocol_version, connection.compression_enabled)
+                    {
+                        let _ = connection.send_message(&encoded).await;
+                    }
+                }
+
+                deliver(&message_sender, message);
+            }
+        });
+    }
+
+    /// Handles one decrypted `Channel::FileTransfer` chunk for `spawn_reader`:
+    /// writes it to the matching `IncomingTransfer`, and once every chunk has
+    /// arrived, verifies the checksum and reports the outcome back to the
+    /// sender with a `file_complete_message`.
+    #[allow(clippy::too_many_arguments)]
+    async fn handle_incoming_file_chunk(
+        incoming_transfers: &Arc<RwLock<HashMap<Uuid, crate::file_transfer::IncomingTransfer>>>,
+        connections: &Arc<RwLock<HashMap<Uuid, PeerConnection>>>,
+        connection_id: Uuid,
+        own_user_id: Uuid,
+        own_display_name: &str,
+        message_sender: &mpsc::Sender<Message>,
+        frame: crate::file_transfer::FileChunkFrame,
+        data: Vec<u8>,
+    ) {
+        let complete = {
+            let mut transfers = incoming_transfers.write().await;
+            let Some(transfer) = transfers.get_mut(&frame.transfer_id) else {
+                debug!(
+                    "[conn {}] Chunk for unknown or already-finished transfer {}, dropping",
+                    connection_id, frame.transfer_id
+                );
+                return;
+            };
+            match transfer.write_chunk(frame.index, &data).await {
+                Ok(complete) => complete,
+                Err(e) => {
+                    warn!(
+                        "[conn {}] Failed writing chunk {} of transfer {}: {}",
+                        connection_id, frame.index, frame.transfer_id, e
+                    );
+                    return;
+                }
+            }
+        };
+
+        if !complete {
+            return;
+        }
+
+        let Some(transfer) = incoming_transfers.write().await.remove(&frame.transfer_id) else {
+            return;
+        };
+        let peer_id = transfer.peer_id;
+        let file_name = transfer.offer.file_name.clone();
+        let checksum_ok = match transfer.finish().await {
+            Ok(ok) => ok,
+            Err(e) => {
+                warn!(
+                    "[conn {}] Failed finalizing transfer {} ({}): {}",
+                    connection_id, frame.transfer_id, file_name, e
+                );
+                false
+            }
+        };
+
+        if checksum_ok {
+            info!(
+                "[conn {}] Transfer {} ({}) from peer {} complete and verified",
+                connection_id, frame.transfer_id, file_name, peer_id
+            );
+        } else {
+            warn!(
+                "[conn {}] Transfer {} ({}) from peer {} failed checksum verification",
+                connection_id, frame.transfer_id, file_name, peer_id
+            );
+        }
+
+        let complete_message = Message::file_complete_message(
+            own_user_id,
+            peer_id,
+            own_display_name.to_string(),
+            frame.transfer_id,
+            checksum_ok,
+        );
+        if let Some(connection) = connections.write().await.get_mut(&peer_id)
+            && let Ok(encoded) = crate::protocol::encode_message(
+                &complete_message,
+                connection.protocol_version,
+                connection.compression_enabled,
+            )
+        {
+            let _ = connection.send_message(&encoded).await;
+        }
+        deliver(message_sender, complete_message);
+    }
+
+    /// Spawned once a `FileAccept` for `transfer_id` arrives - streams `path`
+    /// to `peer_id` in `file_transfer::CHUNK_SIZE` pieces over the bounded
+    /// `Channel::FileTransfer` queue, retrying briefly on a full queue rather
+    /// than dropping a chunk.
+    fn spawn_file_sender(
+        connections: Arc<RwLock<HashMap<Uuid, PeerConnection>>>,
+        peer_id: Uuid,
+        transfer_id: Uuid,
+        path: PathBuf,
+    ) {
+        tokio::spawn(async move {
+            match Self::stream_file(&connections, peer_id, transfer_id, &path).await {
+                Ok(()) => info!(
+                    "Transfer {} fully sent to peer {}, awaiting checksum confirmation",
+                    transfer_id, peer_id
+                ),
+                Err(e) => warn!("Transfer {} to peer {} failed: {}", transfer_id, peer_id, e),
+            }
+        });
+    }
+
+    async fn stream_file(
+        connections: &Arc<RwLock<HashMap<Uuid, PeerConnection>>>,
+        peer_id: Uuid,
+        transfer_id: Uuid,
+        path: &Path,
+    ) -> Result<()> {
+        let mut file = tokio::fs::File::open(path).await?;
+        let total_chunks = crate::file_transfer::chunk_count(file.metadata().await?.len());
+        let mut buffer = vec![0u8; crate::file_transfer::CHUNK_SIZE as usize];
+        let mut index = 0u64;
+        let mut last_reported_pct = 0u64;
+
+        loop {
+            let n = file.read(&mut buffer).await?;
+            if n == 0 {
+                break;
+            }
+
+            let mut attempts = 0;
+            loop {
+                let sent = {
+                    let connections = connections.read().await;
+                    let connection = connections
+                        .get(&peer_id)
+                        .ok_or_else(|| anyhow!("Peer {} disconnected mid-transfer", peer_id))?;
+                    connection.send_encrypted_file_chunk(transfer_id, index, &buffer[..n])
+                };
+                match sent {
+                    Ok(()) => break,
+                    Err(e) if attempts < 50 => {
+                        attempts += 1;
+                        debug!("Chunk {} of transfer {} queue full, retrying: {}", index, transfer_id, e);
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+
+            index += 1;
+            let pct = index * 100 / total_chunks;
+            if pct >= last_reported_pct + 10 {
+                last_reported_pct = pct;
+                info!("Transfer {}: {}% sent ({}/{} chunks)", transfer_id, pct, index, total_chunks);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Discovers our public-facing `ip:port` via STUN, so it can be shared
+    /// with a peer (e.g. through a future rendezvous service) for hole punching.
+    pub async fn discover_public_address(&self) -> Result<SocketAddr> {
+        crate::nat::discover_public_address(crate::nat::DEFAULT_STUN_SERVER).await
+    }
+
+    /// Connects to a peer behind NAT by racing outbound TCP connection
+    /// attempts against its STUN-discovered public address, then continuing
+    /// with the normal handshake over whichever attempt succeeds.
+    pub async fn connect_with_hole_punch(&self, peer_public_addr: SocketAddr) -> Result<Peer> {
+        let stream = crate::nat::punch(peer_public_addr, 5).await?;
+        self.complete_outgoing_connection(stream, peer_public_addr)
+            .await
+    }
+
+    pub async fn connect_to_peer(&self, address: &str) -> Result<Peer> {
+        let max_peers = crate::config::load_config_cached()
+            .map(|config| config.max_peers)
+            .unwrap_or(usize::MAX);
+        let current_peers = peer_count(&self.connections, &self.quic_peers).await;
+        if current_peers >= max_peers {
+            return Err(anyhow!(
+                "Cannot connect to {}: at max_peers limit ({}/{})",
+                address,
+                current_peers,
+                max_peers
+            ));
+        }
+
+        let proxy_address = crate::config::load_config_cached()
+            .ok()
+            .and_then(|config| config.proxy_address);
+
+        let (stream, addr) = if let Some(proxy_address) = proxy_address {
+            // The proxy resolves `address` itself (see `socks5::connect_through_proxy`'s
+            // doc comment on why we don't resolve hostnames locally here), so there's
+            // no DNS fallback to apply - just parse what we need for the Peer record.
+            info!("Connecting to {} via SOCKS5 proxy {}", address, proxy_address);
+            let stream = crate::socks5::connect_through_proxy(&proxy_address, address).await?;
+            let addr: SocketAddr = address
+                .parse()
+                .map_err(|_| anyhow!("'{}' must be a literal ip:port when proxied", address))?;
+            (stream, addr)
+        } else {
+            crate::addr::connect_tcp(address, None).await?
+        };
+        info!("Connected to peer at {}", addr);
+        self.complete_outgoing_connection(stream, addr).await
+    }
+
+    /// Tries a direct connection first, falling back to `relay_address`
+    /// (a `rus relay` instance) when the peer can't be reached directly -
+    /// e.g. both sides are behind NATs that hole punching couldn't traverse.
+    pub async fn connect_to_peer_with_relay_fallback(
+        &self,
+        address: &str,
+        relay_address: Option<&str>,
+    ) -> Result<Peer> {
+        match self.connect_to_peer(address).await {
+            Ok(peer) => Ok(peer),
+            Err(direct_err) => {
+                let Some(relay_address) = relay_address else {
+                    return Err(direct_err);
+                };
+                warn!(
+                    "Direct connect to {} failed ({}), falling back to relay {}",
+                    address, direct_err, relay_address
+                );
+                self.connect_via_relay(relay_address).await
+            }
+        }
+    }
+
+    /// Registers with a relay server so peers addressed by our id can reach
+    /// us through it. The relay never holds a shared secret - frames are
+    /// still end-to-end encrypted the same way a direct `PeerConnection` is.
+    async fn connect_via_relay(&self, relay_address: &str) -> Result<Peer> {
+        let mut stream = TcpStream::connect(relay_address).await?;
+        let register = format!("REGISTER {}\n", self.identity.user_id);
+        stream.write_all(register.as_bytes()).await?;
+
+        let addr: SocketAddr = relay_address.parse()?;
+        info!("Connected via relay {}", relay_address);
+        self.complete_outgoing_connection(stream, addr).await
+    }
+
+    // Mirrors handle_incoming_connection's handshake, but for the dialing side.
+    async fn complete_outgoing_connection(
+        &self,
+        mut stream: TcpStream,
+        addr: SocketAddr,
+    ) -> Result<Peer> {
+        let handshake_msg = Message::handshake_message(
+            self.identity.user_id,
+            self.identity.keypair.public_key.clone(),
+            self.identity.get_display_name(),
+            self.identity.verifying_key_base64().unwrap_or_default(),
+        );
+
+        let outcome = crate::noise::handshake(&mut stream, &handshake_msg, &self.identity).await?;
+        if !outcome.authenticated {
+            debug!("Handshake with {} completed without static-key authentication", addr);
+        }
+        let peer_handshake = outcome.peer_message;
+
+        if !matches!(peer_handshake.message_type, MessageType::Handshake) {
+            return Err(anyhow!("Expected handshake message"));
+        }
+
+        let our_private = self.identity.get_private_key_bytes()?;
+        let their_public_bytes = base64::Engine::decode(
+            &base64::engine::general_purpose::STANDARD,
+            &peer_handshake.content,
+        )?;
+
+        if their_public_bytes.len() != 32 {
+            return Err(anyhow!("Invalid public key length"));
+        }
+        let mut their_public = [0u8; 32];
+        their_public.copy_from_slice(&their_public_bytes);
+
+        let peer = Peer::new(
+            peer_handshake.sender_id,
+            "unknown@peer.local".to_string(), // We'll need to exchange this info
+            peer_handshake.sender_name.clone(),
+            addr,
+            peer_handshake.content,
+        );
+
+        let mut connection = PeerConnection::new(peer.clone(), stream);
+        connection.protocol_version = crate::protocol::negotiate(&peer_handshake.protocol_versions);
+        connection.peer.capabilities = crate::peer::Capabilities::from_bits(peer_handshake.capabilities);
+        connection.peer.signing_key = peer_handshake.signing_key.clone();
+        connection.compression_enabled = connection.peer.capabilities.has(crate::peer::Capabilities::COMPRESSION)
+            && crate::peer::Capabilities::supported().has(crate::peer::Capabilities::COMPRESSION);
+        connection.establish_shared_secret(&our_private, &their_public);
+        connection.peer.set_authenticated();
+        if let Ok(config) = crate::config::load_config_cached() {
+            connection.set_rate_limits(config.upload_limit_bytes_per_sec, config.download_limit_bytes_per_sec);
+        }
+        let peer = connection.peer.clone();
+        let connection_id = connection.connection_id;
+
+        if self.stats.read().await.has_seen(&peer.id.to_string()) {
+            self.stats.write().await.record_reconnect(&peer.id.to_string());
+        }
+
+        {
+            let mut conns = self.connections.write().await;
+            conns.insert(connection.peer.id, connection);
+        }
+        self.publish_peer_snapshot().await;
+
+        info!(
+            "[conn {}] Completed outgoing handshake with peer {} ({})",
+            connection_id, peer.id, peer.display_name
+        );
+
+        Self::spawn_reader(
+            peer.id,
+            connection_id,
+            self.connections.clone(),
+            self.quic_peers.clone(),
+            self.peer_snapshot.clone(),
+            self.message_sender.clone(),
+            self.dnd.clone(),
+            self.stats.clone(),
+            self.dedup.clone(),
+            self.identity.user_id,
+            self.identity.get_display_name(),
+            self.outgoing_transfers.clone(),
+            self.pending_offers.clone(),
+            self.incoming_transfers.clone(),
+        );
+
+        Ok(peer)
+    }
+
+    /// Proof-of-concept off-grid connection over `transport::SerialTransport`
+    /// instead of TCP - a null-modem cable or a Bluetooth RFCOMM device
+    /// (`/dev/rfcomm0`), for chatting with a nearby machine with no network
+    /// link at all. Runs the same `noise::handshake` as a TCP peer since it
+    /// only needs `AsyncRead + AsyncWrite`, so everything past the handshake
+    /// (`protocol::encode_message`, `spawn_reader`, the connections map) is
+    /// unchanged from the TCP path.
+    ///
+    /// There's no `SocketAddr` for a serial link, so `Peer::address` is set
+    /// to an unroutable placeholder; nothing in this codebase dials a peer
+    /// back out by address for an already-established connection.
+    #[cfg(feature = "serial")]
+    pub async fn connect_via_serial(&self, path: &str, baud_rate: u32) -> Result<Peer> {
+        use tokio_serial::SerialPortBuilderExt;
+        let mut raw_port = tokio_serial::new(path, baud_rate).open_native_async()?;
+
+        let handshake_msg = Message::handshake_message(
+            self.identity.user_id,
+            self.identity.keypair.public_key.clone(),
+            self.identity.get_display_name(),
+            self.identity.verifying_key_base64().unwrap_or_default(),
+        );
+
+        let outcome = crate::noise::handshake(&mut raw_port, &handshake_msg, &self.identity).await?;
+        if !outcome.authenticated {
+            debug!("Handshake over {} completed without static-key authentication", path);
+        }
+        let peer_handshake = outcome.peer_message;
+
+        if !matches!(peer_handshake.message_type, MessageType::Handshake) {
+            return Err(anyhow!("Expected handshake message"));
+        }
+
+        let placeholder_addr: SocketAddr = "0.0.0.0:0".parse().unwrap();
+        let mut peer = Peer::new(
+            peer_handshake.sender_id,
+            "unknown@peer.local".to_string(),
+            peer_handshake.sender_name.clone(),
+            placeholder_addr,
+            peer_handshake.content,
+        );
+        peer.set_transport(TransportKind::Serial);
+
+        let mut connection =
+            PeerConnection::new(peer.clone(), crate::transport::SerialTransport::from_stream(raw_port));
+        connection.protocol_version = crate::protocol::negotiate(&peer_handshake.protocol_versions);
+        connection.peer.capabilities = crate::peer::Capabilities::from_bits(peer_handshake.capabilities);
+        connection.peer.signing_key = peer_handshake.signing_key.clone();
+        connection.peer.set_authenticated();
+        let peer = connection.peer.clone();
+        let connection_id = connection.connection_id;
+
+        {
+            let mut conns = self.connections.write().await;
+            conns.insert(connection.peer.id, connection);
+        }
+        self.publish_peer_snapshot().await;
+
+        info!(
+            "[conn {}] Completed serial handshake with peer {} ({}) on {}",
+            connection_id, peer.id, peer.display_name, path
+        );
+
+        Self::spawn_reader(
+            peer.id,
+            connection_id,
+            self.connections.clone(),
+            self.quic_peers.clone(),
+            self.peer_snapshot.clone(),
+            self.message_sender.clone(),
+            self.dnd.clone(),
+            self.stats.clone(),
+            self.dedup.clone(),
+            self.identity.user_id,
+            self.identity.get_display_name(),
+            self.outgoing_transfers.clone(),
+            self.pending_offers.clone(),
+            self.incoming_transfers.clone(),
+        );
+
+        Ok(peer)
+    }
+
+    pub async fn send_message(&self, peer_id: &str, content: &str) -> Result<String> {
+        let peer_uuid = Uuid::parse_str(peer_id)?;
+
+        {
+            let mut connections = self.connections.write().await;
+            if let Some(connection) = connections.get_mut(&peer_uuid) {
+                let mut message = Message::text_message(
+                    self.identity.user_id,
+                    peer_uuid,
+                    content.to_string(),
+                    self.identity.get_display_name(),
+                );
+                message.connection_id = Some(connection.connection_id);
+                message.sign(&self.identity);
+
+                let message_id = message.id.to_string();
+                let encoded = crate::protocol::encode_message(&message, connection.protocol_version, connection.compression_enabled)?;
+                connection.send_message(&encoded).await?;
+                self.stats
+                    .write()
+                    .await
+                    .record_sent(peer_id, encoded.len() as u64);
+
+                // Send to local message handler
+                deliver(&self.message_sender, message);
+
+                return Ok(message_id);
+            }
+        }
+
+        self.send_message_via_quic(peer_uuid, content).await
+    }
+
+    /// Sends `content` as a single `MessageType::Broadcast` to every
+    /// authenticated connection, returning each peer's outcome so a caller
+    /// (e.g. `SessionManager::broadcast_message`) can queue failures into the
+    /// outbox instead of losing them - see `/all`. A failure sending to one
+    /// peer doesn't stop delivery to the rest.
+    ///
+    /// The body is encrypted once with our `sender_key` (generated on first
+    /// use) rather than left as plaintext `content` for each connection's
+    /// own pairwise encryption to protect individually - `ensure_sender_key_distributed`
+    /// makes sure every current peer already has that key before the
+    /// ciphertext goes out, and `SessionManager::merge_message` reverses
+    /// this with `decrypt_broadcast` on the receiving end.
+    pub async fn broadcast(&self, content: &str) -> Vec<(String, Result<(), String>)> {
+        let peer_ids = self.connected_peer_ids().await;
+
+        if let Err(e) = self.ensure_sender_key_distributed(&peer_ids).await {
+            return peer_ids
+                .into_iter()
+                .map(|id| (id.to_string(), Err(format!("Failed to distribute sender key: {}", e))))
+                .collect();
+        }
+
+        let key = *self.sender_key.read().await;
+        let Some(key) = key else {
+            return peer_ids
+                .into_iter()
+                .map(|id| (id.to_string(), Err("No sender key established".to_string())))
+                .collect();
+        };
+
+        match CryptoEngine::encrypt_message(content, &key) {
+            Ok(ciphertext) => self.fan_out(peer_ids, &ciphertext, Message::broadcast_message).await,
+            Err(e) => peer_ids
+                .into_iter()
+                .map(|id| (id.to_string(), Err(format!("Failed to encrypt broadcast: {}", e))))
+                .collect(),
+        }
+    }
+
+    /// Announces a new session topic to every authenticated connection - see
+    /// `/topic`. Same delivery semantics as `broadcast`, but sent as plain
+    /// `content` like before - session metadata, not a group chat message,
+    /// so there's nothing here for the sender-key scheme to protect twice.
+    pub async fn broadcast_topic(&self, topic: &str) -> Vec<(String, Result<(), String>)> {
+        let peer_ids = self.connected_peer_ids().await;
+        self.fan_out(peer_ids, topic, Message::topic_message).await
+    }
+
+    async fn connected_peer_ids(&self) -> Vec<Uuid> {
+        self.connections
+            .read()
+            .await
+            .values()
+            .filter(|connection| connection.peer.is_connected())
+            .map(|connection| connection.peer.id)
+            .collect()
+    }
+
+    /// Makes sure every peer in `peer_ids` has our current `sender_key`,
+    /// generating one first if this is the first broadcast - see
+    /// `broadcast`. Only sends to peers `sender_key_recipients` doesn't
+    /// already cover, so a long-running conversation isn't re-distributing
+    /// the key on every single message, only when someone new shows up.
+    async fn ensure_sender_key_distributed(&self, peer_ids: &[Uuid]) -> Result<()> {
+        let key = {
+            let mut sender_key = self.sender_key.write().await;
+            match *sender_key {
+                Some(key) => key,
+                None => {
+                    let key = CryptoEngine::generate_symmetric_key();
+                    *sender_key = Some(key);
+                    key
+                }
+            }
+        };
+
+        let needs_key: Vec<Uuid> = {
+            let recipients = self.sender_key_recipients.read().await;
+            peer_ids.iter().filter(|id| !recipients.contains(id)).copied().collect()
+        };
+
+        if needs_key.is_empty() {
+            return Ok(());
+        }
+
+        let encoded = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, key);
+        let outcomes = self.fan_out(needs_key, &encoded, Message::sender_key_message).await;
+
+        let mut recipients = self.sender_key_recipients.write().await;
+        for (peer_id, outcome) in outcomes {
+            match (Uuid::parse_str(&peer_id), outcome) {
+                (Ok(peer_id), Ok(())) => {
+                    recipients.insert(peer_id);
+                }
+                (_, Err(e)) => warn!("Failed to distribute sender key to peer {}: {}", peer_id, e),
+                (Err(e), Ok(())) => warn!("Distributed sender key to unparseable peer id {}: {}", peer_id, e),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Stores a `MessageType::SenderKey` received from `sender_id` - see
+    /// `SessionManager::merge_message` and `decrypt_broadcast`.
+    pub async fn receive_sender_key(&self, sender_id: Uuid, key: [u8; 32]) {
+        self.peer_sender_keys.write().await.insert(sender_id, key);
+    }
+
+    /// Reverses `broadcast`'s sender-key encryption for a `MessageType::Broadcast`
+    /// received from `sender_id`. Fails if `sender_id`'s `MessageType::SenderKey`
+    /// hasn't arrived yet - e.g. it's still in flight behind this same message,
+    /// or got lost - there's nothing to decrypt with otherwise.
+    pub async fn decrypt_broadcast(&self, sender_id: Uuid, ciphertext: &str) -> Result<String> {
+        let key = self
+            .peer_sender_keys
+            .read()
+            .await
+            .get(&sender_id)
+            .copied()
+            .ok_or_else(|| anyhow!("No sender key received yet from {}", sender_id))?;
+        CryptoEngine::decrypt_message(ciphertext, &key)
+    }
+
+    /// Shared fan-out for `broadcast`/`broadcast_topic`/sender-key
+    /// distribution: builds one message per connection in `peer_ids` via
+    /// `build_message` (so each gets its own id and `connection_id`) and
+    /// sends it, collecting each peer's outcome rather than stopping at the
+    /// first failure.
+    async fn fan_out(
+        &self,
+        peer_ids: Vec<Uuid>,
+        content: &str,
+        build_message: impl Fn(Uuid, String, String) -> Message,
+    ) -> Vec<(String, Result<(), String>)> {
+        let mut outcomes = Vec::with_capacity(peer_ids.len());
+        let mut connections = self.connections.write().await;
+        for peer_id in peer_ids {
+            let Some(connection) = connections.get_mut(&peer_id) else {
+                continue;
+            };
+
+            let mut message = build_message(
+                self.identity.user_id,
+                self.identity.get_display_name(),
+                content.to_string(),
+            );
+            message.connection_id = Some(connection.connection_id);
+            message.sign(&self.identity);
+
+            let outcome = match crate::protocol::encode_message(
+                &message,
+                connection.protocol_version,
+                connection.compression_enabled,
+            ) {
+                Ok(encoded) => match connection.send_message(&encoded).await {
+                    Ok(()) => {
+                        self.stats
+                            .write()
+                            .await
+                            .record_sent(&peer_id.to_string(), encoded.len() as u64);
+                        deliver(&self.message_sender, message);
+                        Ok(())
+                    }
+                    Err(e) => Err(e.to_string()),
+                },
+                Err(e) => Err(e.to_string()),
+            };
+            outcomes.push((peer_id.to_string(), outcome));
+        }
+        outcomes
+    }
+
+    /// Queues a chunk of file data to `peer_id`, failing fast with a clear
+    /// error if the peer never advertised `Capabilities::FILE_TRANSFER` during
+    /// the handshake rather than queuing a frame the other side has no way to
+    /// handle - see `PeerConnection::send_file_chunk`.
+    pub async fn send_file_chunk(&self, peer_id: &str, data: Vec<u8>) -> Result<()> {
+        let peer_uuid = Uuid::parse_str(peer_id)?;
+
+        let connections = self.connections.read().await;
+        let connection = connections
+            .get(&peer_uuid)
+            .ok_or_else(|| anyhow!("Not connected to peer {}", peer_id))?;
+
+        if !connection.peer.capabilities.has(crate::peer::Capabilities::FILE_TRANSFER) {
+            return Err(anyhow!(
+                "Peer {} does not support file transfer",
+                peer_id
+            ));
+        }
+
+        connection.send_file_chunk(data)
+    }
+
+    /// Encodes and sends an already-built `Message` to `peer_id` over its
+    /// existing connection, recording stats and delivering a local copy the
+    /// same way `send_message` does - shared by `offer_file`/`accept_file`/`reject_file`
+    /// so those don't each repeat the encode/send/record/deliver sequence.
+    async fn send_control_message(&self, peer_id: Uuid, message: Message) -> Result<()> {
+        let mut connections = self.connections.write().await;
+        let connection = connections
+            .get_mut(&peer_id)
+            .ok_or_else(|| anyhow!("Not connected to peer {}", peer_id))?;
+
+        let mut message = message;
+        message.connection_id = Some(connection.connection_id);
+        message.sign(&self.identity);
+        let encoded = crate::protocol::encode_message(&message, connection.protocol_version, connection.compression_enabled)?;
+        connection.send_message(&encoded).await?;
+        self.stats
+            .write()
+            .await
+            .record_sent(&peer_id.to_string(), encoded.len() as u64);
+        deliver(&self.message_sender, message);
+
+        Ok(())
+    }
 
-impl PeerConnection {
-    pub fn new(peer: Peer, stream: TcpStream) -> Self {
-        PeerConnection {
-            peer,
-            stream: Arc::new(RwLock::new(stream)),
-            shared_secret: None,
+    /// Like `send_control_message`, but sends over `PeerConnection::send_priority_message`
+    /// instead of `send_message`, so `message` can't queue behind a large chat
+    /// or file-transfer payload already in flight on this connection - used by
+    /// `send_read_receipt` and `send_ephemeral`'s TCP fallback, not by
+    /// content-bearing sends like `send_markdown`/`send_image`.
+    async fn send_priority_control_message(&self, peer_id: Uuid, message: Message) -> Result<()> {
+        let mut connections = self.connections.write().await;
+        let connection = connections
+            .get_mut(&peer_id)
+            .ok_or_else(|| anyhow!("Not connected to peer {}", peer_id))?;
+
+        let mut message = message;
+        message.connection_id = Some(connection.connection_id);
+        message.sign(&self.identity);
+        let encoded = crate::protocol::encode_message(&message, connection.protocol_version, connection.compression_enabled)?;
+        connection.send_priority_message(&encoded).await?;
+        self.stats
+            .write()
+            .await
+            .record_sent(&peer_id.to_string(), encoded.len() as u64);
+        deliver(&self.message_sender, message);
+
+        Ok(())
+    }
+
+    /// Offers `path` to `peer_id`: hashes the file, sends a `FileOffer`
+    /// message, and records an `OutgoingTransfer` so the eventual
+    /// `FileAccept` (handled in `spawn_reader`) knows what to stream. Fails
+    /// fast if the peer never advertised `Capabilities::FILE_TRANSFER`, the
+    /// same check `send_file_chunk` makes.
+    pub async fn offer_file(&self, peer_id: &str, path: &Path) -> Result<Uuid> {
+        let peer_uuid = Uuid::parse_str(peer_id)?;
+
+        let supports_file_transfer = self
+            .connections
+            .read()
+            .await
+            .get(&peer_uuid)
+            .map(|connection| connection.peer.capabilities.has(crate::peer::Capabilities::FILE_TRANSFER))
+            .ok_or_else(|| anyhow!("Not connected to peer {}", peer_id))?;
+        if !supports_file_transfer {
+            return Err(anyhow!("Peer {} does not support file transfer", peer_id));
         }
+
+        let metadata = tokio::fs::metadata(path).await?;
+        let file_name = path
+            .file_name()
+            .ok_or_else(|| anyhow!("'{}' has no file name", path.display()))?
+            .to_string_lossy()
+            .to_string();
+        let sha256 = crate::file_transfer::hash_file(path).await?;
+
+        let offer = crate::file_transfer::FileOffer {
+            transfer_id: Uuid::new_v4(),
+            file_name,
+            file_size: metadata.len(),
+            sha256,
+        };
+        let transfer_id = offer.transfer_id;
+
+        let message = Message::file_offer_message(self.identity.user_id, peer_uuid, self.identity.get_display_name(), &offer);
+        self.send_control_message(peer_uuid, message).await?;
+
+        info!(
+            "Offered file '{}' ({} bytes) to peer {} as transfer {}",
+            offer.file_name, offer.file_size, peer_id, transfer_id
+        );
+        self.outgoing_transfers.write().await.insert(
+            transfer_id,
+            crate::file_transfer::OutgoingTransfer { peer_id: peer_uuid, path: path.to_path_buf(), offer },
+        );
+
+        Ok(transfer_id)
     }
 
-    pub async fn send_message(&mut self, message: &str) -> Result<()> {
-        if let Some(secret) = &self.shared_secret {
-            let encrypted = CryptoEngine::encrypt_message(message, secret)?;
-            let mut stream = self.stream.write().await;
+    /// Accepts a pending offer from `pending_offers`, opens `dest_path` for
+    /// writing, and replies with a `FileAccept` so the offering peer starts
+    /// streaming - see `spawn_reader`'s `MessageType::FileAccept` handling.
+    pub async fn accept_file(&self, transfer_id: Uuid, dest_path: &Path) -> Result<PathBuf> {
+        let (peer_id, offer) = self
+            .pending_offers
+            .write()
+            .await
+            .remove(&transfer_id)
+            .ok_or_else(|| anyhow!("No pending offer for transfer {}", transfer_id))?;
 
-            let data = format!("{}\n", encrypted);
-            stream.write_all(data.as_bytes()).await?;
-            stream.flush().await?;
+        let file = tokio::fs::File::create(dest_path).await?;
+        let total_chunks = crate::file_transfer::chunk_count(offer.file_size);
+        self.incoming_transfers.write().await.insert(
+            transfer_id,
+            crate::file_transfer::IncomingTransfer {
+                peer_id,
+                offer,
+                dest_path: dest_path.to_path_buf(),
+                file,
+                received_chunks: 0,
+                total_chunks,
+            },
+        );
 
-            debug!("Sent encrypted message to peer {}", self.peer.id);
-            Ok(())
-        } else {
-            Err(anyhow!("No shared secret established"))
+        let message = Message::file_accept_message(self.identity.user_id, peer_id, self.identity.get_display_name(), transfer_id);
+        self.send_control_message(peer_id, message).await?;
+
+        info!("Accepted transfer {} from peer {}, writing to {}", transfer_id, peer_id, dest_path.display());
+        Ok(dest_path.to_path_buf())
+    }
+
+    /// Announces that `message_ids` (from `peer_id`) have been read - see
+    /// `ChatSession::mark_conversation_read` and `/read`. Gated by
+    /// `Config::read_receipts_enabled` in `SessionManager::mark_conversation_read`,
+    /// not here, so this always sends what it's asked to.
+    /// Sends the ids in receipts of at most `READ_RECEIPT_BATCH` each, so every
+    /// receipt stays well under `chat_chunk::CHAT_CHUNK_SIZE` - see
+    /// `PeerConnection::send_priority_message`.
+    pub async fn send_read_receipt(&self, peer_id: &str, message_ids: Vec<Uuid>) -> Result<()> {
+        const READ_RECEIPT_BATCH: usize = 16;
+        let peer_uuid = Uuid::parse_str(peer_id)?;
+        let read_at = chrono::Utc::now();
+        for batch in message_ids.chunks(READ_RECEIPT_BATCH) {
+            let payload = crate::message::ReadReceiptPayload { message_ids: batch.to_vec(), read_at };
+            let message = Message::read_receipt_message(self.identity.user_id, peer_uuid, self.identity.get_display_name(), &payload);
+            self.send_priority_control_message(peer_uuid, message).await?;
         }
+        Ok(())
     }
 
-    pub async fn receive_message(&mut self) -> Result<String> {
-        if let Some(secret) = &self.shared_secret {
-            let mut stream = self.stream.write().await;
-            let mut buffer = vec![0; 4096];
+    /// Announces that `message_id` (addressed to `peer_id`) has been edited
+    /// to `new_content` - see `ChatSession::apply_edit` and `/edit`.
+    pub async fn send_edit(&self, peer_id: &str, message_id: Uuid, new_content: String) -> Result<()> {
+        let peer_uuid = Uuid::parse_str(peer_id)?;
+        let payload = crate::message::EditPayload { message_id, new_content };
+        let message = Message::edit_message(self.identity.user_id, peer_uuid, self.identity.get_display_name(), &payload);
+        self.send_control_message(peer_uuid, message).await
+    }
 
-            let n = stream.read(&mut buffer).await?;
-            if n == 0 {
-                return Err(anyhow!("Connection closed"));
-            }
+    /// Asks `peer_id` to tombstone `message_id` - see
+    /// `ChatSession::apply_retraction` and `/retract`.
+    pub async fn send_retraction(&self, peer_id: &str, message_id: Uuid) -> Result<()> {
+        let peer_uuid = Uuid::parse_str(peer_id)?;
+        let message = Message::retract_message(self.identity.user_id, peer_uuid, self.identity.get_display_name(), message_id);
+        self.send_control_message(peer_uuid, message).await
+    }
+
+    /// Adds or removes our `emoji` reaction on `message_id` (addressed to
+    /// `peer_id`) - see `ChatSession::apply_reaction`, `/react` and `/unreact`.
+    pub async fn send_reaction(&self, peer_id: &str, message_id: Uuid, emoji: String, add: bool) -> Result<()> {
+        let peer_uuid = Uuid::parse_str(peer_id)?;
+        let payload = crate::message::ReactionPayload { message_id, emoji, add };
+        let message = Message::reaction_message(self.identity.user_id, peer_uuid, self.identity.get_display_name(), &payload);
+        self.send_control_message(peer_uuid, message).await
+    }
 
-            let encrypted_data = String::from_utf8_lossy(&buffer[..n]);
-            let encrypted_data = encrypted_data.trim();
+    /// Sends markdown source to `peer_id` - see `Message::markdown_message` and `/md`.
+    pub async fn send_markdown(&self, peer_id: &str, text: String) -> Result<()> {
+        let peer_uuid = Uuid::parse_str(peer_id)?;
+        let message = Message::markdown_message(self.identity.user_id, peer_uuid, self.identity.get_display_name(), text);
+        self.send_control_message(peer_uuid, message).await
+    }
 
-            let decrypted = CryptoEngine::decrypt_message(encrypted_data, secret)?;
-            debug!("Received and decrypted message from peer {}", self.peer.id);
+    /// Sends an inline image to `peer_id` - see `Message::image_message` and `/image`.
+    pub async fn send_image(&self, peer_id: &str, filename: String, mime: String, data: Vec<u8>) -> Result<()> {
+        let peer_uuid = Uuid::parse_str(peer_id)?;
+        let attachment = crate::file_transfer::Attachment::new(filename, mime, &data);
+        let payload = crate::message::ImagePayload {
+            attachment,
+            data: base64::Engine::encode(&base64::engine::general_purpose::STANDARD, data),
+        };
+        let message = Message::image_message(self.identity.user_id, peer_uuid, self.identity.get_display_name(), &payload);
+        self.send_control_message(peer_uuid, message).await
+    }
 
-            Ok(decrypted)
-        } else {
-            Err(anyhow!("No shared secret established"))
+    /// Sends a code snippet to `peer_id` - see `Message::code_message` and `/code`.
+    pub async fn send_code(&self, peer_id: &str, lang: String, text: String) -> Result<()> {
+        let peer_uuid = Uuid::parse_str(peer_id)?;
+        let payload = crate::message::CodePayload { lang, text };
+        let message = Message::code_message(self.identity.user_id, peer_uuid, self.identity.get_display_name(), &payload);
+        self.send_control_message(peer_uuid, message).await
+    }
+
+    /// Sends an already-built message (typically one with `forwarded_from`
+    /// set) to `peer_id` as-is, keeping its original `message_type`/`content` -
+    /// see `Message::forwarded_from` and `SessionManager::forward_message`.
+    pub async fn send_forwarded_message(&self, peer_id: &str, message: Message) -> Result<()> {
+        let peer_uuid = Uuid::parse_str(peer_id)?;
+        self.send_control_message(peer_uuid, message).await
+    }
+
+    /// Declines a pending offer from `pending_offers` and notifies the sender.
+    pub async fn reject_file(&self, transfer_id: Uuid) -> Result<()> {
+        let (peer_id, offer) = self
+            .pending_offers
+            .write()
+            .await
+            .remove(&transfer_id)
+            .ok_or_else(|| anyhow!("No pending offer for transfer {}", transfer_id))?;
+
+        let message = Message::file_reject_message(self.identity.user_id, peer_id, self.identity.get_display_name(), transfer_id);
+        self.send_control_message(peer_id, message).await?;
+
+        info!("Rejected transfer {} ({}) from peer {}", transfer_id, offer.file_name, peer_id);
+        Ok(())
+    }
+
+    /// Starts the datagram transport on `port` (0 for an ephemeral port) and
+    /// spawns a task forwarding anything it receives into the same message
+    /// channel as TCP connections use. Binds the same interface as the
+    /// primary TCP listener - see `Config::bind_address`.
+    pub async fn start_quic_transport(&self, port: u16) -> Result<SocketAddr> {
+        let bind_address = crate::config::load_config_cached()
+            .map(|c| c.bind_address)
+            .unwrap_or_else(|_| "0.0.0.0".to_string());
+        let transport = Arc::new(QuicTransport::bind(format!("{}:{}", bind_address, port).parse()?).await?);
+        let local_addr = transport.local_addr()?;
+
+        let reader_transport = transport.clone();
+        let message_sender = self.message_sender.clone();
+        let ephemeral_acks = self.ephemeral_acks.clone();
+        let identity = self.identity.clone();
+        tokio::spawn(async move {
+            while let Some((from, data)) = reader_transport.recv().await {
+                match serde_json::from_slice::<Message>(&data) {
+                    Ok(message) if matches!(message.message_type, MessageType::Ack) => {
+                        if let Ok(acked_id) = Uuid::parse_str(&message.content)
+                            && let Some(tx) = ephemeral_acks.write().await.remove(&acked_id)
+                        {
+                            let _ = tx.send(());
+                        }
+                    }
+                    Ok(message) => {
+                        let ack = Message::ack_message(
+                            identity.user_id,
+                            identity.get_display_name(),
+                            message.id,
+                        );
+                        if let Ok(encoded) = serde_json::to_string(&ack)
+                            && let Err(e) = reader_transport.send_to(from, encoded.as_bytes()).await
+                        {
+                            debug!("Failed to ack datagram from {}: {}", from, e);
+                        }
+                        deliver(&message_sender, message);
+                    }
+                    Err(e) => {
+                        debug!("Dropped malformed QUIC datagram from {}: {}", from, e);
+                    }
+                }
+            }
+        });
+
+        *self.quic.write().await = Some(transport);
+        info!("QUIC (datagram prototype) listening on {}", local_addr);
+        Ok(local_addr)
+    }
+
+    /// Registers a peer reachable over the datagram transport instead of TCP.
+    /// There is no handshake or encryption on this path yet - see `quic::QuicTransport`.
+    pub async fn connect_via_quic(&self, address: &str) -> Result<Peer> {
+        if self.quic.read().await.is_none() {
+            self.start_quic_transport(0).await?;
         }
+
+        let addr: SocketAddr = address.parse()?;
+        let mut peer = Peer::new(
+            Uuid::new_v4(),
+            "unknown@peer.local".to_string(),
+            "Unknown".to_string(),
+            addr,
+            "".to_string(),
+        );
+        peer.set_transport(TransportKind::Quic);
+        peer.set_connected();
+
+        self.quic_peers.write().await.insert(peer.id, peer.clone());
+        self.publish_peer_snapshot().await;
+        info!("Registered QUIC peer at {}", address);
+        Ok(peer)
     }
 
-    pub fn establish_shared_secret(&mut self, our_private: &[u8; 32], their_public: &[u8; 32]) {
-        self.shared_secret = Some(CryptoEngine::generate_shared_secret(
-            our_private,
-            their_public,
-        ));
-        info!("Shared secret established with peer {}", self.peer.id);
+    async fn send_message_via_quic(&self, peer_uuid: Uuid, content: &str) -> Result<String> {
+        let peer = {
+            let quic_peers = self.quic_peers.read().await;
+            quic_peers
+                .get(&peer_uuid)
+                .cloned()
+                .ok_or_else(|| anyhow!("Peer not found or not connected"))?
+        };
+
+        let transport = self
+            .quic
+            .read()
+            .await
+            .clone()
+            .ok_or_else(|| anyhow!("QUIC transport not started"))?;
+
+        let message = Message::text_message(
+            self.identity.user_id,
+            peer_uuid,
+            content.to_string(),
+            self.identity.get_display_name(),
+        );
+
+        let message_id = message.id.to_string();
+        let message_json = serde_json::to_string(&message)?;
+        transport.send_to(peer.address, message_json.as_bytes()).await?;
+        self.stats
+            .write()
+            .await
+            .record_sent(&peer_uuid.to_string(), message_json.len() as u64);
+
+        deliver(&self.message_sender, message);
+        Ok(message_id)
     }
-}
 
-pub struct NetworkManager {
-    identity: Identity,
-    connections: Arc<RwLock<HashMap<Uuid, PeerConnection>>>,
-    message_sender: mpsc::UnboundedSender<Message>,
-    message_receiver: Arc<RwLock<mpsc::UnboundedReceiver<Message>>>,
-}
+    /// Sends a short, latency-sensitive message (e.g. `MessageType::Typing`)
+    /// over UDP with application-level acknowledgment and retransmit, for
+    /// `peer_id`s registered via `connect_via_quic`. Retries up to
+    /// `EPHEMERAL_MAX_RETRIES` times, then falls back to the reliable TCP
+    /// `send_priority_control_message`, which needs `peer_id` in
+    /// `connections` and otherwise returns a "Not connected" error.
+    ///
+    /// A TCP-connected peer has no known UDP endpoint in this codebase - a
+    /// peer is registered in either `connections` or `quic_peers`, never
+    /// both - so a `peer_id` not found in `quic_peers` skips straight to
+    /// `send_priority_control_message` instead of attempting a datagram at
+    /// all, which keeps `message_type` intact and still gives it priority
+    /// over any chat/file-transfer traffic already queued on that connection.
+    pub async fn send_ephemeral(
+        &self,
+        peer_id: &str,
+        message_type: MessageType,
+        content: &str,
+    ) -> Result<()> {
+        let peer_uuid = Uuid::parse_str(peer_id)?;
 
-impl NetworkManager {
-    pub async fn new(identity: Identity) -> Result<Self> {
-        let (message_sender, message_receiver) = mpsc::unbounded_channel();
+        let Some(peer) = self.quic_peers.read().await.get(&peer_uuid).cloned() else {
+            let message = Message::new(
+                self.identity.user_id,
+                Some(peer_uuid),
+                message_type,
+                content.to_string(),
+                self.identity.get_display_name(),
+            );
+            return self.send_priority_control_message(peer_uuid, message).await;
+        };
 
-        Ok(NetworkManager {
-            identity,
-            connections: Arc::new(RwLock::new(HashMap::new())),
-            message_sender,
-            message_receiver: Arc::new(RwLock::new(message_receiver)),
-        })
+        let Some(transport) = self.quic.read().await.clone() else {
+            let message = Message::new(
+                self.identity.user_id,
+                Some(peer_uuid),
+                message_type,
+                content.to_string(),
+                self.identity.get_display_name(),
+            );
+            return self.send_priority_control_message(peer_uuid, message).await;
+        };
+
+        let message = Message::new(
+            self.identity.user_id,
+            Some(peer_uuid),
+            message_type,
+            content.to_string(),
+            self.identity.get_display_name(),
+        );
+        let encoded = serde_json::to_string(&message)?;
+
+        for attempt in 0..EPHEMERAL_MAX_RETRIES {
+            let (tx, rx) = tokio::sync::oneshot::channel();
+            self.ephemeral_acks.write().await.insert(message.id, tx);
+
+            transport.send_to(peer.address, encoded.as_bytes()).await?;
+
+            if tokio::time::timeout(EPHEMERAL_ACK_TIMEOUT, rx).await.is_ok() {
+                self.stats
+                    .write()
+                    .await
+                    .record_sent(&peer_uuid.to_string(), encoded.len() as u64);
+                return Ok(());
+            }
+
+            self.ephemeral_acks.write().await.remove(&message.id);
+            debug!(
+                "No ack for ephemeral datagram to {} (attempt {}/{})",
+                peer_id,
+                attempt + 1,
+                EPHEMERAL_MAX_RETRIES
+            );
+        }
+
+        warn!(
+            "Ephemeral datagram to {} went unacknowledged after {} attempts, falling back to TCP",
+            peer_id, EPHEMERAL_MAX_RETRIES
+        );
+        self.send_priority_control_message(peer_uuid, message).await
     }
 
-    pub async fn start_listening(&self, port: u16) -> Result<()> {
+    /// Listens for WebSocket upgrades on `port`, for browser/napi clients
+    /// that can't open a raw TCP socket to us. Handshake is a plaintext frame
+    /// exchange (see `PeerConnection::send_handshake`) rather than
+    /// `noise::handshake`, since a `WebSocketStream` isn't `AsyncRead`/`AsyncWrite`;
+    /// everything after that - framing, reading, the `connections` map - is
+    /// shared with the TCP path via `Transport`.
+    pub async fn start_websocket_listener(&self, port: u16) -> Result<()> {
+        if let Some(handle) = self.ws_listener_handle.write().await.take() {
+            handle.abort();
+        }
+
         let addr = format!("0.0.0.0:{}", port);
         let listener = TcpListener::bind(&addr).await?;
-        info!("Rustalk listening on {}", addr);
+        info!("Rustalk listening for WebSocket connections on {}", addr);
 
         let connections = self.connections.clone();
+        let quic_peers = self.quic_peers.clone();
+        let peer_snapshot = self.peer_snapshot.clone();
         let identity = self.identity.clone();
         let message_sender = self.message_sender.clone();
+        let dnd = self.dnd.clone();
+        let stats = self.stats.clone();
+        let dedup = self.dedup.clone();
+        let outgoing_transfers = self.outgoing_transfers.clone();
+        let pending_offers = self.pending_offers.clone();
+        let incoming_transfers = self.incoming_transfers.clone();
 
-        tokio::spawn(async move {
+        let handle = tokio::spawn(async move {
             loop {
                 match listener.accept().await {
                     Ok((stream, addr)) => {
-                        info!("New connection from {}", addr);
+                        crate::addr::apply_socket_tuning(&stream);
+                        info!("New WebSocket connection from {}", addr);
 
                         let connections = connections.clone();
+                        let quic_peers = quic_peers.clone();
+                        let peer_snapshot = peer_snapshot.clone();
                         let identity = identity.clone();
                         let message_sender = message_sender.clone();
+                        let dnd = dnd.clone();
+                        let stats = stats.clone();
+                        let dedup = dedup.clone();
+                        let outgoing_transfers = outgoing_transfers.clone();
+                        let pending_offers = pending_offers.clone();
+                        let incoming_transfers = incoming_transfers.clone();
 
                         tokio::spawn(async move {
-                            if let Err(e) = Self::handle_incoming_connection(
+                            if let Err(e) = Self::handle_incoming_ws_connection(
                                 stream,
                                 addr,
                                 connections,
+                                quic_peers,
+                                peer_snapshot,
                                 identity,
                                 message_sender,
+                                dnd,
+                                stats,
+                                dedup,
+                                outgoing_transfers,
+                                pending_offers,
+                                incoming_transfers,
                             )
                             .await
                             {
-                                error!("Error handling connection from {}: {}", addr, e);
+                                error!("Error handling WebSocket connection from {}: {}", addr, e);
                             }
                         });
                     }
                     Err(e) => {
-                        error!("Failed to accept connection: {}", e);
+                        error!("Failed to accept WebSocket connection: {}", e);
                     }
                 }
             }
         });
 
+        *self.ws_listener_handle.write().await = Some(handle);
         Ok(())
     }
 
-    async fn handle_incoming_connection(
-        mut stream: TcpStream,
+    #[allow(clippy::too_many_arguments)]
+    async fn handle_incoming_ws_connection(
+        stream: TcpStream,
         addr: SocketAddr,
         connections: Arc<RwLock<HashMap<Uuid, PeerConnection>>>,
+        quic_peers: Arc<RwLock<HashMap<Uuid, Peer>>>,
+        peer_snapshot: Arc<ArcSwap<Vec<Peer>>>,
         identity: Identity,
-        message_sender: mpsc::UnboundedSender<Message>,
+        message_sender: mpsc::Sender<Message>,
+        dnd: Arc<AtomicBool>,
+        stats: Arc<RwLock<crate::stats::NetworkStats>>,
+        dedup: Arc<RwLock<crate::dedup::Dedup>>,
+        outgoing_transfers: Arc<RwLock<HashMap<Uuid, crate::file_transfer::OutgoingTransfer>>>,
+        pending_offers: Arc<RwLock<HashMap<Uuid, (Uuid, crate::file_transfer::FileOffer)>>>,
+        incoming_transfers: Arc<RwLock<HashMap<Uuid, crate::file_transfer::IncomingTransfer>>>,
     ) -> Result<()> {
-        // Perform handshake
+        let ws_stream = crate::websocket::accept(stream).await?;
+
         let handshake_msg = Message::handshake_message(
             identity.user_id,
             identity.keypair.public_key.clone(),
             identity.get_display_name(),
+            identity.verifying_key_base64().unwrap_or_default(),
+        );
+        let peer = Peer::new(
+            Uuid::new_v4(),
+            "unknown@peer.local".to_string(),
+            "Unknown".to_string(),
+            addr,
+            "".to_string(),
         );
+        let mut connection = PeerConnection::new(peer, ws_stream);
 
+        // Reuse the same encrypted framing as the TCP path once a shared
+        // secret exists, just over the WebSocket transport instead of raw
+        // TCP bytes. The handshake itself is sent before that, in plaintext.
         let handshake_data = serde_json::to_string(&handshake_msg)?;
-        stream
-            .write_all(format!("{}\n", handshake_data).as_bytes())
+        connection
+            .send_handshake(&handshake_data)
             .await?;
-        stream.flush().await?;
-
-        // Read peer's handshake
-        let mut buffer = vec![0; 4096];
-        let n = stream.read(&mut buffer).await?;
-
-        if n == 0 {
-            return Err(anyhow!("Connection closed during handshake"));
-        }
-
-        let peer_handshake: Message = serde_json::from_slice(&buffer[..n])?;
+        let peer_handshake_raw = connection.receive_handshake().await?;
+        let peer_handshake: Message = serde_json::from_str(&peer_handshake_raw)?;
 
         if !matches!(peer_handshake.message_type, MessageType::Handshake) {
             return Err(anyhow!("Expected handshake message"));
         }
 
-        // Create peer
-        // Save values before moving
         let sender_name = peer_handshake.sender_name.clone();
         let sender_id = peer_handshake.sender_id;
 
-        // Establish shared secret first
         let our_private = identity.get_private_key_bytes()?;
         let their_public_bytes = base64::Engine::decode(
             &base64::engine::general_purpose::STANDARD,
             &peer_handshake.content,
         )?;
-
-        let peer = Peer::new(
-            sender_id,
-            "unknown@peer.local".to_string(), // We'll need to exchange this info
-            peer_handshake.sender_name,
-            addr,
-            peer_handshake.content, // This contains the public key
-        );
-
-        let mut connection = PeerConnection::new(peer, stream);
-
         if their_public_bytes.len() != 32 {
             return Err(anyhow!("Invalid public key length"));
         }
-
         let mut their_public = [0u8; 32];
         their_public.copy_from_slice(&their_public_bytes);
 
+        connection.peer.id = sender_id;
+        connection.peer.display_name = peer_handshake.sender_name;
+        connection.peer.public_key = peer_handshake.content;
+        connection.protocol_version = crate::protocol::negotiate(&peer_handshake.protocol_versions);
+        connection.peer.capabilities = crate::peer::Capabilities::from_bits(peer_handshake.capabilities);
+        connection.peer.signing_key = peer_handshake.signing_key.clone();
+        connection.compression_enabled = connection.peer.capabilities.has(crate::peer::Capabilities::COMPRESSION)
+            && crate::peer::Capabilities::supported().has(crate::peer::Capabilities::COMPRESSION);
         connection.establish_shared_secret(&our_private, &their_public);
         connection.peer.set_authenticated();
+        connection.peer.set_transport(TransportKind::WebSocket);
+        if let Ok(config) = crate::config::load_config_cached() {
+            connection.set_rate_limits(config.upload_limit_bytes_per_sec, config.download_limit_bytes_per_sec);
+        }
 
         let peer_id = connection.peer.id;
+        let connection_id = connection.connection_id;
+
+        if stats.read().await.has_seen(&peer_id.to_string()) {
+            stats.write().await.record_reconnect(&peer_id.to_string());
+        }
 
-        // Store connection
         {
             let mut conns = connections.write().await;
             conns.insert(peer_id, connection);
         }
+        peer_snapshot.store(Arc::new(snapshot_peers(&connections, &quic_peers).await));
 
-        // Send connection established message
-        let _ = message_sender.send(Message::system_message(format!(
-            "Connected to {}",
-            sender_name
-        )));
-
+        let mut connected_message = Message::system_event_message(SystemEvent::PeerConnected {
+            display_name: sender_name.clone(),
+        });
+        connected_message.connection_id = Some(connection_id);
+        deliver(&message_sender, connected_message);
         info!(
-            "Successfully connected to peer {} ({})",
-            peer_id, sender_name
+            "[conn {}] Successfully connected to WebSocket peer {} ({})",
+            connection_id, peer_id, sender_name
+        );
+
+        let own_display_name = identity.get_display_name();
+        Self::spawn_reader(
+            peer_id,
+            connection_id,
+            connections,
+            quic_peers,
+            peer_snapshot,
+            message_sender,
+            dnd,
+            stats,
+            dedup,
+            identity.user_id,
+            own_display_name,
+            outgoing_transfers,
+            pending_offers,
+            incoming_transfers,
         );
 
         Ok(())
     }
 
-    pub async fn connect_to_peer(&self, address: &str) -> Result<Peer> {
-        let stream = TcpStream::connect(address).await?;
-        let addr: SocketAddr = address.parse()?;
-
-        info!("Connected to peer at {}", address);
+    /// Dials a peer's `ws://host:port` address.
+    pub async fn connect_via_websocket(&self, address: &str) -> Result<Peer> {
+        let ws_stream = crate::websocket::connect(address).await?;
+        let addr: SocketAddr = address.strip_prefix("ws://").unwrap_or(address).parse()?;
 
-        // This is similar to handle_incoming_connection but for outgoing connections
-        // For brevity, I'll implement a simplified version
+        let handshake_msg = Message::handshake_message(
+            self.identity.user_id,
+            self.identity.keypair.public_key.clone(),
+            self.identity.get_display_name(),
+            self.identity.verifying_key_base64().unwrap_or_default(),
+        );
         let peer = Peer::new(
-            Uuid::new_v4(), // Temporary ID until handshake
+            Uuid::new_v4(),
             "unknown@peer.local".to_string(),
             "Unknown".to_string(),
             addr,
             "".to_string(),
         );
+        let mut connection = PeerConnection::new(peer, ws_stream);
 
-        let connection = PeerConnection::new(peer.clone(), stream);
+        let handshake_data = serde_json::to_string(&handshake_msg)?;
+        connection.send_handshake(&handshake_data).await?;
+        let peer_handshake_raw = connection.receive_handshake().await?;
+        let peer_handshake: Message = serde_json::from_str(&peer_handshake_raw)?;
 
-        // Store connection (simplified - in real implementation, complete handshake first)
-        {
-            let mut conns = self.connections.write().await;
-            conns.insert(connection.peer.id, connection);
+        if !matches!(peer_handshake.message_type, MessageType::Handshake) {
+            return Err(anyhow!("Expected handshake message"));
         }
 
-        Ok(peer)
-    }
-
-    pub async fn send_message(&self, peer_id: &str, content: &str) -> Result<String> {
-        let peer_uuid = Uuid::parse_str(peer_id)?;
+        let our_private = self.identity.get_private_key_bytes()?;
+        let their_public_bytes = base64::Engine::decode(
+            &base64::engine::general_purpose::STANDARD,
+            &peer_handshake.content,
+        )?;
+        if their_public_bytes.len() != 32 {
+            return Err(anyhow!("Invalid public key length"));
+        }
+        let mut their_public = [0u8; 32];
+        their_public.copy_from_slice(&their_public_bytes);
 
-        let mut connections = self.connections.write().await;
-        if let Some(connection) = connections.get_mut(&peer_uuid) {
-            let message = Message::text_message(
-                self.identity.user_id,
-                peer_uuid,
-                content.to_string(),
-                self.identity.get_display_name(),
-            );
+        connection.peer.id = peer_handshake.sender_id;
+        connection.peer.display_name = peer_handshake.sender_name.clone();
+        connection.peer.public_key = peer_handshake.content;
+        connection.protocol_version = crate::protocol::negotiate(&peer_handshake.protocol_versions);
+        connection.peer.capabilities = crate::peer::Capabilities::from_bits(peer_handshake.capabilities);
+        connection.peer.signing_key = peer_handshake.signing_key.clone();
+        connection.compression_enabled = connection.peer.capabilities.has(crate::peer::Capabilities::COMPRESSION)
+            && crate::peer::Capabilities::supported().has(crate::peer::Capabilities::COMPRESSION);
+        connection.establish_shared_secret(&our_private, &their_public);
+        connection.peer.set_authenticated();
+        if let Ok(config) = crate::config::load_config_cached() {
+            connection.set_rate_limits(config.upload_limit_bytes_per_sec, config.download_limit_bytes_per_sec);
+        }
+        connection.peer.set_transport(TransportKind::WebSocket);
 
-            let message_id = message.id.to_string();
-            let message_json = serde_json::to_string(&message)?;
-            connection.send_message(&message_json).await?;
+        let peer = connection.peer.clone();
+        let connection_id = connection.connection_id;
 
-            // Send to local message handler
-            let _ = self.message_sender.send(message);
+        if self.stats.read().await.has_seen(&peer.id.to_string()) {
+            self.stats.write().await.record_reconnect(&peer.id.to_string());
+        }
 
-            Ok(message_id)
-        } else {
-            Err(anyhow!("Peer not found or not connected"))
+        {
+            let mut conns = self.connections.write().await;
+            conns.insert(peer.id, connection);
         }
+        self.publish_peer_snapshot().await;
+
+        info!("[conn {}] Connected to WebSocket peer at {}", connection_id, address);
+        Self::spawn_reader(
+            peer.id,
+            connection_id,
+            self.connections.clone(),
+            self.quic_peers.clone(),
+            self.peer_snapshot.clone(),
+            self.message_sender.clone(),
+            self.dnd.clone(),
+            self.stats.clone(),
+            self.dedup.clone(),
+            self.identity.user_id,
+            self.identity.get_display_name(),
+            self.outgoing_transfers.clone(),
+            self.pending_offers.clone(),
+            self.incoming_transfers.clone(),
+        );
+
+        Ok(peer)
     }
 
     pub async fn get_connected_peers(&self) -> Vec<Peer> {
-        let connections = self.connections.read().await;
-        connections.values().map(|conn| conn.peer.clone()).collect()
+        self.peer_snapshot.load().as_ref().clone()
     }
 
     pub async fn disconnect_peer(&self, peer_id: Uuid) -> Result<()> {
-        let mut connections = self.connections.write().await;
-        if let Some(mut connection) = connections.remove(&peer_id) {
+        let removed = {
+            let mut connections = self.connections.write().await;
+            connections.remove(&peer_id)
+        };
+
+        if let Some(mut connection) = removed {
+            self.notify_disconnect(&mut connection, "User disconnected").await;
             connection.peer.set_disconnected();
+            self.publish_peer_snapshot().await;
             info!("Disconnected from peer {}", peer_id);
             Ok(())
         } else {
@@ -294,6 +2745,25 @@ impl NetworkManager {
         }
     }
 
+    /// Best-effort notification to `connection`'s peer that we're disconnecting
+    /// gracefully, so it can mark us offline immediately instead of waiting for
+    /// a read error or heartbeat timeout.
+    async fn notify_disconnect(&self, connection: &mut PeerConnection, reason: &str) {
+        let message = Message::disconnect_message(
+            self.identity.user_id,
+            self.identity.get_display_name(),
+            reason.to_string(),
+        );
+        if let Ok(encoded) = crate::protocol::encode_message(&message, connection.protocol_version, connection.compression_enabled)
+            && let Err(e) = connection.send_message(&encoded).await
+        {
+            debug!(
+                "Could not notify peer {} of disconnect: {}",
+                connection.peer.id, e
+            );
+        }
+    }
+
     pub async fn receive_messages(&self) -> Option<Message> {
         let mut receiver = self.message_receiver.write().await;
         receiver.recv().await
@@ -313,11 +2783,14 @@ impl NetworkManager {
 
             // For now, just return online if connection exists
             // In a real implementation, you'd send an actual ping and wait for response
+            let response_time = start_time.elapsed().as_millis() as u64;
+            self.stats.write().await.record_rtt(peer_id, response_time);
+
             crate::peer::PeerPingStatus {
                 user_id: peer_id.to_string(),
                 is_online: true,
                 last_seen: chrono::Utc::now(),
-                response_time: Some(start_time.elapsed().as_millis() as u64),
+                response_time: Some(response_time),
             }
         } else {
             crate::peer::PeerPingStatus::offline(peer_id.to_string())
@@ -331,27 +2804,101 @@ impl NetworkManager {
 
     pub async fn stop_listening(&self) -> Result<()> {
         info!("Stopping listening for new connections...");
-        // Note: In a real implementation, you'd want to store the listener handle
-        // and be able to stop it. For now, we'll just shutdown existing connections.
+
+        if let Some(handle) = self.listener_handle.write().await.take() {
+            handle.abort();
+            info!("Accept loop stopped, port released");
+        }
+        self.stop_additional_listeners().await;
+
         self.shutdown_connections().await;
         Ok(())
     }
 
+    /// Stops every listener started via `start_additional_listener`,
+    /// releasing each bound interface.
+    async fn stop_additional_listeners(&self) {
+        for (addr, handle) in self.extra_listener_handles.write().await.drain(..) {
+            handle.abort();
+            info!("Accept loop on {} stopped, port released", addr);
+        }
+    }
+
     pub async fn shutdown_connections(&self) {
         info!("Shutting down all connections...");
 
-        let mut connections = self.connections.write().await;
-        for (peer_id, mut connection) in connections.drain() {
+        let drained: Vec<(Uuid, PeerConnection)> =
+            self.connections.write().await.drain().collect();
+        for (peer_id, mut connection) in drained {
+            self.notify_disconnect(&mut connection, "Session ended").await;
             connection.peer.set_disconnected();
             info!("Disconnected from peer {}", peer_id);
         }
+        self.publish_peer_snapshot().await;
 
         info!("All connections shut down");
     }
 
     pub async fn shutdown(&mut self) {
         info!("Shutting down network manager...");
+
+        if let Some(handle) = self.listener_handle.write().await.take() {
+            handle.abort();
+            info!("Accept loop stopped, port released");
+        }
+        self.stop_additional_listeners().await;
+
         self.shutdown_connections().await;
         info!("Network manager shutdown complete");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::InMemoryTransport;
+
+    fn test_peer() -> Peer {
+        Peer::new(
+            Uuid::new_v4(),
+            "peer@test".to_string(),
+            "Peer".to_string(),
+            "127.0.0.1:0".parse().unwrap(),
+            String::new(),
+        )
+    }
+
+    /// Queues one never-completing chunk (claims `total: 2`, only ever sends
+    /// index 0) under a fresh `manifest_id`, the same wire shape
+    /// `PeerConnection::send_chunked_chat_frame` produces.
+    async fn send_incomplete_chat_chunk(remote: &mut InMemoryTransport) {
+        let frame = crate::chat_chunk::ChatChunkFrame {
+            manifest_id: Uuid::new_v4(),
+            index: 0,
+            total: 2,
+            data: vec![0u8],
+        };
+        let mut body = vec![1u8];
+        body.extend_from_slice(&frame.encode().expect("encode"));
+        let framed = multiplex::frame(Channel::Chat, &body);
+        remote.write_frame(&framed).await.expect("write_frame");
+    }
+
+    #[tokio::test]
+    async fn concurrent_chat_reassemblies_are_capped_by_evicting_the_oldest() {
+        let (local, mut remote) = InMemoryTransport::pair();
+        let mut connection = PeerConnection::new(test_peer(), local);
+        connection.shared_secret = Some([0u8; 32]);
+
+        for _ in 0..MAX_CONCURRENT_CHAT_REASSEMBLIES + 5 {
+            send_incomplete_chat_chunk(&mut remote).await;
+            // None of these ever complete, so `receive_message` only ever
+            // loops internally and times out - that's the point, it proves
+            // the map stays bounded rather than growing per manifest_id.
+            let _ = tokio::time::timeout(std::time::Duration::from_millis(20), connection.receive_message()).await;
+        }
+
+        assert_eq!(connection.chat_reassembly.len(), MAX_CONCURRENT_CHAT_REASSEMBLIES);
+        assert_eq!(connection.chat_reassembly_order.len(), MAX_CONCURRENT_CHAT_REASSEMBLIES);
+    }
+}
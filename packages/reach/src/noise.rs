@@ -0,0 +1,253 @@
+use anyhow::{Result, anyhow};
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use x25519_dalek::{EphemeralSecret, PublicKey};
+
+use crate::crypto::CryptoEngine;
+use crate::identity::Identity;
+use crate::message::Message;
+
+/// Result of a completed `handshake()` call: the peer's decrypted handshake
+/// message, the secret derived for it, and whether the peer's static
+/// (long-term) identity key was authenticated - see `HandshakeOutcome::authenticated`.
+pub struct HandshakeOutcome {
+    pub peer_message: Message,
+    pub ephemeral_secret: [u8; 32],
+    /// `true` if the peer proved possession of the private signing key behind
+    /// `peer_message.signing_key` by signing this specific handshake's
+    /// ephemeral transcript - see `Envelope::static_key_signature`. `false`
+    /// if the peer had no signing key to sign with (identities
+    /// saved before `signing_key` existed - see `Identity::signing_key`) or
+    /// the signature didn't check out, in which case this handshake gives no
+    /// stronger guarantee than the unauthenticated DH secret. This is
+    /// possession proof, not identity pinning - same as the rest of this
+    /// codebase's trust-on-first-use model (see `addressbook::AddressBook`),
+    /// a first-seen `signing_key` is only as trustworthy as the channel it
+    /// was first seen over.
+    pub authenticated: bool,
+}
+
+/// What actually crosses the wire encrypted under the ephemeral DH secret -
+/// the real handshake message plus proof that whoever holds `peer_message`'s
+/// `signing_key` sent it, not just a copy of the plaintext ephemeral values
+/// seen on the wire.
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    message: Message,
+    /// Base64 Ed25519 signature over `transcript(our_ephemeral, their_ephemeral)`
+    /// (from the signer's point of view) made with the sender's
+    /// `Identity::sign` - see `transcript`. `None` if the sender had no
+    /// signing key to sign with.
+    static_key_signature: Option<String>,
+}
+
+/// Noise XX-shaped handshake: both sides generate a fresh X25519 ephemeral
+/// keypair and send the public half in the clear (the one plaintext step
+/// real Noise has too), then derive a transport secret from the actual
+/// X25519 Diffie-Hellman shared point - not from the plaintext ephemeral
+/// values themselves - and use it to encrypt each side's static identity
+/// (display name, long-term public key, Ed25519 verifying key). Each side
+/// also signs the ephemeral exchange with its long-term Ed25519 signing key
+/// before encrypting, so the peer can confirm whoever answered actually
+/// holds the private key behind the identity it's claiming - see
+/// `HandshakeOutcome::authenticated`. Both sides run this same function;
+/// there is no separate initiator/responder role to pick.
+pub async fn handshake<S>(stream: &mut S, our_message: &Message, identity: &Identity) -> Result<HandshakeOutcome>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let our_ephemeral_secret = EphemeralSecret::random_from_rng(OsRng);
+    let our_ephemeral_public = PublicKey::from(&our_ephemeral_secret);
+
+    stream.write_all(our_ephemeral_public.as_bytes()).await?;
+    stream.flush().await?;
+
+    let mut their_ephemeral_bytes = [0u8; 32];
+    stream.read_exact(&mut their_ephemeral_bytes).await?;
+    let their_ephemeral_public = PublicKey::from(their_ephemeral_bytes);
+
+    let shared_point = our_ephemeral_secret.diffie_hellman(&their_ephemeral_public);
+    let secret = derive_transport_secret(shared_point.as_bytes(), &our_ephemeral_public, &their_ephemeral_public);
+
+    let our_transcript = transcript(&our_ephemeral_public, &their_ephemeral_public);
+    let envelope = Envelope {
+        message: our_message.clone(),
+        static_key_signature: identity.sign(&our_transcript),
+    };
+    let our_json = serde_json::to_string(&envelope)?;
+    let encrypted = CryptoEngine::encrypt_message(&our_json, &secret)?;
+    stream
+        .write_all(format!("{}\n", encrypted).as_bytes())
+        .await?;
+    stream.flush().await?;
+
+    let mut buffer = vec![0u8; 4096];
+    let n = stream.read(&mut buffer).await?;
+    if n == 0 {
+        return Err(anyhow!("Connection closed during handshake"));
+    }
+
+    let their_encrypted = String::from_utf8_lossy(&buffer[..n]);
+    let their_json = CryptoEngine::decrypt_message(their_encrypted.trim(), &secret)?;
+    let their_envelope: Envelope = serde_json::from_str(&their_json)?;
+
+    // From the peer's point of view when it signed, "our ephemeral" was
+    // their_ephemeral_public and "their ephemeral" was our_ephemeral_public -
+    // the mirror image of `our_transcript` above.
+    let their_transcript = transcript(&their_ephemeral_public, &our_ephemeral_public);
+    let authenticated = their_envelope
+        .static_key_signature
+        .as_deref()
+        .is_some_and(|signature| verify(&their_transcript, signature, &their_envelope.message.signing_key));
+
+    Ok(HandshakeOutcome {
+        peer_message: their_envelope.message,
+        ephemeral_secret: secret,
+        authenticated,
+    })
+}
+
+/// Bytes a static-key signature covers: the signer's own ephemeral public
+/// key followed by the peer's, in that order - deliberately not sorted, so a
+/// signature made for one handshake can't be replayed as the other party's
+/// in a different one (each side's transcript differs by which key goes
+/// first).
+fn transcript(own_ephemeral: &PublicKey, their_ephemeral: &PublicKey) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(64);
+    bytes.extend_from_slice(own_ephemeral.as_bytes());
+    bytes.extend_from_slice(their_ephemeral.as_bytes());
+    bytes
+}
+
+/// Checks `signature` (base64 Ed25519, as produced by `Identity::sign`)
+/// against `data`, using `verifying_key_base64` (as advertised in
+/// `Message::signing_key`) - same decode/verify shape as
+/// `Message::verify_signature`, just over a caller-supplied transcript
+/// instead of a message's `signable_bytes`. `false` on any malformed input
+/// rather than erroring, since an unauthenticated handshake is a valid
+/// (if weaker) outcome here, not a failure to propagate.
+fn verify(data: &[u8], signature: &str, verifying_key_base64: &str) -> bool {
+    let Ok(key_bytes) = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, verifying_key_base64)
+    else {
+        return false;
+    };
+    let Ok(key_bytes): Result<[u8; 32], _> = key_bytes.try_into() else {
+        return false;
+    };
+    let Ok(verifying_key) = ed25519_dalek::VerifyingKey::from_bytes(&key_bytes) else {
+        return false;
+    };
+    let Ok(sig_bytes) = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, signature) else {
+        return false;
+    };
+    let Ok(sig_bytes): Result<[u8; 64], _> = sig_bytes.try_into() else {
+        return false;
+    };
+    let signature = ed25519_dalek::Signature::from_bytes(&sig_bytes);
+    ed25519_dalek::Verifier::verify(&verifying_key, data, &signature).is_ok()
+}
+
+/// Derives the AES-256-GCM key used to encrypt the handshake envelope from
+/// the real X25519 DH output, domain-separated with both ephemeral public
+/// keys (sorted so both sides land on the same bytes regardless of which one
+/// is dialing) so the same DH output never produces the same transport
+/// secret across two different ephemeral pairs.
+fn derive_transport_secret(shared_point: &[u8; 32], a: &PublicKey, b: &PublicKey) -> [u8; 32] {
+    let (first, second) = if a.as_bytes() <= b.as_bytes() {
+        (a, b)
+    } else {
+        (b, a)
+    };
+
+    let mut hasher = Sha256::new();
+    hasher.update(shared_point);
+    hasher.update(first.as_bytes());
+    hasher.update(second.as_bytes());
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identity::UserCredentials;
+
+    fn identity(email: &str) -> Identity {
+        Identity::new(UserCredentials {
+            email: email.to_string(),
+            name: "Tester".to_string(),
+            password: "hunter2".to_string(),
+        })
+        .expect("identity creation")
+    }
+
+    fn handshake_message(identity: &Identity) -> Message {
+        Message::handshake_message(
+            identity.user_id,
+            identity.keypair.public_key.clone(),
+            identity.get_display_name(),
+            identity.verifying_key_base64().unwrap_or_default(),
+        )
+    }
+
+    #[tokio::test]
+    async fn handshake_derives_a_shared_secret_and_authenticates_both_sides() {
+        let alice = identity("alice@example.com");
+        let bob = identity("bob@example.com");
+        let (mut alice_stream, mut bob_stream) = tokio::io::duplex(8192);
+
+        let alice_message = handshake_message(&alice);
+        let bob_message = handshake_message(&bob);
+
+        let (alice_outcome, bob_outcome) = tokio::join!(
+            handshake(&mut alice_stream, &alice_message, &alice),
+            handshake(&mut bob_stream, &bob_message, &bob),
+        );
+        let alice_outcome = alice_outcome.expect("alice's handshake");
+        let bob_outcome = bob_outcome.expect("bob's handshake");
+
+        // Both sides land on the same transport secret despite never putting
+        // it on the wire themselves - it's derived from the X25519 DH
+        // output, not the plaintext ephemeral values an eavesdropper also saw.
+        assert_eq!(alice_outcome.ephemeral_secret, bob_outcome.ephemeral_secret);
+        assert!(alice_outcome.authenticated);
+        assert!(bob_outcome.authenticated);
+        assert_eq!(alice_outcome.peer_message.sender_id, bob.user_id);
+        assert_eq!(bob_outcome.peer_message.sender_id, alice.user_id);
+    }
+
+    #[tokio::test]
+    async fn handshake_is_unauthenticated_when_a_side_has_no_signing_key() {
+        let alice = identity("alice@example.com");
+        let mut bob = identity("bob@example.com");
+        bob.signing_key = None;
+        let (mut alice_stream, mut bob_stream) = tokio::io::duplex(8192);
+
+        let alice_message = handshake_message(&alice);
+        let bob_message = handshake_message(&bob);
+
+        let (alice_outcome, bob_outcome) = tokio::join!(
+            handshake(&mut alice_stream, &alice_message, &alice),
+            handshake(&mut bob_stream, &bob_message, &bob),
+        );
+        let alice_outcome = alice_outcome.expect("alice's handshake");
+        let bob_outcome = bob_outcome.expect("bob's handshake");
+
+        // Bob never signed anything, so Alice can't authenticate him; Bob can
+        // still authenticate Alice since she did sign.
+        assert!(!alice_outcome.authenticated);
+        assert!(bob_outcome.authenticated);
+    }
+
+    #[test]
+    fn verify_rejects_a_signature_over_a_different_transcript() {
+        let signer = identity("signer@example.com");
+        let real_transcript = b"real transcript".to_vec();
+        let signature = signer.sign(&real_transcript).expect("identity has a signing key");
+        let verifying_key = signer.verifying_key_base64().expect("identity has a signing key");
+
+        assert!(verify(&real_transcript, &signature, &verifying_key));
+        assert!(!verify(b"forged transcript", &signature, &verifying_key));
+    }
+}
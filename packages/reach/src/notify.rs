@@ -0,0 +1,103 @@
+use anyhow::{Result, anyhow};
+use chrono::Utc;
+use log::warn;
+use std::process::Command;
+
+use crate::config::Config;
+
+/// Resolves which sound (if any) should play for a message from `peer_id`,
+/// preferring a per-peer override in `Config::peer_notification_sounds` over
+/// the global `Config::notification_sound`. A per-peer value of `"none"`
+/// mutes that peer specifically without touching the global setting.
+fn resolve_sound<'a>(config: &'a Config, peer_id: &str) -> Option<&'a str> {
+    match config.peer_notification_sounds.get(peer_id) {
+        Some(sound) if sound == "none" => None,
+        Some(sound) => Some(sound.as_str()),
+        None => config.notification_sound.as_deref(),
+    }
+}
+
+/// True if `content` mentions `display_name` as `@name` (case-insensitive).
+pub fn is_mention(content: &str, display_name: &str) -> bool {
+    let needle = format!("@{}", display_name.to_lowercase());
+    content.to_lowercase().contains(&needle)
+}
+
+/// True if `peer_id`'s `Config::muted_until` snooze hasn't expired yet.
+/// A past or missing timestamp counts as not muted, so an expired snooze
+/// unmutes itself on the next message with no cleanup needed.
+fn is_muted(config: &Config, peer_id: &str) -> bool {
+    config
+        .muted_until
+        .get(peer_id)
+        .is_some_and(|until| *until > Utc::now())
+}
+
+/// Parses a short duration like `"2h"`, `"30m"`, `"1d"`, `"45s"` into a
+/// `chrono::Duration` - see `/mute` and `SessionManager::mute_conversation`.
+pub fn parse_duration(input: &str) -> Result<chrono::Duration> {
+    let bad = || anyhow!("Invalid duration '{}' - expected e.g. '2h', '30m', '1d'", input);
+
+    let (amount, unit) = input.split_at(input.len().saturating_sub(1));
+    let amount: i64 = amount.parse().map_err(|_| bad())?;
+
+    match unit {
+        "s" => Ok(chrono::Duration::seconds(amount)),
+        "m" => Ok(chrono::Duration::minutes(amount)),
+        "h" => Ok(chrono::Duration::hours(amount)),
+        "d" => Ok(chrono::Duration::days(amount)),
+        _ => Err(bad()),
+    }
+}
+
+/// Alerts the user that a message from `peer_id` arrived, via a terminal bell
+/// or an audio file - see `Config::notification_sound`. Do Not Disturb and an
+/// active `/mute` both suppress everything except mentions, matching most
+/// chat clients. A missing player binary or audio file only logs a warning -
+/// a failed notification shouldn't interrupt the chat session.
+pub fn notify(config: &Config, peer_id: &str, is_dnd: bool, is_mention: bool) {
+    if (is_dnd || is_muted(config, peer_id)) && !is_mention {
+        return;
+    }
+
+    let Some(sound) = resolve_sound(config, peer_id) else {
+        return;
+    };
+
+    if sound == "bell" {
+        print!("\x07");
+        let _ = std::io::Write::flush(&mut std::io::stdout());
+        return;
+    }
+
+    let path = sound.to_string();
+    let player = play_file(&path);
+    if let Err(e) = player {
+        warn!("Failed to play notification sound {}: {}", path, e);
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn play_file(path: &str) -> std::io::Result<std::process::Child> {
+    Command::new("afplay").arg(path).spawn()
+}
+
+#[cfg(target_os = "linux")]
+fn play_file(path: &str) -> std::io::Result<std::process::Child> {
+    Command::new("aplay").arg(path).spawn()
+}
+
+#[cfg(target_os = "windows")]
+fn play_file(path: &str) -> std::io::Result<std::process::Child> {
+    Command::new("powershell")
+        .args(["-c", &format!("(New-Object Media.SoundPlayer '{}').PlaySync();", path)])
+        .spawn()
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+fn play_file(_path: &str) -> std::io::Result<std::process::Child> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "Audio file notifications are not supported on this platform",
+    ))
+}
@@ -0,0 +1,129 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// How many times `SessionManager`'s background retry loop will re-attempt
+/// an entry before giving up and marking it permanently `Failed` - see
+/// `Outbox::record_attempt`. `/retry` can still re-queue it by hand afterwards.
+pub const MAX_AUTO_RETRIES: u32 = 5;
+
+/// Where a queued message currently stands - see `Outbox`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum OutboxStatus {
+    /// Not attempted since being queued (or re-queued by `/retry`) - waiting
+    /// for the next retry tick.
+    Queued,
+    /// At least one send attempt has failed, and automatic retries haven't
+    /// been exhausted yet.
+    Retrying,
+    /// `MAX_AUTO_RETRIES` attempts all failed - no longer retried
+    /// automatically, but `/retry` can still re-queue it.
+    Failed,
+}
+
+/// One message that couldn't be delivered to a peer - see
+/// `SessionManager::send_message` and `/outbox`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxEntry {
+    pub id: Uuid,
+    pub peer_id: String,
+    pub content: String,
+    pub status: OutboxStatus,
+    pub attempts: u32,
+    pub last_error: String,
+    pub queued_at: DateTime<Utc>,
+}
+
+/// Messages that failed to reach their peer, kept around so the user isn't
+/// left wondering whether "Message sent" actually meant anything - see
+/// `/outbox`, `/retry`, `/discard`. `SessionManager` retries `Queued`/
+/// `Retrying` entries on a timer and promotes a message here the moment a
+/// live send attempt fails, rather than queuing sends up front - most sends
+/// succeed immediately, so there's nothing to show until one doesn't.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Outbox {
+    entries: Vec<OutboxEntry>,
+}
+
+impl Outbox {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a freshly failed send as a new `Queued` entry, ready for the next
+    /// retry tick.
+    pub fn enqueue(&mut self, peer_id: String, content: String, error: String) -> Uuid {
+        let id = Uuid::new_v4();
+        self.entries.push(OutboxEntry {
+            id,
+            peer_id,
+            content,
+            status: OutboxStatus::Queued,
+            attempts: 0,
+            last_error: error,
+            queued_at: Utc::now(),
+        });
+        id
+    }
+
+    pub fn list(&self) -> &[OutboxEntry] {
+        &self.entries
+    }
+
+    pub fn get(&self, id: Uuid) -> Option<&OutboxEntry> {
+        self.entries.iter().find(|entry| entry.id == id)
+    }
+
+    /// Records the outcome of a retry attempt: on failure, bumps `attempts`
+    /// and flips to `Failed` once `MAX_AUTO_RETRIES` is reached; on success,
+    /// removes the entry entirely.
+    pub fn record_attempt(&mut self, id: Uuid, result: Result<(), String>) {
+        match result {
+            Ok(()) => {
+                self.remove(id);
+            }
+            Err(error) => {
+                if let Some(entry) = self.entries.iter_mut().find(|entry| entry.id == id) {
+                    entry.attempts += 1;
+                    entry.last_error = error;
+                    entry.status = if entry.attempts >= MAX_AUTO_RETRIES {
+                        OutboxStatus::Failed
+                    } else {
+                        OutboxStatus::Retrying
+                    };
+                }
+            }
+        }
+    }
+
+    /// Re-queues a `Failed` (or any) entry for another round of automatic
+    /// retries, resetting its attempt count - see `/retry`.
+    pub fn requeue(&mut self, id: Uuid) -> bool {
+        if let Some(entry) = self.entries.iter_mut().find(|entry| entry.id == id) {
+            entry.attempts = 0;
+            entry.status = OutboxStatus::Queued;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Drops an entry without retrying it again - see `/discard`.
+    pub fn discard(&mut self, id: Uuid) -> bool {
+        self.remove(id).is_some()
+    }
+
+    fn remove(&mut self, id: Uuid) -> Option<OutboxEntry> {
+        let index = self.entries.iter().position(|entry| entry.id == id)?;
+        Some(self.entries.remove(index))
+    }
+
+    /// `Queued`/`Retrying` entries due for the next automatic retry pass.
+    pub(crate) fn pending(&self) -> Vec<OutboxEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.status != OutboxStatus::Failed)
+            .cloned()
+            .collect()
+    }
+}
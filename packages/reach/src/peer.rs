@@ -12,6 +12,68 @@ pub enum PeerStatus {
     Authenticated,
 }
 
+/// Which transport a peer's traffic is carried over. See `quic::QuicTransport`
+/// for the alongside-TCP datagram path this selects.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub enum TransportKind {
+    #[default]
+    Tcp,
+    Quic,
+    WebSocket,
+    /// Reached over `transport::SerialTransport` - a cable or Bluetooth
+    /// RFCOMM link rather than a network socket. Only ever set when the
+    /// `serial` feature is enabled.
+    Serial,
+}
+
+/// Feature bitset a peer advertises during the handshake (see
+/// `Message::handshake_message`), so a sender can check what the other side
+/// actually supports and fail fast with a clear error instead of sending
+/// something the peer has no way to handle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct Capabilities(u32);
+
+impl Capabilities {
+    pub const COMPRESSION: Capabilities = Capabilities(1 << 0);
+    pub const FILE_TRANSFER: Capabilities = Capabilities(1 << 1);
+    pub const GROUPS: Capabilities = Capabilities(1 << 2);
+    pub const PQ_CRYPTO: Capabilities = Capabilities(1 << 3);
+
+    /// What this build actually implements today. Groups and post-quantum
+    /// crypto are reserved bits with no behavior behind them yet - only the
+    /// stubbed `network::Channel::FileTransfer` path and zstd payload
+    /// compression (see `protocol::encode_message`) actually exist.
+    pub fn supported() -> Capabilities {
+        Capabilities(Capabilities::FILE_TRANSFER.0 | Capabilities::COMPRESSION.0)
+    }
+
+    pub fn from_bits(bits: u32) -> Capabilities {
+        Capabilities(bits)
+    }
+
+    pub fn bits(self) -> u32 {
+        self.0
+    }
+
+    pub fn has(self, flag: Capabilities) -> bool {
+        self.0 & flag.0 != 0
+    }
+
+    /// Names of every flag set, for `/capabilities` to print - see `commands::Command::Capabilities`.
+    pub fn names(self) -> Vec<&'static str> {
+        let all = [
+            (Capabilities::COMPRESSION, "compression"),
+            (Capabilities::FILE_TRANSFER, "file-transfer"),
+            (Capabilities::GROUPS, "groups"),
+            (Capabilities::PQ_CRYPTO, "pq-crypto"),
+        ];
+        all.into_iter()
+            .filter(|(flag, _)| self.has(*flag))
+            .map(|(_, name)| name)
+            .collect()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Peer {
     pub id: Uuid,
@@ -22,6 +84,20 @@ pub struct Peer {
     pub status: PeerStatus,
     pub connected_at: Option<DateTime<Utc>>,
     pub last_seen: DateTime<Utc>,
+    #[serde(default)]
+    pub transport: TransportKind,
+    /// Features this peer advertised during the handshake - see `Capabilities`.
+    #[serde(default)]
+    pub capabilities: Capabilities,
+    /// Base64-encoded Ed25519 verifying key this peer advertised during the
+    /// handshake, used to check `Message::signature` on messages claiming to
+    /// be from them - see `Identity::signing_key` and
+    /// `Message::verify_signature`. Empty for peers that predate this field
+    /// or didn't have a signing key configured, in which case their messages
+    /// are accepted unverified, the same tolerant handling
+    /// `protocol_versions`/`capabilities` get from older peers.
+    #[serde(default)]
+    pub signing_key: String,
 }
 
 impl Peer {
@@ -41,9 +117,16 @@ impl Peer {
             status: PeerStatus::Connecting,
             connected_at: None,
             last_seen: Utc::now(),
+            transport: TransportKind::default(),
+            capabilities: Capabilities::default(),
+            signing_key: String::new(),
         }
     }
 
+    pub fn set_transport(&mut self, transport: TransportKind) {
+        self.transport = transport;
+    }
+
     pub fn set_connected(&mut self) {
         self.status = PeerStatus::Connected;
         self.connected_at = Some(Utc::now());
@@ -72,11 +155,7 @@ impl Peer {
     }
 
     pub fn connection_duration(&self) -> Option<chrono::Duration> {
-        if let Some(connected_at) = self.connected_at {
-            Some(Utc::now() - connected_at)
-        } else {
-            None
-        }
+        self.connected_at.map(|connected_at| Utc::now() - connected_at)
     }
 }
 
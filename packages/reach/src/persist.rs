@@ -0,0 +1,59 @@
+use anyhow::Result;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+type WriteJob = Box<dyn FnOnce() -> Result<()> + Send>;
+
+/// Runs filesystem writes (config, registry, history - anything saved via
+/// `enqueue`) off the async runtime via `spawn_blocking`, queued and applied
+/// strictly in submission order so a burst of saves (e.g. rapid
+/// `/template save` calls) doesn't pile up competing blocking threads or
+/// race each other. Call `flush` before exit - e.g. from
+/// `SessionManager::end_session` - to guarantee the queue has drained.
+pub struct WriteBehindQueue {
+    sender: mpsc::UnboundedSender<WriteJob>,
+    worker: JoinHandle<()>,
+}
+
+impl WriteBehindQueue {
+    pub fn new() -> Self {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<WriteJob>();
+        let worker = tokio::spawn(async move {
+            while let Some(job) = receiver.recv().await {
+                match tokio::task::spawn_blocking(job).await {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => log::warn!("Deferred write failed: {}", e),
+                    Err(e) => log::warn!("Deferred write task panicked: {}", e),
+                }
+            }
+        });
+        WriteBehindQueue { sender, worker }
+    }
+
+    /// Enqueues a write to run on a blocking thread; returns immediately.
+    pub fn enqueue(&self, job: impl FnOnce() -> Result<()> + Send + 'static) {
+        let _ = self.sender.send(Box::new(job));
+    }
+
+    /// Waits for every write enqueued so far to finish.
+    pub async fn flush(&self) {
+        let (tx, rx) = oneshot::channel();
+        self.enqueue(move || {
+            let _ = tx.send(());
+            Ok(())
+        });
+        let _ = rx.await;
+    }
+}
+
+impl Default for WriteBehindQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for WriteBehindQueue {
+    fn drop(&mut self) {
+        self.worker.abort();
+    }
+}
@@ -0,0 +1,149 @@
+use anyhow::{Result, anyhow};
+use log::{debug, warn};
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpStream, UdpSocket};
+use tokio::time::timeout;
+
+/// Result of a successful router port mapping request.
+#[derive(Debug, Clone)]
+pub struct PortMapping {
+    pub external_port: u16,
+    pub external_ip: Option<String>,
+}
+
+/// Best-effort UPnP IGD port mapping for a listening TCP port, so peers
+/// outside the LAN can connect without the user touching their router.
+///
+/// This speaks just enough of SSDP discovery and the UPnP `WANIPConnection`
+/// SOAP action to map a port on common home routers. It does not implement
+/// NAT-PMP, IPv6, or routers that require authentication; if discovery or
+/// the mapping request fails for any reason, it simply returns `Ok(None)`
+/// so the caller falls back to listening LAN-only.
+pub async fn map_port(port: u16) -> Result<Option<PortMapping>> {
+    let control_url = match discover_control_url().await {
+        Ok(url) => url,
+        Err(e) => {
+            debug!("UPnP discovery failed, continuing without port mapping: {}", e);
+            return Ok(None);
+        }
+    };
+
+    match request_mapping(&control_url, port).await {
+        Ok(mapping) => Ok(Some(mapping)),
+        Err(e) => {
+            warn!("UPnP port mapping request failed: {}", e);
+            Ok(None)
+        }
+    }
+}
+
+/// Sends an SSDP M-SEARCH multicast and extracts the `LOCATION` header (the
+/// router's device description URL) from the first reply. A full client
+/// would also fetch that description to find the real control URL; we
+/// approximate it with the conventional `/ctl/IPConn` path used by most
+/// consumer routers.
+async fn discover_control_url() -> Result<String> {
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    let request = "M-SEARCH * HTTP/1.1\r\n\
+         HOST: 239.255.255.250:1900\r\n\
+         MAN: \"ssdp:discover\"\r\n\
+         MX: 2\r\n\
+         ST: urn:schemas-upnp-org:device:InternetGatewayDevice:1\r\n\r\n";
+
+    socket
+        .send_to(request.as_bytes(), "239.255.255.250:1900")
+        .await?;
+
+    let mut buf = [0u8; 2048];
+    let (n, from) = timeout(Duration::from_secs(2), socket.recv_from(&mut buf))
+        .await
+        .map_err(|_| anyhow!("No UPnP gateway responded within 2s"))??;
+
+    let response = String::from_utf8_lossy(&buf[..n]);
+    debug!("UPnP SSDP reply from {}", from);
+
+    let location = response
+        .lines()
+        .find_map(|line| line.strip_prefix("LOCATION:").or(line.strip_prefix("Location:")))
+        .ok_or_else(|| anyhow!("SSDP reply missing LOCATION header"))?
+        .trim();
+
+    // The router's HTTP server is at LOCATION's ip:port, not the SSDP port
+    // the reply came from; keep that and swap in the conventional path.
+    let gateway_addr: SocketAddr = location
+        .trim_start_matches("http://")
+        .split('/')
+        .next()
+        .unwrap_or_default()
+        .parse()
+        .map_err(|_| anyhow!("LOCATION '{}' has no ip:port to map through", location))?;
+    debug!("Found UPnP gateway at {} ({})", gateway_addr, location);
+
+    Ok(format!("{}/ctl/IPConn", gateway_addr))
+}
+
+/// Issues a raw `AddPortMapping` SOAP request over HTTP to the gateway's
+/// control URL, mapping `port` to this host for both TCP in and out.
+async fn request_mapping(control_url: &str, port: u16) -> Result<PortMapping> {
+    let gateway: SocketAddr = control_url
+        .trim_start_matches("http://")
+        .split('/')
+        .next()
+        .ok_or_else(|| anyhow!("Invalid control URL: {}", control_url))?
+        .parse()
+        .map_err(|_| anyhow!("Could not parse gateway address from {}", control_url))?;
+
+    let local_ip = local_ip_for(gateway).await?;
+
+    let body = format!(
+        r#"<?xml version="1.0"?>
+<s:Envelope xmlns:s="http://schemas.xmlsoap.org/soap/envelope/" s:encodingStyle="http://schemas.xmlsoap.org/soap/encoding/">
+<s:Body><u:AddPortMapping xmlns:u="urn:schemas-upnp-org:service:WANIPConnection:1">
+<NewRemoteHost></NewRemoteHost>
+<NewExternalPort>{port}</NewExternalPort>
+<NewProtocol>TCP</NewProtocol>
+<NewInternalPort>{port}</NewInternalPort>
+<NewInternalClient>{local_ip}</NewInternalClient>
+<NewEnabled>1</NewEnabled>
+<NewPortMappingDescription>rustalk</NewPortMappingDescription>
+<NewLeaseDuration>0</NewLeaseDuration>
+</u:AddPortMapping></s:Body></s:Envelope>"#
+    );
+
+    let request = format!(
+        "POST /ctl/IPConn HTTP/1.1\r\n\
+         Host: {gateway}\r\n\
+         Content-Type: text/xml; charset=\"utf-8\"\r\n\
+         SOAPAction: \"urn:schemas-upnp-org:service:WANIPConnection:1#AddPortMapping\"\r\n\
+         Content-Length: {len}\r\n\
+         Connection: close\r\n\r\n{body}",
+        len = body.len()
+    );
+
+    let mut stream = timeout(Duration::from_secs(3), TcpStream::connect(gateway)).await??;
+    stream.write_all(request.as_bytes()).await?;
+
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).await?;
+    let response = String::from_utf8_lossy(&response);
+
+    if !response.contains("200 OK") {
+        return Err(anyhow!("Gateway rejected port mapping: {}", response.lines().next().unwrap_or("")));
+    }
+
+    Ok(PortMapping {
+        external_port: port,
+        external_ip: None,
+    })
+}
+
+/// Opens a throwaway UDP socket toward `gateway` to learn which local
+/// interface address the OS would route through - a common trick to find
+/// "our" LAN address without enumerating interfaces.
+async fn local_ip_for(gateway: SocketAddr) -> Result<String> {
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    socket.connect(gateway).await?;
+    Ok(socket.local_addr()?.ip().to_string())
+}
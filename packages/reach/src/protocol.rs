@@ -0,0 +1,93 @@
+use anyhow::{Result, anyhow};
+use base64::Engine;
+
+use crate::message::Message;
+
+/// The oldest wire format this build can still speak: plain JSON text, no
+/// version negotiation. Peers from before this module existed send an empty
+/// `Message::protocol_versions` list during the handshake, which `negotiate`
+/// treats as "only speaks version 1".
+pub const LEGACY_VERSION: u8 = 1;
+
+/// The compact binary format (bincode, base64-wrapped so it still fits
+/// through the existing newline-delimited `Channel::Chat` framing - see
+/// `multiplex`) this build prefers when the peer also supports it.
+pub const BINARY_VERSION: u8 = 2;
+
+/// Every wire format this build can produce or parse, newest first. Sent in
+/// `Message::handshake_message` so both sides can agree on the highest one
+/// they share - see `negotiate`.
+pub const SUPPORTED_VERSIONS: &[u8] = &[BINARY_VERSION, LEGACY_VERSION];
+
+/// Picks the highest protocol version both ends understand. An empty or
+/// entirely foreign `remote_versions` (an old peer, or a hypothetical future
+/// one that dropped support for everything we know) falls back to
+/// `LEGACY_VERSION` rather than failing the connection - the same
+/// "interoperate with older and newer clients" goal the binary format itself
+/// is for.
+pub fn negotiate(remote_versions: &[u8]) -> u8 {
+    SUPPORTED_VERSIONS
+        .iter()
+        .find(|version| remote_versions.contains(version))
+        .copied()
+        .unwrap_or(LEGACY_VERSION)
+}
+
+/// Payloads smaller than this aren't worth the zstd framing overhead - see
+/// `encode_message`.
+const COMPRESSION_THRESHOLD_BYTES: usize = 512;
+
+const ZSTD_LEVEL: i32 = 3;
+
+/// Serializes `message` per the negotiated `version`, ready to be encrypted
+/// and sent over `Channel::Chat` - see `PeerConnection::send_message`.
+///
+/// `compression` is whether both ends negotiated `Capabilities::COMPRESSION`
+/// (see `network::PeerConnection::compression_enabled`) - only `BINARY_VERSION`
+/// frames above `COMPRESSION_THRESHOLD_BYTES` are actually compressed even
+/// when it's true; a leading flag byte tells `decode_message` whether this
+/// particular frame needs decompressing, since plenty of short messages
+/// (a "hi", a heartbeat) aren't worth it either way.
+pub fn encode_message(message: &Message, version: u8, compression: bool) -> Result<String> {
+    match version {
+        BINARY_VERSION => {
+            let bytes = bincode::serialize(message)?;
+            let (flag, payload) = if compression && bytes.len() > COMPRESSION_THRESHOLD_BYTES {
+                (1u8, zstd::stream::encode_all(&bytes[..], ZSTD_LEVEL)?)
+            } else {
+                (0u8, bytes)
+            };
+
+            let mut framed = Vec::with_capacity(payload.len() + 1);
+            framed.push(flag);
+            framed.extend_from_slice(&payload);
+            Ok(base64::engine::general_purpose::STANDARD.encode(framed))
+        }
+        LEGACY_VERSION => Ok(serde_json::to_string(message)?),
+        other => Err(anyhow!("Unsupported protocol version: {}", other)),
+    }
+}
+
+/// The `encode_message` counterpart, used once a message has been decrypted
+/// off the wire - see `PeerConnection::receive_message`. Reads the
+/// compression flag byte `encode_message` wrote rather than taking a
+/// `compression` parameter itself, so it works regardless of what either
+/// side negotiated - only what this specific frame actually is matters.
+pub fn decode_message(data: &str, version: u8) -> Result<Message> {
+    match version {
+        BINARY_VERSION => {
+            let framed = base64::engine::general_purpose::STANDARD.decode(data.trim())?;
+            let (&flag, payload) = framed
+                .split_first()
+                .ok_or_else(|| anyhow!("Empty binary frame"))?;
+            let bytes = match flag {
+                0 => payload.to_vec(),
+                1 => zstd::stream::decode_all(payload)?,
+                other => return Err(anyhow!("Unknown compression flag: {}", other)),
+            };
+            Ok(bincode::deserialize(&bytes)?)
+        }
+        LEGACY_VERSION => Ok(serde_json::from_str(data)?),
+        other => Err(anyhow!("Unsupported protocol version: {}", other)),
+    }
+}
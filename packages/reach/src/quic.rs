@@ -0,0 +1,55 @@
+use anyhow::Result;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::UdpSocket;
+use tokio::sync::{RwLock, mpsc};
+
+/// A datagram-based alternative to the TCP transport in `network.rs`,
+/// selectable per-peer alongside it.
+///
+/// This is not real QUIC: there is no TLS handshake, connection migration,
+/// or stream multiplexing, only framed UDP datagrams. Wiring in `quinn`
+/// properly needs a certificate story (self-signed + pinned trust, since
+/// peers aren't CA-issued) this prototype doesn't have yet; until then this
+/// gives `NetworkManager` an interchangeable transport with the same
+/// bind/send/recv shape a real QUIC backend would have.
+pub struct QuicTransport {
+    socket: Arc<UdpSocket>,
+    inbox: RwLock<mpsc::UnboundedReceiver<(SocketAddr, Vec<u8>)>>,
+}
+
+impl QuicTransport {
+    pub async fn bind(addr: SocketAddr) -> Result<Self> {
+        let socket = Arc::new(UdpSocket::bind(addr).await?);
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        let recv_socket = socket.clone();
+        tokio::spawn(async move {
+            let mut buf = vec![0u8; 64 * 1024];
+            while let Ok((n, from)) = recv_socket.recv_from(&mut buf).await {
+                if tx.send((from, buf[..n].to_vec())).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(QuicTransport {
+            socket,
+            inbox: RwLock::new(rx),
+        })
+    }
+
+    pub async fn send_to(&self, addr: SocketAddr, data: &[u8]) -> Result<()> {
+        self.socket.send_to(data, addr).await?;
+        Ok(())
+    }
+
+    /// Waits for the next datagram from any peer.
+    pub async fn recv(&self) -> Option<(SocketAddr, Vec<u8>)> {
+        self.inbox.write().await.recv().await
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.socket.local_addr()?)
+    }
+}
@@ -0,0 +1,42 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+/// Per-IP sliding-window accept limiter for `NetworkManager::start_listening`,
+/// so a single hostile (or misbehaving) peer spamming connections can't
+/// exhaust file descriptors or memory on its own. Paired with a semaphore
+/// capping concurrent in-progress handshakes and a per-handshake timeout -
+/// see `handle_incoming_connection`.
+pub struct ConnectionRateLimiter {
+    max_per_window: usize,
+    window: Duration,
+    recent_accepts: RwLock<HashMap<IpAddr, Vec<Instant>>>,
+}
+
+impl ConnectionRateLimiter {
+    pub fn new(max_per_window: usize, window: Duration) -> Self {
+        ConnectionRateLimiter {
+            max_per_window,
+            window,
+            recent_accepts: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Records an accept from `ip` and returns whether it's within the rate
+    /// limit, pruning timestamps that have aged out of the window as it goes.
+    pub async fn check(&self, ip: IpAddr) -> bool {
+        let now = Instant::now();
+        let mut recent_accepts = self.recent_accepts.write().await;
+        let timestamps = recent_accepts.entry(ip).or_default();
+
+        timestamps.retain(|t| now.duration_since(*t) < self.window);
+
+        if timestamps.len() >= self.max_per_window {
+            false
+        } else {
+            timestamps.push(now);
+            true
+        }
+    }
+}
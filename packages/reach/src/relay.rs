@@ -0,0 +1,117 @@
+use anyhow::{Result, anyhow};
+use log::{debug, info, warn};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{RwLock, mpsc};
+
+type Outbox = mpsc::UnboundedSender<String>;
+
+/// Blind relay for `rus relay` mode: forwards frames between two peers who
+/// can't connect to each other directly. The relay only ever sees frames
+/// addressed by peer id - it never holds a shared secret, so it can't
+/// decrypt what it forwards, regardless of what the peers put in the payload.
+///
+/// This is single-hop only, and deliberately doesn't try to be true
+/// multi-hop onion routing (each relay learning only the next hop, not the
+/// original sender or final recipient, under independent per-hop keys).
+/// That needs a sender to establish a secret with an intermediate relay it
+/// is *not* directly connected to, which this codebase can't do yet:
+/// `crypto::CryptoEngine::generate_shared_secret` isn't real ECDH - it
+/// hashes `our_private || their_public`, which is directionally asymmetric
+/// (the two sides get different results unless they already share a
+/// connection's negotiated secret), and there's no asymmetric-encryption
+/// primitive here to let a sender hand a relay a fresh key out of band
+/// either. Layering encryption on top of that would look like onion
+/// routing without actually hiding anything from an intermediate relay -
+/// worse than not having the feature. The one real X25519 exchange here,
+/// `noise::handshake`, only runs over a direct stream to the peer, so it
+/// doesn't cover a relay further along the path either; multi-hop is left
+/// unimplemented rather than faked.
+pub struct RelayServer {
+    port: u16,
+}
+
+impl RelayServer {
+    pub fn new(port: u16) -> Self {
+        RelayServer { port }
+    }
+
+    pub async fn run(self) -> Result<()> {
+        let addr = format!("0.0.0.0:{}", self.port);
+        let listener = TcpListener::bind(&addr).await?;
+        info!("Relay listening on {}", addr);
+
+        let registrations: Arc<RwLock<HashMap<String, Outbox>>> = Arc::new(RwLock::new(HashMap::new()));
+
+        loop {
+            let (stream, peer_addr) = listener.accept().await?;
+            let registrations = registrations.clone();
+            tokio::spawn(async move {
+                if let Err(e) = Self::handle_client(stream, registrations).await {
+                    debug!("Relay client {} disconnected: {}", peer_addr, e);
+                }
+            });
+        }
+    }
+
+    /// Registration/forwarding protocol, one line per frame:
+    /// `REGISTER <peer_id>` once at connect time, then `TO <target_id> <payload>`
+    /// per outbound frame. Registered peers receive `FROM <sender_id> <payload>`.
+    async fn handle_client(
+        stream: TcpStream,
+        registrations: Arc<RwLock<HashMap<String, Outbox>>>,
+    ) -> Result<()> {
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+
+        let first = lines
+            .next_line()
+            .await?
+            .ok_or_else(|| anyhow!("Client disconnected before registering"))?;
+        let peer_id = first
+            .strip_prefix("REGISTER ")
+            .ok_or_else(|| anyhow!("Expected REGISTER <peer_id>, got '{}'", first))?
+            .trim()
+            .to_string();
+
+        let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+        registrations.write().await.insert(peer_id.clone(), tx);
+        info!("Relay: peer {} registered", peer_id);
+
+        let writer_task = tokio::spawn(async move {
+            while let Some(line) = rx.recv().await {
+                if writer.write_all(format!("{}\n", line).as_bytes()).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        while let Some(line) = lines.next_line().await? {
+            let Some(rest) = line.strip_prefix("TO ") else {
+                warn!("Relay: ignoring malformed frame from {}", peer_id);
+                continue;
+            };
+            let Some((target_id, payload)) = rest.split_once(' ') else {
+                warn!("Relay: malformed TO frame from {}", peer_id);
+                continue;
+            };
+
+            let registrations = registrations.read().await;
+            if let Some(target_tx) = registrations.get(target_id) {
+                let _ = target_tx.send(format!("FROM {} {}", peer_id, payload));
+            } else {
+                debug!(
+                    "Relay: target {} not registered, dropping frame from {}",
+                    target_id, peer_id
+                );
+            }
+        }
+
+        registrations.write().await.remove(&peer_id);
+        writer_task.abort();
+        info!("Relay: peer {} disconnected", peer_id);
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// One message held locally until its `deliver_at` time, instead of being
+/// sent right away - see `/schedule` and `SessionManager::start_schedule_loop`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledMessage {
+    pub id: Uuid,
+    /// Matched by id or display name, same as `SessionManager::send_message`'s
+    /// `target_peer`, but resolved again at delivery time since the peer may
+    /// connect or disconnect between now and `deliver_at`. `None` delivers
+    /// to every peer active at that time, same as `send_message`.
+    pub target_peer: Option<String>,
+    pub content: String,
+    pub deliver_at: DateTime<Utc>,
+}
+
+/// Messages queued by `/schedule` for delayed delivery, persisted to
+/// `scheduled.json` the same way `outbox::Outbox` is persisted to
+/// `outbox.json` - see `SessionManager::persist_schedule`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScheduledQueue {
+    entries: Vec<ScheduledMessage>,
+}
+
+impl ScheduledQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn schedule(&mut self, target_peer: Option<String>, content: String, deliver_at: DateTime<Utc>) -> Uuid {
+        let id = Uuid::new_v4();
+        self.entries.push(ScheduledMessage {
+            id,
+            target_peer,
+            content,
+            deliver_at,
+        });
+        id
+    }
+
+    pub fn list(&self) -> &[ScheduledMessage] {
+        &self.entries
+    }
+
+    /// Removes and returns every entry whose `deliver_at` has passed, ready
+    /// for `start_schedule_loop` to send - see `Outbox::pending`, which this
+    /// mirrors except due entries are taken rather than just inspected,
+    /// since each one only needs to be sent once.
+    pub fn take_due(&mut self, now: DateTime<Utc>) -> Vec<ScheduledMessage> {
+        let mut due = Vec::new();
+        self.entries.retain(|entry| {
+            if entry.deliver_at <= now {
+                due.push(entry.clone());
+                false
+            } else {
+                true
+            }
+        });
+        due
+    }
+
+    /// Cancels a pending scheduled message before it's sent - see `/unschedule`.
+    pub fn cancel(&mut self, id: Uuid) -> bool {
+        let index = self.entries.iter().position(|entry| entry.id == id);
+        match index {
+            Some(index) => {
+                self.entries.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+}
@@ -1,18 +1,78 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::net::SocketAddr;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::{RwLock, mpsc};
+use uuid::Uuid;
 
+use crate::translation::TranslationHook;
+use crate::conversation::{Conversation, ConversationSettings};
+use crate::events::{EVENT_CHANNEL_CAPACITY, SessionEvent};
+use crate::outbox::{Outbox, OutboxEntry};
+use crate::persist::WriteBehindQueue;
+use crate::scheduled::{ScheduledMessage, ScheduledQueue};
 use crate::{Identity, Message, MessageType, NetworkManager, Peer};
+use tokio::sync::broadcast;
+
+/// How often `start_session`'s background task retries `Outbox` entries -
+/// see `outbox::Outbox::pending`.
+const OUTBOX_RETRY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// How often `start_config_watch_loop` checks the config file's mtime - fast
+/// enough that a hand edit feels "instant" without statting the file on
+/// every message.
+const CONFIG_WATCH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// How often `start_schedule_loop` checks for `/schedule`d messages whose
+/// `deliver_at` has passed. Shorter than `OUTBOX_RETRY_INTERVAL` since a
+/// scheduled send is a deliberately timed event a user is watching for,
+/// not a background retry.
+const SCHEDULE_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatSession {
     pub id: String,
     pub active_peers: HashMap<String, Peer>,
+    /// Backed by `storage::MessageStore` (SQLite), not the JSON blob
+    /// `session_state_file` persists the rest of `ChatSession` to - see
+    /// `SessionManager::persist_message` and `start_session`. Skipped here so
+    /// a growing history doesn't get rewritten whole on every `end_session`.
+    #[serde(skip)]
     pub message_history: Vec<Message>,
     pub current_port: u16,
+    /// Peer ids whose conversation is hidden from the active sidebar, but not deleted.
+    pub archived_peers: HashSet<String>,
+    /// Translation hooks keyed by peer id, applied to incoming messages from that peer.
+    pub translation_hooks: HashMap<String, TranslationHook>,
+    /// When set, `SessionManager::send_message` refuses to send - see `/readonly`.
+    /// Local-only, not announced to peers; meant for archived contacts or
+    /// broadcast channels where a message would be sent by mistake.
+    pub read_only: bool,
+    /// Free-text description for this session, set by `/topic` and announced
+    /// to every connected peer via `MessageType::Topic` - see
+    /// `SessionManager::set_topic`. This codebase has no concept of a
+    /// multi-member room or owner/moderator roles, so unlike a real group
+    /// chat's topic, any participant can change it.
+    #[serde(default)]
+    pub topic: Option<String>,
+    /// Unsent drafts keyed by peer id, so switching between conversations in
+    /// a TUI doesn't lose what the user was typing - see `conversations`.
+    #[serde(default)]
+    pub drafts: HashMap<String, String>,
+    /// Per-conversation preferences keyed by peer id - see `conversations`.
+    #[serde(default)]
+    pub conversation_settings: HashMap<String, ConversationSettings>,
+    /// When this session was started - not restored by `restore_saved_state`,
+    /// so it always reflects how long the *current* process has been running
+    /// rather than accumulating across restarts. See `SessionManager::session_stats`.
+    #[serde(skip, default = "chrono::Utc::now")]
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    /// How many peers `add_peer` has added and `remove_peer` has actually
+    /// removed this session - see `SessionManager::session_stats`.
+    #[serde(skip)]
+    pub peers_connected: u64,
+    #[serde(skip)]
+    pub peers_disconnected: u64,
 }
 
 impl ChatSession {
@@ -22,23 +82,134 @@ impl ChatSession {
             active_peers: HashMap::new(),
             message_history: Vec::new(),
             current_port: port,
+            archived_peers: HashSet::new(),
+            translation_hooks: HashMap::new(),
+            read_only: false,
+            topic: None,
+            drafts: HashMap::new(),
+            conversation_settings: HashMap::new(),
+            started_at: chrono::Utc::now(),
+            peers_connected: 0,
+            peers_disconnected: 0,
         }
     }
 
     pub fn add_peer(&mut self, peer: Peer) {
+        self.peers_connected += 1;
         self.active_peers.insert(peer.id.to_string(), peer);
     }
 
     pub fn remove_peer(&mut self, peer_id: &str) -> Option<Peer> {
-        self.active_peers.remove(peer_id)
+        self.archived_peers.remove(peer_id);
+        let removed = self.active_peers.remove(peer_id);
+        if removed.is_some() {
+            self.peers_disconnected += 1;
+        }
+        removed
+    }
+
+    /// Hides a conversation from the active sidebar without deleting its history.
+    pub fn archive_conversation(&mut self, peer_id: &str) -> Result<()> {
+        if !self.active_peers.contains_key(peer_id) {
+            return Err(anyhow::anyhow!("Peer '{}' not found in session", peer_id));
+        }
+        self.archived_peers.insert(peer_id.to_string());
+        Ok(())
     }
 
-    pub fn add_message(&mut self, message: Message) {
+    pub fn unarchive_conversation(&mut self, peer_id: &str) {
+        self.archived_peers.remove(peer_id);
+    }
+
+    pub fn is_archived(&self, peer_id: &str) -> bool {
+        self.archived_peers.contains(peer_id)
+    }
+
+    /// Marks this conversation read-only, so `SessionManager::send_message`
+    /// refuses to send until it's turned off again.
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    pub fn add_message(&mut self, mut message: Message) {
+        self.prepare_message(&mut message);
         self.message_history.push(message);
     }
 
+    /// Inserts a message into history ordered by its original `timestamp`
+    /// rather than arrival order, marking it `delivered_late` if it lands
+    /// before the current tail. Used when an offline queue flushes after a
+    /// reconnect, so catch-up messages appear where they were actually sent
+    /// instead of bunched at the end of the conversation.
+    pub fn merge_message(&mut self, mut message: Message) {
+        self.prepare_message(&mut message);
+
+        let insert_at = self
+            .message_history
+            .iter()
+            .position(|existing| existing.timestamp > message.timestamp)
+            .unwrap_or(self.message_history.len());
+
+        if insert_at != self.message_history.len() {
+            message.delivered_late = true;
+        }
+
+        self.message_history.insert(insert_at, message);
+    }
+
+    /// Shared bookkeeping for any message entering history: unarchiving the
+    /// conversation it belongs to and applying a translation hook, if set.
+    fn prepare_message(&mut self, message: &mut Message) {
+        // A new message in an archived conversation brings it back to the sidebar.
+        self.unarchive_conversation(&message.sender_id.to_string());
+        if let Some(recipient_id) = message.recipient_id {
+            self.unarchive_conversation(&recipient_id.to_string());
+        }
+
+        if let Some(hook) = self.translation_hooks.get(&message.sender_id.to_string())
+            && hook.enabled
+        {
+            match hook.translate(&message.content) {
+                Ok(translated) => message.translated_content = Some(translated),
+                Err(e) => log::warn!(
+                    "Translation hook for peer {} failed: {}",
+                    message.sender_id,
+                    e
+                ),
+            }
+        }
+    }
+
+    /// Enables a translation hook for the given peer, translating incoming messages
+    /// from `source_lang` into `target_lang` via the configured external command.
+    pub fn set_translation_hook(&mut self, peer_id: String, hook: TranslationHook) {
+        self.translation_hooks.insert(peer_id, hook);
+    }
+
+    pub fn disable_translation_hook(&mut self, peer_id: &str) {
+        if let Some(hook) = self.translation_hooks.get_mut(peer_id) {
+            hook.enabled = false;
+        }
+    }
+
+    pub fn get_translation_hook(&self, peer_id: &str) -> Option<&TranslationHook> {
+        self.translation_hooks.get(peer_id)
+    }
+
+    /// Peers visible in the active sidebar, i.e. not archived.
     pub fn get_peers(&self) -> Vec<&Peer> {
-        self.active_peers.values().collect()
+        self.active_peers
+            .values()
+            .filter(|peer| !self.archived_peers.contains(&peer.id.to_string()))
+            .collect()
+    }
+
+    /// Peers whose conversation has been archived.
+    pub fn get_archived_peers(&self) -> Vec<&Peer> {
+        self.active_peers
+            .values()
+            .filter(|peer| self.archived_peers.contains(&peer.id.to_string()))
+            .collect()
     }
 
     pub fn get_peer(&self, peer_id: &str) -> Option<&Peer> {
@@ -53,6 +224,257 @@ impl ChatSession {
         };
         self.message_history[start..].iter().collect()
     }
+
+    /// Flags a message for the starred review view, across whichever conversation it belongs to.
+    pub fn star_message(&mut self, message_id: Uuid) -> Result<()> {
+        let message = self
+            .message_history
+            .iter_mut()
+            .find(|message| message.id == message_id)
+            .ok_or_else(|| anyhow::anyhow!("Message '{}' not found in history", message_id))?;
+        message.starred = true;
+        Ok(())
+    }
+
+    pub fn unstar_message(&mut self, message_id: Uuid) -> Result<()> {
+        let message = self
+            .message_history
+            .iter_mut()
+            .find(|message| message.id == message_id)
+            .ok_or_else(|| anyhow::anyhow!("Message '{}' not found in history", message_id))?;
+        message.starred = false;
+        Ok(())
+    }
+
+    pub fn get_starred_messages(&self) -> Vec<&Message> {
+        self.message_history.iter().filter(|m| m.starred).collect()
+    }
+
+    /// Looks up a message by id - see `SessionManager::forward_message`.
+    pub fn get_message(&self, message_id: Uuid) -> Option<&Message> {
+        self.message_history.iter().find(|message| message.id == message_id)
+    }
+
+    /// Pins a message to highlight it within its conversation - see `/pin`.
+    /// Unlike `star_message`, which is a cross-conversation review flag,
+    /// a pin is meant to be read back per-conversation via `get_pinned_messages`.
+    pub fn pin_message(&mut self, message_id: Uuid) -> Result<()> {
+        let message = self
+            .message_history
+            .iter_mut()
+            .find(|message| message.id == message_id)
+            .ok_or_else(|| anyhow::anyhow!("Message '{}' not found in history", message_id))?;
+        message.pinned = true;
+        Ok(())
+    }
+
+    pub fn unpin_message(&mut self, message_id: Uuid) -> Result<()> {
+        let message = self
+            .message_history
+            .iter_mut()
+            .find(|message| message.id == message_id)
+            .ok_or_else(|| anyhow::anyhow!("Message '{}' not found in history", message_id))?;
+        message.pinned = false;
+        Ok(())
+    }
+
+    /// Pinned messages in the conversation with `peer_id` - either side of
+    /// it, since a pin might be on something we sent or something they sent.
+    pub fn get_pinned_messages(&self, peer_id: &str) -> Vec<&Message> {
+        self.message_history
+            .iter()
+            .filter(|message| {
+                message.pinned
+                    && (message.sender_id.to_string() == peer_id
+                        || message.recipient_id.is_some_and(|id| id.to_string() == peer_id))
+            })
+            .collect()
+    }
+
+    /// Marks every unread message from `peer_id` as read locally, returning
+    /// their ids so the caller can announce them via `MessageType::ReadReceipt`
+    /// if `Config::read_receipts_enabled` - see `SessionManager::mark_conversation_read`.
+    pub fn mark_conversation_read(&mut self, peer_id: &str) -> Vec<Uuid> {
+        self.message_history
+            .iter_mut()
+            .filter(|message| message.sender_id.to_string() == peer_id && message.read_at.is_none())
+            .map(|message| {
+                message.read_at = Some(chrono::Utc::now());
+                message.id
+            })
+            .collect()
+    }
+
+    /// Applies an inbound `ReadReceipt`: stamps `read_at` on every listed
+    /// message still unread, leaving an earlier timestamp alone if one
+    /// somehow already got there first.
+    pub fn apply_read_receipt(&mut self, message_ids: &[Uuid], read_at: chrono::DateTime<chrono::Utc>) {
+        for message in self
+            .message_history
+            .iter_mut()
+            .filter(|message| message_ids.contains(&message.id) && message.read_at.is_none())
+        {
+            message.read_at = Some(read_at);
+        }
+    }
+
+    /// Replaces `message_id`'s content and marks it edited - see `/edit`.
+    /// Refuses the edit if `editor_id` isn't the message's original sender,
+    /// whether that's the local user editing their own message or an inbound
+    /// `MessageType::Edit` claiming to be from someone it isn't.
+    pub fn apply_edit(&mut self, editor_id: Uuid, message_id: Uuid, new_content: String) -> Result<()> {
+        let message = self
+            .message_history
+            .iter_mut()
+            .find(|message| message.id == message_id)
+            .ok_or_else(|| anyhow::anyhow!("Message '{}' not found in history", message_id))?;
+
+        if message.sender_id != editor_id {
+            return Err(anyhow::anyhow!(
+                "Only the original sender can edit message '{}'",
+                message_id
+            ));
+        }
+
+        message.content = new_content;
+        message.edited = true;
+        Ok(())
+    }
+
+    /// Tombstones `message_id`: clears its text and flags it retracted -
+    /// see `/retract`. Same sender-only restriction as `apply_edit`.
+    pub fn apply_retraction(&mut self, requester_id: Uuid, message_id: Uuid) -> Result<()> {
+        let message = self
+            .message_history
+            .iter_mut()
+            .find(|message| message.id == message_id)
+            .ok_or_else(|| anyhow::anyhow!("Message '{}' not found in history", message_id))?;
+
+        if message.sender_id != requester_id {
+            return Err(anyhow::anyhow!(
+                "Only the original sender can retract message '{}'",
+                message_id
+            ));
+        }
+
+        message.content = String::new();
+        message.translated_content = None;
+        message.retracted = true;
+        Ok(())
+    }
+
+    /// Applies an inbound `DeliveryAck`: marks `message_id` delivered, unless
+    /// it was already marked `Failed` by the send path giving up on every
+    /// target first - an ack arriving after that would be for a retry we
+    /// don't have a record of and shouldn't overwrite `Failed`.
+    pub fn apply_delivery_ack(&mut self, message_id: Uuid) -> Result<()> {
+        let message = self
+            .message_history
+            .iter_mut()
+            .find(|message| message.id == message_id)
+            .ok_or_else(|| anyhow::anyhow!("Message '{}' not found in history", message_id))?;
+
+        if message.delivery_status == crate::message::DeliveryStatus::Sent {
+            message.delivery_status = crate::message::DeliveryStatus::Delivered;
+        }
+        Ok(())
+    }
+
+    /// Marks `message_id` as failed to deliver to every target - see
+    /// `SessionManager::send_message`. Silently a no-op if the message isn't
+    /// in history, which shouldn't happen since it's only called right after
+    /// `add_message` put it there.
+    pub fn mark_delivery_failed(&mut self, message_id: Uuid) {
+        if let Some(message) = self.message_history.iter_mut().find(|message| message.id == message_id) {
+            message.delivery_status = crate::message::DeliveryStatus::Failed;
+        }
+    }
+
+    /// Adds or removes `reactor_id`'s `emoji` reaction on `message_id` - see
+    /// `/react`/`/unreact`. Unlike `apply_edit`/`apply_retraction`, any peer
+    /// may react to a message, not just its original sender.
+    pub fn apply_reaction(&mut self, reactor_id: Uuid, message_id: Uuid, emoji: String, add: bool) -> Result<()> {
+        let message = self
+            .message_history
+            .iter_mut()
+            .find(|message| message.id == message_id)
+            .ok_or_else(|| anyhow::anyhow!("Message '{}' not found in history", message_id))?;
+
+        let reactors = message.reactions.entry(emoji.clone()).or_default();
+        if add {
+            if !reactors.contains(&reactor_id) {
+                reactors.push(reactor_id);
+            }
+        } else {
+            reactors.retain(|id| *id != reactor_id);
+            if reactors.is_empty() {
+                message.reactions.remove(&emoji);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sets (or, if `draft` is empty, clears) the in-progress draft for the
+    /// conversation with `peer_id` - see `conversations`.
+    pub fn set_draft(&mut self, peer_id: &str, draft: String) {
+        if draft.is_empty() {
+            self.drafts.remove(peer_id);
+        } else {
+            self.drafts.insert(peer_id.to_string(), draft);
+        }
+    }
+
+    /// Mutes or unmutes notifications for the conversation with `peer_id` -
+    /// see `conversations`. This is separate from `/mute`'s timed snooze
+    /// (`Config::muted_until` - see `notify::is_muted`); it's a persistent
+    /// per-conversation setting.
+    pub fn set_conversation_muted(&mut self, peer_id: &str, muted: bool) {
+        self.conversation_settings.entry(peer_id.to_string()).or_default().muted = muted;
+    }
+
+    /// Groups `message_history` into one `Conversation` per other
+    /// participant - see `SessionManager::conversations`. `own_id`
+    /// distinguishes "the peer this conversation is with" from "us", since a
+    /// message we sent has our own id as `sender_id`. Messages with neither a
+    /// recognizable peer on the other end (e.g. a broadcast we sent, which
+    /// has no single `recipient_id`) aren't part of any conversation and are
+    /// dropped from this view - `get_recent_messages` still covers those.
+    /// Archived conversations are dropped too unless `include_archived` is
+    /// set - see `archive_conversation`/`/archive`.
+    pub fn conversations(&self, own_id: Uuid, include_archived: bool) -> Vec<Conversation> {
+        let mut grouped: HashMap<String, Vec<Message>> = HashMap::new();
+        for message in &self.message_history {
+            let peer_id = if message.sender_id == own_id {
+                message.recipient_id.map(|id| id.to_string())
+            } else {
+                Some(message.sender_id.to_string())
+            };
+
+            if let Some(peer_id) = peer_id
+                && (include_archived || !self.archived_peers.contains(&peer_id))
+            {
+                grouped.entry(peer_id).or_default().push(message.clone());
+            }
+        }
+
+        grouped
+            .into_iter()
+            .map(|(peer_id, history)| {
+                let unread_count = history
+                    .iter()
+                    .filter(|message| message.sender_id.to_string() == peer_id && message.read_at.is_none())
+                    .count();
+                Conversation {
+                    draft: self.drafts.get(&peer_id).cloned().unwrap_or_default(),
+                    settings: self.conversation_settings.get(&peer_id).cloned().unwrap_or_default(),
+                    peer_id,
+                    history,
+                    unread_count,
+                }
+            })
+            .collect()
+    }
 }
 
 pub struct SessionManager {
@@ -61,12 +483,37 @@ pub struct SessionManager {
     pub current_session: Arc<RwLock<Option<ChatSession>>>,
     pub message_sender: Option<mpsc::Sender<Message>>,
     pub message_receiver: Arc<RwLock<Option<mpsc::Receiver<Message>>>>,
+    /// Queues config/registry saves onto `spawn_blocking` instead of writing
+    /// inline on the runtime thread handling the chat command. Flushed in
+    /// `end_session`.
+    write_behind: WriteBehindQueue,
+    /// Messages that failed to reach a peer, retried on a timer by
+    /// `start_session` and persisted to `outbox.json` after every mutation
+    /// so a queued message survives a restart instead of only living in
+    /// memory - see `outbox::Outbox`, `restore_outbox`, and `/outbox`.
+    outbox: Arc<RwLock<Outbox>>,
+    /// Messages queued by `/schedule` for delayed delivery, sent by
+    /// `start_schedule_loop` once due and persisted to `scheduled.json` the
+    /// same way `outbox` is - see `scheduled::ScheduledQueue`.
+    schedule: Arc<RwLock<ScheduledQueue>>,
+    /// SQLite-backed message history, shared with the background tasks
+    /// spawned by `start_schedule_loop` - see `storage::MessageStore` and
+    /// `persist_message`.
+    message_store: Arc<crate::storage::MessageStore>,
+    /// Fan-out channel for `SessionEvent`s - see `subscribe`. Kept even when
+    /// no one has subscribed yet; `broadcast::Sender::send` just reports no
+    /// receivers rather than erroring.
+    events: broadcast::Sender<SessionEvent>,
 }
 
 impl SessionManager {
     pub async fn new(identity: Identity) -> Result<Self> {
         let network = NetworkManager::new(identity.clone()).await?;
         let (tx, rx) = mpsc::channel(100);
+        let storage_key = crate::crypto::CryptoEngine::derive_storage_key(&identity.get_private_key_bytes()?);
+        let message_store =
+            Arc::new(crate::storage::MessageStore::open(&Self::message_store_file()?, storage_key)?);
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
 
         Ok(SessionManager {
             identity,
@@ -74,12 +521,91 @@ impl SessionManager {
             current_session: Arc::new(RwLock::new(None)),
             message_sender: Some(tx),
             message_receiver: Arc::new(RwLock::new(Some(rx))),
+            write_behind: WriteBehindQueue::new(),
+            outbox: Arc::new(RwLock::new(Self::restore_outbox().unwrap_or_default())),
+            schedule: Arc::new(RwLock::new(Self::restore_schedule().unwrap_or_default())),
+            message_store,
+            events,
         })
     }
 
-    pub async fn start_session(&self, port: u16) -> Result<String> {
-        let session_id = format!("session_{}", chrono::Utc::now().timestamp());
-        let session = ChatSession::new(session_id.clone(), port);
+    /// Subscribes to session activity - see `events::SessionEvent`. Each
+    /// subscriber gets its own copy of every event sent after this call; the
+    /// CLI's watch mode, a future TUI, and the napi bindings can all hold one
+    /// without stepping on each other.
+    pub fn subscribe(&self) -> broadcast::Receiver<SessionEvent> {
+        self.events.subscribe()
+    }
+
+    /// Broadcasts `event` to every current subscriber. A `send` error just
+    /// means no one is currently subscribed - not a failure worth surfacing.
+    fn emit(&self, event: SessionEvent) {
+        let _ = self.events.send(event);
+    }
+
+    fn message_store_file() -> Result<std::path::PathBuf> {
+        Ok(crate::config::get_config_dir()?.join("messages.sqlite"))
+    }
+
+    /// Queues `message` to be written to the SQLite-backed history store off
+    /// the runtime thread - see `storage::MessageStore`. Called alongside
+    /// every `ChatSession::add_message`/`merge_message` so history survives
+    /// a crash, not just a graceful `end_session`.
+    fn persist_message(&self, message: Message) {
+        let store = self.message_store.clone();
+        self.write_behind.enqueue(move || store.insert_message(&message));
+    }
+
+    /// Saves a canned response template. The config rewrite happens off the
+    /// runtime via the write-behind queue; the in-process cache is updated
+    /// immediately so a `/t <name>` right afterwards still sees it.
+    pub async fn save_template(&self, name: String, content: String) -> Result<()> {
+        let mut config = crate::config::load_config_cached()?;
+        config.save_template(name, content);
+        crate::config::set_cached_config(config.clone());
+        self.write_behind
+            .enqueue(move || crate::config::save_config(&config));
+        Ok(())
+    }
+
+    /// `bind_address` overrides `Config::bind_address` for this call only
+    /// (e.g. `rus chat --bind`) - pass `None` to use whatever's configured.
+    pub async fn start_session(&self, port: u16, bind_address: Option<&str>) -> Result<String> {
+        let mut session_id = format!("session_{}", chrono::Utc::now().timestamp());
+
+        // `port` may be `0` to request an OS-assigned ephemeral port -
+        // `start_listening`'s return value is the port actually bound.
+        let bind_address = match bind_address {
+            Some(addr) => addr.to_string(),
+            None => crate::config::load_config_cached()
+                .map(|c| c.bind_address)
+                .unwrap_or_else(|_| "0.0.0.0".to_string()),
+        };
+        let bound_port = self.network.write().await.start_listening(port, &bind_address).await?;
+        let mut session = ChatSession::new(session_id.clone(), bound_port);
+
+        // Carry over the session id, archives, translation hooks and
+        // read-only state from the last graceful shutdown - see
+        // `end_session` and `restore_saved_state`. Active peer connections
+        // aren't restored directly (a `Peer`'s connection state can't just be
+        // deserialized back into existence) - `reconnect_known_peers` below
+        // re-dials them from the address book instead.
+        if let Some(saved) = Self::restore_saved_state() {
+            session.id = saved.id.clone();
+            session_id = saved.id;
+            session.archived_peers = saved.archived_peers;
+            session.translation_hooks = saved.translation_hooks;
+            session.read_only = saved.read_only;
+        }
+
+        // Message history lives in `message_store` (SQLite), not in the JSON
+        // blob above, so it survives more than just a clean shutdown - see
+        // `persist_message`. Loaded fresh on every `start_session` rather
+        // than ever being written to the JSON blob.
+        match self.message_store.load_messages() {
+            Ok(messages) => session.message_history = messages,
+            Err(e) => log::warn!("Failed to load message history: {}", e),
+        }
 
         {
             let mut current_session = self.current_session.write().await;
@@ -88,53 +614,353 @@ impl SessionManager {
 
         {
             let network = self.network.write().await;
-            network.start_listening(port).await?;
+
+            if let Some(ws_port) = crate::config::load_config_cached().ok().and_then(|c| c.websocket_port) {
+                network.start_websocket_listener(ws_port).await?;
+            }
+
+            // Best-effort, like `ReachEngine::connect_known_peers` - one bad
+            // address (typo, interface not up yet) shouldn't stop the
+            // session from starting on the interfaces that do work.
+            for bind_addr in crate::config::load_config_cached()
+                .map(|c| c.additional_listen_addresses)
+                .unwrap_or_default()
+            {
+                if let Err(e) = network.start_additional_listener(&bind_addr).await {
+                    log::warn!("Failed to listen on additional address {}: {}", bind_addr, e);
+                }
+            }
+
+            network.start_heartbeat_monitor(
+                std::time::Duration::from_secs(15),
+                chrono::Duration::seconds(45),
+            );
+        }
+
+        self.start_outbox_retry_loop();
+        self.start_schedule_loop();
+        self.start_history_prune_loop();
+        self.start_config_watch_loop();
+
+        let reconnected = self.reconnect_known_peers().await;
+        if !reconnected.is_empty() {
+            log::info!("Reconnected to {} known peer(s) on resume", reconnected.len());
         }
 
         Ok(session_id)
     }
 
+    /// Spawns a background task that retries every `Queued`/`Retrying`
+    /// `Outbox` entry on `OUTBOX_RETRY_INTERVAL`, so a message that failed
+    /// because a peer was briefly unreachable gets another shot without the
+    /// user having to notice and type `/retry` themselves - see
+    /// `outbox::Outbox::record_attempt`.
+    fn start_outbox_retry_loop(&self) {
+        let network = self.network.clone();
+        let outbox = self.outbox.clone();
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(OUTBOX_RETRY_INTERVAL);
+            loop {
+                ticker.tick().await;
+
+                let pending = outbox.read().await.pending();
+                for entry in pending {
+                    let result = network
+                        .read()
+                        .await
+                        .send_message(&entry.peer_id, &entry.content)
+                        .await
+                        .map(|_| ())
+                        .map_err(|e| e.to_string());
+                    let snapshot = {
+                        let mut outbox = outbox.write().await;
+                        outbox.record_attempt(entry.id, result);
+                        outbox.clone()
+                    };
+                    if let Err(e) = Self::write_outbox_file(&snapshot) {
+                        log::warn!("Failed to persist outbox: {}", e);
+                    }
+                }
+            }
+        });
+    }
+
+    /// Spawns a background task that runs `prune_history` on
+    /// `Config::history_prune_interval_secs`, so the retention policy in
+    /// `Config`'s `history_max_*` fields is enforced on its own even if the
+    /// user never runs `rus history prune` manually.
+    fn start_history_prune_loop(&self) {
+        let message_store = self.message_store.clone();
+
+        tokio::spawn(async move {
+            loop {
+                let interval_secs = crate::config::load_config_cached()
+                    .map(|config| config.history_prune_interval_secs)
+                    .unwrap_or(86400);
+                tokio::time::sleep(std::time::Duration::from_secs(interval_secs)).await;
+
+                match Self::run_prune(&message_store) {
+                    Ok(pruned) if pruned > 0 => log::info!("Pruned {} message(s) from history", pruned),
+                    Ok(_) => {}
+                    Err(e) => log::warn!("Failed to prune message history: {}", e),
+                }
+            }
+        });
+    }
+
+    /// Reads the `history_max_*` limits out of `Config` and applies them via
+    /// `storage::MessageStore::prune`, returning how many messages were
+    /// removed. Shared by `start_history_prune_loop` and `prune_history` so
+    /// `rus history prune` and the background task agree on what "pruning"
+    /// means.
+    fn run_prune(message_store: &crate::storage::MessageStore) -> Result<usize> {
+        let config = crate::config::load_config_cached()?;
+        message_store.prune(
+            config.history_max_messages_per_conversation,
+            config.history_max_age_days,
+            config.history_max_disk_usage_bytes,
+        )
+    }
+
+    /// Spawns a background task that polls the config file's mtime on
+    /// `CONFIG_WATCH_INTERVAL` and, when it changes, reloads it so a hand
+    /// edit to `config.toml` takes effect without restarting `rus` -
+    /// equivalent to what the wider codebase would call "the running
+    /// engine", since `ReachEngine` itself is never instantiated by any
+    /// binary.
+    ///
+    /// Reloading refreshes `config::CONFIG_CACHE` via `set_cached_config`,
+    /// which is enough on its own for `max_peers` to take effect - see the
+    /// `load_config_cached` calls in `network.rs`'s connection-accept path.
+    /// `log_level` additionally needs an explicit `logging::set_level` call,
+    /// since nothing re-reads it per use. `auto_accept_connections` is
+    /// refreshed in the cache, which is enough on its own since
+    /// `network.rs`'s connection-accept path reads it live via
+    /// `load_config_cached` on every incoming handshake - see
+    /// `NetworkManager::handle_incoming_connection`.
+    fn start_config_watch_loop(&self) {
+        let events = self.events.clone();
+
+        tokio::spawn(async move {
+            let Ok(config_file) = crate::config::get_config_file() else {
+                return;
+            };
+
+            let mut last_modified = std::fs::metadata(&config_file).and_then(|m| m.modified()).ok();
+            let mut last_config = crate::config::load_config_cached().ok();
+
+            let mut ticker = tokio::time::interval(CONFIG_WATCH_INTERVAL);
+            loop {
+                ticker.tick().await;
+
+                let modified = match std::fs::metadata(&config_file).and_then(|m| m.modified()) {
+                    Ok(modified) => modified,
+                    Err(_) => continue,
+                };
+                if last_modified == Some(modified) {
+                    continue;
+                }
+                last_modified = Some(modified);
+
+                let new_config = match crate::config::load_config() {
+                    Ok(config) => config,
+                    Err(e) => {
+                        log::warn!("Failed to reload config: {}", e);
+                        continue;
+                    }
+                };
+
+                let mut changed_fields = Vec::new();
+                if let Some(old) = &last_config {
+                    if old.log_level != new_config.log_level {
+                        changed_fields.push("log_level".to_string());
+                        if let Err(e) = crate::logging::set_level(&new_config.log_level) {
+                            log::warn!("Failed to apply reloaded log level: {}", e);
+                        }
+                    }
+                    if old.max_peers != new_config.max_peers {
+                        changed_fields.push("max_peers".to_string());
+                    }
+                    if old.auto_accept_connections != new_config.auto_accept_connections {
+                        changed_fields.push("auto_accept_connections".to_string());
+                    }
+                }
+
+                crate::config::set_cached_config(new_config.clone());
+                last_config = Some(new_config);
+
+                if !changed_fields.is_empty() {
+                    log::info!("Reloaded config ({})", changed_fields.join(", "));
+                    let _ = events.send(SessionEvent::ConfigReloaded { changed_fields });
+                }
+            }
+        });
+    }
+
+    /// Runs the retention policy immediately instead of waiting for
+    /// `start_history_prune_loop`'s next tick - see `rus history prune`.
+    pub async fn prune_history(&self) -> Result<usize> {
+        Self::run_prune(&self.message_store)
+    }
+
     pub async fn connect_to_peer(&self, address: &str) -> Result<()> {
         let network = self.network.read().await;
-        network.connect_to_peer(address).await?;
+
+        let peer = if address.starts_with("ws://") {
+            network.connect_via_websocket(address).await?
+        } else {
+            let relay_address = crate::config::load_config_cached()
+                .ok()
+                .and_then(|config| config.relay_address);
+
+            // A `user@domain` handle is resolved to a host:port via the
+            // domain's `_rustalk._tcp` SRV record instead of being dialed
+            // literally - see `dnscontact::resolve_contact`. The TXT-record
+            // public key it returns isn't pinned against the handshake yet;
+            // it's there for a future verification step.
+            let dial_address = if address.contains('@') {
+                crate::dnscontact::resolve_contact(address).await?.address
+            } else {
+                address.to_string()
+            };
+
+            network
+                .connect_to_peer_with_relay_fallback(&dial_address, relay_address.as_deref())
+                .await?
+        };
+
+        // Remember this peer so a future session can reconnect without
+        // retyping the address - see `addressbook::AddressBook`.
+        if let Ok(address_book) = crate::addressbook::AddressBook::new()
+            && let Err(e) = address_book.remember(&peer.id.to_string(), &peer.public_key, &peer.display_name, address)
+        {
+            log::warn!("Failed to update address book for peer {}: {}", peer.id, e);
+        }
 
         // Add peer to current session
         if let Some(session) = self.current_session.write().await.as_mut() {
-            let peer_addr: SocketAddr = address.parse()?;
-            let peer = Peer::new(
-                uuid::Uuid::new_v4(),
-                format!("unknown@{}", address),
-                "Unknown".to_string(),
-                peer_addr,
-                "unknown_key".to_string(),
-            );
-            session.add_peer(peer);
+            session.add_peer(peer.clone());
         }
+        self.emit(SessionEvent::PeerConnected(Box::new(peer)));
 
         Ok(())
     }
 
-    pub async fn send_message(&self, content: String, target_peer: Option<String>) -> Result<()> {
-        let recipient_id = if let Some(_peer_name) = target_peer {
-            // In a real implementation, you'd look up the peer ID by name
-            // For now, just use None for broadcast
-            None
-        } else {
-            None
+    /// Disconnects from `peer_id` and drops it from the current session,
+    /// emitting `SessionEvent::PeerDisconnected` - see `subscribe`.
+    pub async fn remove_peer(&self, peer_id: &str) -> Result<()> {
+        let removed = self
+            .current_session
+            .write()
+            .await
+            .as_mut()
+            .and_then(|session| session.remove_peer(peer_id));
+
+        if removed.is_some() {
+            self.emit(SessionEvent::PeerDisconnected(peer_id.to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Lists inbound connections held by `NetworkManager` pending `/accept`
+    /// or `/reject` - see `Config::auto_accept_connections`.
+    pub async fn pending_peers(&self) -> Vec<Peer> {
+        self.network.read().await.list_pending().await
+    }
+
+    /// Approves a connection held pending approval, adding it to the current
+    /// session and emitting `SessionEvent::PeerConnected` just like an
+    /// outbound `connect_to_peer` - see `/accept`.
+    pub async fn accept_peer(&self, peer_id: &str) -> Result<()> {
+        let peer_id = Uuid::parse_str(peer_id).map_err(|e| anyhow::anyhow!("Invalid peer id: {}", e))?;
+        let peer = self.network.read().await.accept_pending(peer_id).await?;
+
+        if let Some(session) = self.current_session.write().await.as_mut() {
+            session.add_peer(peer.clone());
+        }
+        self.emit(SessionEvent::PeerConnected(Box::new(peer)));
+
+        Ok(())
+    }
+
+    /// Declines a connection held pending approval - see `/reject`.
+    pub async fn reject_peer(&self, peer_id: &str) -> Result<()> {
+        let peer_id = Uuid::parse_str(peer_id).map_err(|e| anyhow::anyhow!("Invalid peer id: {}", e))?;
+        self.network.read().await.reject_pending(peer_id).await
+    }
+
+    /// Re-dials every peer in the local `AddressBook` using its most recently
+    /// used address, so a resumed session comes back with the same peers
+    /// connected rather than starting empty - called automatically from
+    /// `start_session`. This codebase has no concept of "room membership" to
+    /// restore (see `ChatSession::topic`); the address book's known peers are
+    /// the closest real equivalent, and are what this actually reconnects.
+    ///
+    /// Best-effort like `ReachEngine::connect_known_peers`: a peer that's
+    /// offline or has moved is logged and skipped rather than aborting the
+    /// rest. Returns the addresses successfully reconnected.
+    async fn reconnect_known_peers(&self) -> Vec<String> {
+        let known_peers = match crate::addressbook::AddressBook::new().and_then(|book| book.list()) {
+            Ok(known_peers) => known_peers,
+            Err(e) => {
+                log::warn!("Failed to load address book for reconnect: {}", e);
+                return Vec::new();
+            }
         };
 
+        let mut reconnected = Vec::new();
+        for known_peer in known_peers {
+            let Some(address) = known_peer.last_addresses.last() else {
+                continue;
+            };
+
+            match self.connect_to_peer(address).await {
+                Ok(()) => reconnected.push(address.clone()),
+                Err(e) => log::warn!(
+                    "Failed to reconnect to known peer {} ({}): {}",
+                    known_peer.nickname,
+                    address,
+                    e
+                ),
+            }
+        }
+
+        reconnected
+    }
+
+    pub async fn send_message(&self, content: String, target_peer: Option<String>) -> Result<()> {
+        if self
+            .current_session
+            .read()
+            .await
+            .as_ref()
+            .is_some_and(|session| session.read_only)
+        {
+            return Err(anyhow::anyhow!(
+                "This conversation is read-only - use /readonly off to send messages"
+            ));
+        }
+
+        let active_peers = self.get_active_peers().await;
+        let targets = Self::resolve_targets(&active_peers, &target_peer)?;
+
         let message = Message::new(
             self.identity.user_id,
-            recipient_id,
+            None,
             MessageType::Text,
-            content,
+            content.clone(),
             self.identity.get_display_name(),
         );
 
+        let message_id = message.id;
+
         // Add to session history
         if let Some(session) = self.current_session.write().await.as_mut() {
             session.add_message(message.clone());
         }
+        self.persist_message(message.clone());
 
         // Send through message channel
         if let Some(sender) = &self.message_sender {
@@ -144,59 +970,1424 @@ impl SessionManager {
                 .map_err(|e| anyhow::anyhow!("Failed to send message: {}", e))?;
         }
 
+        // Deliver to each target over the network, queuing anything that
+        // fails into the outbox instead of silently dropping it - see
+        // `outbox::Outbox` and `/outbox`. A queued failure still gets another
+        // shot via the retry loop, but the caller shouldn't be told the send
+        // succeeded when every target rejected it outright.
+        let target_count = targets.len();
+        let mut failures = Vec::new();
+        let network = self.network.read().await;
+        for peer_id in targets {
+            if let Err(e) = network.send_message(&peer_id, &content).await {
+                let mut outbox = self.outbox.write().await;
+                outbox.enqueue(peer_id.clone(), content.clone(), e.to_string());
+                self.persist_outbox(outbox.clone());
+                failures.push(format!("{}: {}", peer_id, e));
+            }
+        }
+
+        if target_count > 0 && failures.len() == target_count {
+            if let Some(session) = self.current_session.write().await.as_mut() {
+                session.mark_delivery_failed(message_id);
+            }
+            return Err(anyhow::anyhow!(
+                "Failed to deliver to any peer (queued for retry): {}",
+                failures.join("; ")
+            ));
+        }
+
         Ok(())
     }
 
-    pub async fn get_active_peers(&self) -> Vec<Peer> {
-        if let Some(session) = self.current_session.read().await.as_ref() {
-            session.active_peers.values().cloned().collect()
-        } else {
-            Vec::new()
+    /// Sends markdown source the same way `send_message` sends plain text -
+    /// see `/md`. Delivery failures are only logged rather than queued into
+    /// the outbox: `outbox::Outbox`'s retry loop always resends via
+    /// `NetworkManager::send_message` (plain `Text`), so queuing a markdown
+    /// send there would silently change what eventually gets delivered.
+    pub async fn send_markdown(&self, content: String, target_peer: Option<String>) -> Result<()> {
+        if self
+            .current_session
+            .read()
+            .await
+            .as_ref()
+            .is_some_and(|session| session.read_only)
+        {
+            return Err(anyhow::anyhow!(
+                "This conversation is read-only - use /readonly off to send messages"
+            ));
         }
-    }
 
-    pub async fn get_session_info(&self) -> Option<(String, u16, usize)> {
-        if let Some(session) = self.current_session.read().await.as_ref() {
-            Some((
-                session.id.clone(),
-                session.current_port,
-                session.active_peers.len(),
-            ))
-        } else {
-            None
+        let active_peers = self.get_active_peers().await;
+        let targets = Self::resolve_targets(&active_peers, &target_peer)?;
+
+        let message = Message::new(
+            self.identity.user_id,
+            None,
+            MessageType::Markdown,
+            content.clone(),
+            self.identity.get_display_name(),
+        );
+        if let Some(session) = self.current_session.write().await.as_mut() {
+            session.add_message(message.clone());
+        }
+        self.persist_message(message);
+
+        let network = self.network.read().await;
+        for peer_id in targets {
+            if let Err(e) = network.send_markdown(&peer_id, content.clone()).await {
+                log::warn!("Failed to send markdown to {}: {}", peer_id, e);
+            }
         }
+
+        Ok(())
     }
 
-    pub async fn end_session(&self) -> Result<()> {
+    /// Sends a code snippet the same way `send_markdown` sends markdown - see
+    /// `/code`. Same outbox caveat as `send_markdown`.
+    pub async fn send_code(&self, lang: String, text: String, target_peer: Option<String>) -> Result<()> {
+        if self
+            .current_session
+            .read()
+            .await
+            .as_ref()
+            .is_some_and(|session| session.read_only)
         {
-            let mut current_session = self.current_session.write().await;
-            *current_session = None;
+            return Err(anyhow::anyhow!(
+                "This conversation is read-only - use /readonly off to send messages"
+            ));
+        }
+
+        let active_peers = self.get_active_peers().await;
+        let targets = Self::resolve_targets(&active_peers, &target_peer)?;
+
+        let payload = crate::message::CodePayload { lang, text };
+        let content = serde_json::to_string(&payload).unwrap_or_default();
+        let message = Message::new(
+            self.identity.user_id,
+            None,
+            MessageType::Code,
+            content,
+            self.identity.get_display_name(),
+        );
+        if let Some(session) = self.current_session.write().await.as_mut() {
+            session.add_message(message.clone());
         }
+        self.persist_message(message);
 
         let network = self.network.read().await;
-        network.stop_listening().await?;
+        for peer_id in targets {
+            if let Err(e) = network.send_code(&peer_id, payload.lang.clone(), payload.text.clone()).await {
+                log::warn!("Failed to send code snippet to {}: {}", peer_id, e);
+            }
+        }
 
         Ok(())
     }
 
-    pub async fn list_recent_messages(&self, limit: usize) -> Vec<Message> {
-        if let Some(session) = self.current_session.read().await.as_ref() {
-            let recent: Vec<Message> = session
-                .get_recent_messages(limit)
-                .into_iter()
-                .cloned()
-                .collect();
-            recent
-        } else {
-            Vec::new()
+    /// Sends the image at `path` to `target_peer` - see `/image`. Read
+    /// into memory and sent inline as one message rather than through the
+    /// `file_transfer` accept/reject flow, so this is only suitable for
+    /// images small enough that doing so is reasonable. Unlike
+    /// `send_message`/`send_markdown`/`send_code`, this always targets
+    /// exactly one peer - there's no broadcast-an-image convention here.
+    pub async fn send_image(&self, target_peer: String, path: &std::path::Path) -> Result<()> {
+        if self
+            .current_session
+            .read()
+            .await
+            .as_ref()
+            .is_some_and(|session| session.read_only)
+        {
+            return Err(anyhow::anyhow!(
+                "This conversation is read-only - use /readonly off to send messages"
+            ));
+        }
+
+        let active_peers = self.get_active_peers().await;
+        let targets = Self::resolve_targets(&active_peers, &Some(target_peer))?;
+        let peer_id = targets.into_iter().next().ok_or_else(|| anyhow::anyhow!("Peer not found"))?;
+        let peer_uuid = Uuid::parse_str(&peer_id)?;
+
+        let data = std::fs::read(path)?;
+        let mime = guess_mime(path);
+        let filename = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "image".to_string());
+        let attachment = crate::file_transfer::Attachment::new(filename.clone(), mime.clone(), &data);
+        let payload = crate::message::ImagePayload {
+            attachment,
+            data: base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &data),
+        };
+        let message = Message::image_message(self.identity.user_id, peer_uuid, self.identity.get_display_name(), &payload);
+        if let Some(session) = self.current_session.write().await.as_mut() {
+            session.add_message(message.clone());
         }
+        self.persist_message(message);
+
+        self.network.read().await.send_image(&peer_id, filename, mime, data).await
     }
 
-    pub async fn get_peer_count(&self) -> usize {
-        if let Some(session) = self.current_session.read().await.as_ref() {
-            session.active_peers.len()
-        } else {
-            0
+    /// Re-sends `message_id` from history to `target_peer`, keeping its
+    /// original `message_type`/`content` but stamping `forwarded_from` with
+    /// who actually sent it and when - see `/forward`. Same single-target
+    /// restriction as `send_image`: there's no "forward to everyone" convention.
+    pub async fn forward_message(&self, message_id: Uuid, target_peer: String) -> Result<()> {
+        if self
+            .current_session
+            .read()
+            .await
+            .as_ref()
+            .is_some_and(|session| session.read_only)
+        {
+            return Err(anyhow::anyhow!(
+                "This conversation is read-only - use /readonly off to send messages"
+            ));
         }
+
+        let active_peers = self.get_active_peers().await;
+        let targets = Self::resolve_targets(&active_peers, &Some(target_peer))?;
+        let peer_id = targets.into_iter().next().ok_or_else(|| anyhow::anyhow!("Peer not found"))?;
+        let peer_uuid = Uuid::parse_str(&peer_id)?;
+
+        let original = self
+            .current_session
+            .read()
+            .await
+            .as_ref()
+            .and_then(|session| session.get_message(message_id).cloned())
+            .ok_or_else(|| anyhow::anyhow!("Message '{}' not found in history", message_id))?;
+
+        let mut message = Message::new(
+            self.identity.user_id,
+            Some(peer_uuid),
+            original.message_type,
+            original.content.clone(),
+            self.identity.get_display_name(),
+        );
+        message.forwarded_from = Some(crate::message::ForwardedFrom {
+            sender_id: original.sender_id,
+            sender_name: original.sender_name.clone(),
+            timestamp: original.timestamp,
+        });
+
+        if let Some(session) = self.current_session.write().await.as_mut() {
+            session.add_message(message.clone());
+        }
+        self.persist_message(message.clone());
+
+        self.network.read().await.send_forwarded_message(&peer_id, message).await
+    }
+
+    /// Sends `content` as one `MessageType::Broadcast` to every connected
+    /// peer via `NetworkManager::broadcast`, rather than the plain-text chat
+    /// path's one-send-per-target loop - see `/all`. Returns the number of
+    /// peers it actually reached; per-peer failures are queued in the
+    /// outbox, same as `send_message`.
+    pub async fn broadcast_message(&self, content: String) -> Result<usize> {
+        if self
+            .current_session
+            .read()
+            .await
+            .as_ref()
+            .is_some_and(|session| session.read_only)
+        {
+            return Err(anyhow::anyhow!(
+                "This conversation is read-only - use /readonly off to send messages"
+            ));
+        }
+
+        let message = Message::broadcast_message(
+            self.identity.user_id,
+            self.identity.get_display_name(),
+            content.clone(),
+        );
+        if let Some(session) = self.current_session.write().await.as_mut() {
+            session.add_message(message.clone());
+        }
+        self.persist_message(message);
+
+        let outcomes = self.network.read().await.broadcast(&content).await;
+        let delivered = outcomes.iter().filter(|(_, result)| result.is_ok()).count();
+        for (peer_id, result) in outcomes {
+            if let Err(e) = result {
+                let mut outbox = self.outbox.write().await;
+                outbox.enqueue(peer_id, content.clone(), e);
+                self.persist_outbox(outbox.clone());
+            }
+        }
+
+        Ok(delivered)
+    }
+
+    /// Messages currently stuck in the outbox - see `/outbox`.
+    pub async fn list_outbox(&self) -> Vec<OutboxEntry> {
+        self.outbox.read().await.list().to_vec()
+    }
+
+    /// Re-attempts a specific outbox entry immediately instead of waiting
+    /// for the next automatic retry tick - see `/retry`.
+    pub async fn retry_outbox_entry(&self, id: Uuid) -> Result<()> {
+        let entry = self
+            .outbox
+            .read()
+            .await
+            .get(id)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("No outbox entry with id {}", id))?;
+
+        let result = self
+            .network
+            .read()
+            .await
+            .send_message(&entry.peer_id, &entry.content)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string());
+
+        let mut outbox = self.outbox.write().await;
+        outbox.record_attempt(id, result);
+        self.persist_outbox(outbox.clone());
+        Ok(())
+    }
+
+    /// Drops an outbox entry without retrying it again - see `/discard`.
+    pub async fn discard_outbox_entry(&self, id: Uuid) -> Result<()> {
+        let mut outbox = self.outbox.write().await;
+        if outbox.discard(id) {
+            self.persist_outbox(outbox.clone());
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("No outbox entry with id {}", id))
+        }
+    }
+
+    /// Sends many (peer, content) pairs concurrently instead of round-tripping
+    /// through `network.send_message` one at a time, pipelining encryption and
+    /// the write for each connection - useful for bots/bridges relaying
+    /// bursts of messages. Results are returned in the same order as `messages`.
+    ///
+    /// Note: unlike `send_message` above, this goes straight through
+    /// `NetworkManager` and is not yet reflected in session history - see
+    /// the "make SessionManager actually deliver over network" backlog item.
+    pub async fn send_batch(&self, messages: Vec<(String, String)>) -> Vec<Result<String>> {
+        let network = self.network.read().await;
+        let sends = messages
+            .iter()
+            .map(|(peer_id, content)| network.send_message(peer_id, content));
+        futures::future::join_all(sends).await
+    }
+
+    /// Inserts a message delivered by a reconnect catch-up (e.g. a flushed
+    /// offline queue) into history at its original send time. A
+    /// `MessageType::Topic` updates `ChatSession::topic` instead of being
+    /// added to history, and `MessageType::ReadReceipt`/`MessageType::Edit`/
+    /// `MessageType::Retract`/`MessageType::Reaction` are applied to the
+    /// messages they reference instead of being added to history themselves -
+    /// none of these is a chat message of its own. `MessageType::SenderKey`
+    /// is handed to `NetworkManager` and dropped rather than merged, and
+    /// `MessageType::Broadcast` is decrypted with the sender's key (via
+    /// `NetworkManager::decrypt_broadcast`) before it's added to history -
+    /// see `NetworkManager::broadcast`'s sender-key encryption.
+    pub async fn merge_message(&self, mut message: Message) -> Result<()> {
+        if matches!(message.message_type, MessageType::SenderKey) {
+            return match base64::Engine::decode(&base64::engine::general_purpose::STANDARD, &message.content)
+                .ok()
+                .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            {
+                Some(key) => {
+                    self.network.read().await.receive_sender_key(message.sender_id, key).await;
+                    Ok(())
+                }
+                None => {
+                    log::warn!("Discarding malformed SenderKey from {}", message.sender_id);
+                    Ok(())
+                }
+            };
+        }
+
+        if matches!(message.message_type, MessageType::Broadcast) {
+            match self.network.read().await.decrypt_broadcast(message.sender_id, &message.content).await {
+                Ok(plaintext) => message.content = plaintext,
+                Err(e) => {
+                    log::warn!("Discarding undecryptable Broadcast from {}: {}", message.sender_id, e);
+                    return Ok(());
+                }
+            }
+        }
+
+        if let Some(session) = self.current_session.write().await.as_mut() {
+            if matches!(message.message_type, MessageType::Topic) {
+                session.topic = Some(message.content);
+            } else if matches!(message.message_type, MessageType::ReadReceipt) {
+                if let Ok(payload) = serde_json::from_str::<crate::message::ReadReceiptPayload>(&message.content) {
+                    session.apply_read_receipt(&payload.message_ids, payload.read_at);
+                    for message_id in payload.message_ids {
+                        self.emit(SessionEvent::DeliveryUpdated { message_id, read_at: payload.read_at });
+                    }
+                } else {
+                    log::warn!("Discarding malformed ReadReceipt from {}", message.sender_id);
+                }
+            } else if matches!(message.message_type, MessageType::DeliveryAck) {
+                match Uuid::parse_str(&message.content) {
+                    Ok(message_id) => {
+                        if session.apply_delivery_ack(message_id).is_ok() {
+                            self.emit(SessionEvent::MessageDelivered { message_id });
+                        }
+                    }
+                    Err(_) => log::warn!("Discarding malformed DeliveryAck from {}", message.sender_id),
+                }
+            } else if matches!(message.message_type, MessageType::Edit) {
+                match serde_json::from_str::<crate::message::EditPayload>(&message.content) {
+                    Ok(payload) => {
+                        if let Err(e) = session.apply_edit(message.sender_id, payload.message_id, payload.new_content) {
+                            log::warn!("Discarding Edit from {}: {}", message.sender_id, e);
+                        }
+                    }
+                    Err(_) => log::warn!("Discarding malformed Edit from {}", message.sender_id),
+                }
+            } else if matches!(message.message_type, MessageType::Retract) {
+                match Uuid::parse_str(&message.content) {
+                    Ok(message_id) => {
+                        if let Err(e) = session.apply_retraction(message.sender_id, message_id) {
+                            log::warn!("Discarding Retract from {}: {}", message.sender_id, e);
+                        }
+                    }
+                    Err(_) => log::warn!("Discarding malformed Retract from {}", message.sender_id),
+                }
+            } else if matches!(message.message_type, MessageType::Reaction) {
+                match serde_json::from_str::<crate::message::ReactionPayload>(&message.content) {
+                    Ok(payload) => {
+                        if let Err(e) =
+                            session.apply_reaction(message.sender_id, payload.message_id, payload.emoji, payload.add)
+                        {
+                            log::warn!("Discarding Reaction from {}: {}", message.sender_id, e);
+                        }
+                    }
+                    Err(_) => log::warn!("Discarding malformed Reaction from {}", message.sender_id),
+                }
+            } else {
+                self.persist_message(message.clone());
+                session.merge_message(message.clone());
+                self.emit(SessionEvent::MessageReceived(Box::new(message)));
+            }
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("No active session"))
+        }
+    }
+
+    /// Sets this session's topic locally and announces it to every connected
+    /// peer via `NetworkManager::broadcast_topic` - see `/topic`. Returns the
+    /// topic back for display; per-peer delivery failures are queued in the
+    /// outbox, same as `send_message`.
+    pub async fn set_topic(&self, topic: String) -> Result<String> {
+        if let Some(session) = self.current_session.write().await.as_mut() {
+            session.topic = Some(topic.clone());
+        } else {
+            return Err(anyhow::anyhow!("No active session"));
+        }
+
+        let outcomes = self.network.read().await.broadcast_topic(&topic).await;
+        for (peer_id, result) in outcomes {
+            if let Err(e) = result {
+                let mut outbox = self.outbox.write().await;
+                outbox.enqueue(peer_id, topic.clone(), e);
+                self.persist_outbox(outbox.clone());
+            }
+        }
+
+        Ok(topic)
+    }
+
+    /// This session's current topic, if one has been set - see `/topic`.
+    pub async fn get_topic(&self) -> Option<String> {
+        self.current_session
+            .read()
+            .await
+            .as_ref()
+            .and_then(|session| session.topic.clone())
+    }
+
+    /// Sends a low-latency typing notice to `peer_id` over UDP - see
+    /// `NetworkManager::send_ephemeral` and `/typing`. Not recorded in
+    /// message history; a dropped notice isn't worth retrying past what
+    /// `send_ephemeral` already does internally.
+    pub async fn send_typing_indicator(&self, peer_id: String) -> Result<()> {
+        self.network
+            .read()
+            .await
+            .send_ephemeral(&peer_id, MessageType::Typing, "")
+            .await
+    }
+
+    /// Offers `path` to `peer_id` - see `NetworkManager::offer_file` and `/file send`.
+    pub async fn offer_file(&self, peer_id: &str, path: &std::path::Path) -> Result<Uuid> {
+        self.network.read().await.offer_file(peer_id, path).await
+    }
+
+    /// Accepts a pending offer and starts writing it to `dest_path` - see
+    /// `NetworkManager::accept_file` and `/file accept`.
+    pub async fn accept_file(&self, transfer_id: Uuid, dest_path: &std::path::Path) -> Result<std::path::PathBuf> {
+        self.network.read().await.accept_file(transfer_id, dest_path).await
+    }
+
+    /// Declines a pending offer - see `NetworkManager::reject_file` and `/file reject`.
+    pub async fn reject_file(&self, transfer_id: Uuid) -> Result<()> {
+        self.network.read().await.reject_file(transfer_id).await
+    }
+
+    /// Marks every unread message from `peer_id` as read locally, and - only
+    /// if `Config::read_receipts_enabled` - announces it to them via
+    /// `NetworkManager::send_read_receipt`. Returns how many messages were
+    /// newly marked, for `/read` to report back. A failed announcement isn't
+    /// queued in the outbox like a chat message would be: a receipt that
+    /// never lands just means the peer doesn't see "read at", not a dropped
+    /// conversation.
+    pub async fn mark_conversation_read(&self, peer_id: &str) -> Result<usize> {
+        let message_ids = if let Some(session) = self.current_session.write().await.as_mut() {
+            session.mark_conversation_read(peer_id)
+        } else {
+            return Err(anyhow::anyhow!("No active session"));
+        };
+
+        if message_ids.is_empty() {
+            return Ok(0);
+        }
+
+        let read_receipts_enabled = crate::config::load_config_cached()
+            .map(|c| c.read_receipts_enabled)
+            .unwrap_or(false);
+        if read_receipts_enabled
+            && let Err(e) = self.network.read().await.send_read_receipt(peer_id, message_ids.clone()).await
+        {
+            log::warn!("Failed to send read receipt to {}: {}", peer_id, e);
+        }
+
+        Ok(message_ids.len())
+    }
+
+    /// Edits `message_id`'s text locally and announces the edit to whoever
+    /// it was originally sent to - every active peer if it was a broadcast
+    /// (`recipient_id: None`), just the one peer otherwise. Only the message's
+    /// original sender can edit it - see `ChatSession::apply_edit`.
+    pub async fn edit_message(&self, message_id: Uuid, new_content: String) -> Result<()> {
+        let recipient_id = {
+            let mut current_session = self.current_session.write().await;
+            let session = current_session
+                .as_mut()
+                .ok_or_else(|| anyhow::anyhow!("No active session"))?;
+            let recipient_id = session
+                .message_history
+                .iter()
+                .find(|message| message.id == message_id)
+                .ok_or_else(|| anyhow::anyhow!("Message '{}' not found in history", message_id))?
+                .recipient_id;
+            session.apply_edit(self.identity.user_id, message_id, new_content.clone())?;
+            recipient_id
+        };
+
+        let targets: Vec<String> = match recipient_id {
+            Some(peer_id) => vec![peer_id.to_string()],
+            None => self
+                .get_active_peers()
+                .await
+                .iter()
+                .map(|peer| peer.id.to_string())
+                .collect(),
+        };
+
+        let network = self.network.read().await;
+        for peer_id in targets {
+            if let Err(e) = network.send_edit(&peer_id, message_id, new_content.clone()).await {
+                log::warn!("Failed to announce edit of {} to {}: {}", message_id, peer_id, e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Tombstones `message_id` locally and announces the retraction to
+    /// whoever it was originally sent to - same targeting and sender-only
+    /// restriction as `edit_message`.
+    pub async fn retract_message(&self, message_id: Uuid) -> Result<()> {
+        let recipient_id = {
+            let mut current_session = self.current_session.write().await;
+            let session = current_session
+                .as_mut()
+                .ok_or_else(|| anyhow::anyhow!("No active session"))?;
+            let recipient_id = session
+                .message_history
+                .iter()
+                .find(|message| message.id == message_id)
+                .ok_or_else(|| anyhow::anyhow!("Message '{}' not found in history", message_id))?
+                .recipient_id;
+            session.apply_retraction(self.identity.user_id, message_id)?;
+            recipient_id
+        };
+
+        let targets: Vec<String> = match recipient_id {
+            Some(peer_id) => vec![peer_id.to_string()],
+            None => self
+                .get_active_peers()
+                .await
+                .iter()
+                .map(|peer| peer.id.to_string())
+                .collect(),
+        };
+
+        let network = self.network.read().await;
+        for peer_id in targets {
+            if let Err(e) = network.send_retraction(&peer_id, message_id).await {
+                log::warn!("Failed to announce retraction of {} to {}: {}", message_id, peer_id, e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds or removes our `emoji` reaction on `message_id` locally, then
+    /// announces it to whoever the message was originally sent to - same
+    /// targeting as `edit_message`, but with no sender-only restriction since
+    /// any peer may react.
+    async fn set_reaction(&self, message_id: Uuid, emoji: String, add: bool) -> Result<()> {
+        let recipient_id = {
+            let mut current_session = self.current_session.write().await;
+            let session = current_session
+                .as_mut()
+                .ok_or_else(|| anyhow::anyhow!("No active session"))?;
+            let recipient_id = session
+                .message_history
+                .iter()
+                .find(|message| message.id == message_id)
+                .ok_or_else(|| anyhow::anyhow!("Message '{}' not found in history", message_id))?
+                .recipient_id;
+            session.apply_reaction(self.identity.user_id, message_id, emoji.clone(), add)?;
+            recipient_id
+        };
+
+        let targets: Vec<String> = match recipient_id {
+            Some(peer_id) => vec![peer_id.to_string()],
+            None => self
+                .get_active_peers()
+                .await
+                .iter()
+                .map(|peer| peer.id.to_string())
+                .collect(),
+        };
+
+        let network = self.network.read().await;
+        for peer_id in targets {
+            if let Err(e) = network.send_reaction(&peer_id, message_id, emoji.clone(), add).await {
+                log::warn!("Failed to announce reaction on {} to {}: {}", message_id, peer_id, e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds our `emoji` reaction to `message_id` - see `/react`.
+    pub async fn add_reaction(&self, message_id: Uuid, emoji: String) -> Result<()> {
+        self.set_reaction(message_id, emoji, true).await
+    }
+
+    /// Removes our `emoji` reaction from `message_id` - see `/unreact`.
+    pub async fn remove_reaction(&self, message_id: Uuid, emoji: String) -> Result<()> {
+        self.set_reaction(message_id, emoji, false).await
+    }
+
+    /// Resolves `target_peer` (matched by id or display name, since chat
+    /// input gives a human a name to type, not a UUID) against `active_peers`
+    /// into the peer ids to send to - `None` targets every active peer. Used
+    /// by both `send_message` and `start_schedule_loop`, which re-resolves a
+    /// `/schedule`d message's target at delivery time rather than pinning it
+    /// to peer ids up front.
+    fn resolve_targets(active_peers: &[Peer], target_peer: &Option<String>) -> Result<Vec<String>> {
+        let targets: Vec<String> = match target_peer {
+            Some(peer_ref) => active_peers
+                .iter()
+                .filter(|peer| &peer.id.to_string() == peer_ref || &peer.display_name == peer_ref)
+                .map(|peer| peer.id.to_string())
+                .collect(),
+            None => active_peers.iter().map(|peer| peer.id.to_string()).collect(),
+        };
+        if let Some(peer_ref) = target_peer
+            && targets.is_empty()
+        {
+            return Err(anyhow::anyhow!("Peer '{}' not found", peer_ref));
+        }
+        Ok(targets)
+    }
+
+    /// Queues `content` for delivery at `deliver_at` instead of sending it
+    /// now - see `/schedule`. `target_peer` is matched the same way
+    /// `send_message` does, but re-resolved by `start_schedule_loop` at
+    /// delivery time rather than now, since the peer may not even be
+    /// connected yet.
+    pub async fn schedule_message(
+        &self,
+        content: String,
+        target_peer: Option<String>,
+        deliver_at: chrono::DateTime<chrono::Utc>,
+    ) -> Uuid {
+        let mut schedule = self.schedule.write().await;
+        let id = schedule.schedule(target_peer, content, deliver_at);
+        self.persist_schedule(schedule.clone());
+        id
+    }
+
+    /// Messages waiting to be sent by `/schedule` - see `/scheduled`.
+    pub async fn list_schedule(&self) -> Vec<ScheduledMessage> {
+        self.schedule.read().await.list().to_vec()
+    }
+
+    /// Cancels a pending scheduled message before it's sent - see `/unschedule`.
+    pub async fn cancel_schedule(&self, id: Uuid) -> Result<()> {
+        let mut schedule = self.schedule.write().await;
+        if schedule.cancel(id) {
+            self.persist_schedule(schedule.clone());
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("Scheduled message '{}' not found", id))
+        }
+    }
+
+    /// Spawns a background task that checks every `SCHEDULE_CHECK_INTERVAL`
+    /// for `/schedule`d messages whose `deliver_at` has passed and sends
+    /// them, the same way `send_message`/`broadcast_message` would - a
+    /// delivery failure is queued into the outbox like any other send
+    /// rather than silently dropped.
+    fn start_schedule_loop(&self) {
+        let network = self.network.clone();
+        let current_session = self.current_session.clone();
+        let outbox = self.outbox.clone();
+        let schedule = self.schedule.clone();
+        let identity = self.identity.clone();
+        let message_store = self.message_store.clone();
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(SCHEDULE_CHECK_INTERVAL);
+            loop {
+                ticker.tick().await;
+
+                let due = {
+                    let mut schedule = schedule.write().await;
+                    let due = schedule.take_due(chrono::Utc::now());
+                    if !due.is_empty()
+                        && let Err(e) = Self::write_schedule_file(&schedule)
+                    {
+                        log::warn!("Failed to persist schedule: {}", e);
+                    }
+                    due
+                };
+
+                for entry in due {
+                    let active_peers: Vec<Peer> = current_session
+                        .read()
+                        .await
+                        .as_ref()
+                        .map(|session| session.get_peers().into_iter().cloned().collect())
+                        .unwrap_or_default();
+
+                    let targets = match Self::resolve_targets(&active_peers, &entry.target_peer) {
+                        Ok(targets) => targets,
+                        Err(e) => {
+                            log::warn!("Dropping scheduled message {}: {}", entry.id, e);
+                            continue;
+                        }
+                    };
+
+                    let message = Message::new(
+                        identity.user_id,
+                        None,
+                        MessageType::Text,
+                        entry.content.clone(),
+                        identity.get_display_name(),
+                    );
+                    if let Some(session) = current_session.write().await.as_mut() {
+                        session.add_message(message.clone());
+                    }
+                    if let Err(e) = message_store.insert_message(&message) {
+                        log::warn!("Failed to persist scheduled message: {}", e);
+                    }
+
+                    let network = network.read().await;
+                    for peer_id in targets {
+                        if let Err(e) = network.send_message(&peer_id, &entry.content).await {
+                            let snapshot = {
+                                let mut outbox = outbox.write().await;
+                                outbox.enqueue(peer_id, entry.content.clone(), e.to_string());
+                                outbox.clone()
+                            };
+                            if let Err(e) = Self::write_outbox_file(&snapshot) {
+                                log::warn!("Failed to persist outbox: {}", e);
+                            }
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    pub async fn get_active_peers(&self) -> Vec<Peer> {
+        if let Some(session) = self.current_session.read().await.as_ref() {
+            session.get_peers().into_iter().cloned().collect()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Looks up the roster label for `peer_id`, if the user has added one -
+    /// see `contacts::ContactBook`. `None` means fall back to whatever the
+    /// peer itself advertised as `Peer::display_name`; a contact entry exists
+    /// purely to let the local user override that.
+    pub fn contact_label(&self, peer_id: &str) -> Option<String> {
+        crate::contacts::ContactBook::new().ok()?.get(peer_id).ok()?.map(|contact| contact.display_name)
+    }
+
+    pub async fn get_archived_peers(&self) -> Vec<Peer> {
+        if let Some(session) = self.current_session.read().await.as_ref() {
+            session.get_archived_peers().into_iter().cloned().collect()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Hides a conversation from the active sidebar without deleting its history.
+    pub async fn archive_conversation(&self, peer_id: &str) -> Result<()> {
+        if let Some(session) = self.current_session.write().await.as_mut() {
+            session.archive_conversation(peer_id)
+        } else {
+            Err(anyhow::anyhow!("No active session"))
+        }
+    }
+
+    /// Restores a conversation archived via `archive_conversation` - see `/unarchive`.
+    pub async fn unarchive_conversation(&self, peer_id: &str) -> Result<()> {
+        if let Some(session) = self.current_session.write().await.as_mut() {
+            session.unarchive_conversation(peer_id);
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("No active session"))
+        }
+    }
+
+    /// Marks the current conversation read-only, or lifts that - see `/readonly`.
+    pub async fn set_read_only(&self, read_only: bool) -> Result<()> {
+        if let Some(session) = self.current_session.write().await.as_mut() {
+            session.set_read_only(read_only);
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("No active session"))
+        }
+    }
+
+    pub async fn is_read_only(&self) -> bool {
+        self.current_session
+            .read()
+            .await
+            .as_ref()
+            .is_some_and(|session| session.read_only)
+    }
+
+    /// Enables a translation hook for the given peer's conversation.
+    pub async fn set_translation_hook(&self, peer_id: String, hook: TranslationHook) -> Result<()> {
+        if let Some(session) = self.current_session.write().await.as_mut() {
+            session.set_translation_hook(peer_id, hook);
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("No active session"))
+        }
+    }
+
+    pub async fn disable_translation_hook(&self, peer_id: &str) -> Result<()> {
+        if let Some(session) = self.current_session.write().await.as_mut() {
+            session.disable_translation_hook(peer_id);
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("No active session"))
+        }
+    }
+
+    /// Flags a message for the starred review view, across whichever conversation it belongs to.
+    pub async fn star_message(&self, message_id: Uuid) -> Result<()> {
+        if let Some(session) = self.current_session.write().await.as_mut() {
+            session.star_message(message_id)
+        } else {
+            Err(anyhow::anyhow!("No active session"))
+        }
+    }
+
+    pub async fn unstar_message(&self, message_id: Uuid) -> Result<()> {
+        if let Some(session) = self.current_session.write().await.as_mut() {
+            session.unstar_message(message_id)
+        } else {
+            Err(anyhow::anyhow!("No active session"))
+        }
+    }
+
+    pub async fn get_starred_messages(&self) -> Vec<Message> {
+        if let Some(session) = self.current_session.read().await.as_ref() {
+            session
+                .get_starred_messages()
+                .into_iter()
+                .cloned()
+                .collect()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Pins a message to highlight it within its conversation - see `/pin`.
+    pub async fn pin_message(&self, message_id: Uuid) -> Result<()> {
+        if let Some(session) = self.current_session.write().await.as_mut() {
+            session.pin_message(message_id)
+        } else {
+            Err(anyhow::anyhow!("No active session"))
+        }
+    }
+
+    pub async fn unpin_message(&self, message_id: Uuid) -> Result<()> {
+        if let Some(session) = self.current_session.write().await.as_mut() {
+            session.unpin_message(message_id)
+        } else {
+            Err(anyhow::anyhow!("No active session"))
+        }
+    }
+
+    /// Pinned messages in the conversation with `peer_id` - see `/pins`.
+    pub async fn get_pinned_messages(&self, peer_id: &str) -> Vec<Message> {
+        if let Some(session) = self.current_session.read().await.as_ref() {
+            session
+                .get_pinned_messages(peer_id)
+                .into_iter()
+                .cloned()
+                .collect()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Enumerates every conversation in the current session, one per peer -
+    /// see `ChatSession::conversations`. The CLI's `/conversations` command,
+    /// a future TUI, and the napi bindings all read this instead of
+    /// reaching into `message_history` directly.
+    pub async fn conversations(&self, include_archived: bool) -> Vec<Conversation> {
+        match self.current_session.read().await.as_ref() {
+            Some(session) => session.conversations(self.identity.user_id, include_archived),
+            None => Vec::new(),
+        }
+    }
+
+    /// Unread message count for the conversation with `peer_id`, i.e. how
+    /// many messages `mark_conversation_read(peer_id)` would mark read right
+    /// now - see `conversations` and `/peers`.
+    pub async fn unread_count(&self, peer_id: &str) -> usize {
+        self.conversations(true)
+            .await
+            .into_iter()
+            .find(|conversation| conversation.peer_id == peer_id)
+            .map(|conversation| conversation.unread_count)
+            .unwrap_or(0)
+    }
+
+    /// Sets the in-progress draft for the conversation with `peer_id` - see
+    /// `ChatSession::set_draft`.
+    pub async fn set_draft(&self, peer_id: &str, draft: String) -> Result<()> {
+        if let Some(session) = self.current_session.write().await.as_mut() {
+            session.set_draft(peer_id, draft);
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("No active session"))
+        }
+    }
+
+    /// Mutes or unmutes the conversation with `peer_id` - see
+    /// `ChatSession::set_conversation_muted`.
+    pub async fn set_conversation_muted(&self, peer_id: &str, muted: bool) -> Result<()> {
+        if let Some(session) = self.current_session.write().await.as_mut() {
+            session.set_conversation_muted(peer_id, muted);
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("No active session"))
+        }
+    }
+
+    /// Proves `message_id` really came from the peer it claims to, by
+    /// checking its stored `Message::signature` against that peer's
+    /// `Peer::signing_key` - see `/verify`. Works just as well on a message
+    /// restored from an export as on one still in live history, since the
+    /// signature travels with the message rather than depending on the
+    /// connection it arrived on. Errors if the message isn't in history, was
+    /// never signed, or the signing peer isn't in `active_peers` for us to
+    /// check against (we don't persist peers' signing keys independently of
+    /// their `Peer` record).
+    pub async fn verify_message(&self, message_id: Uuid) -> Result<bool> {
+        let message = self
+            .current_session
+            .read()
+            .await
+            .as_ref()
+            .and_then(|session| session.get_message(message_id).cloned())
+            .ok_or_else(|| anyhow::anyhow!("Message '{}' not found in history", message_id))?;
+
+        if message.signature.is_none() {
+            return Err(anyhow::anyhow!("Message '{}' was never signed", message_id));
+        }
+
+        let signing_key = self
+            .get_active_peers()
+            .await
+            .into_iter()
+            .find(|peer| peer.id == message.sender_id)
+            .map(|peer| peer.signing_key)
+            .filter(|key| !key.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("No signing key known for sender {}", message.sender_id))?;
+
+        Ok(message.verify_signature(&signing_key))
+    }
+
+    pub async fn get_session_info(&self) -> Option<(String, u16, usize)> {
+        self.current_session.read().await.as_ref().map(|session| {
+            (
+                session.id.clone(),
+                session.current_port,
+                session.active_peers.len(),
+            )
+        })
+    }
+
+    /// The single graceful-shutdown path for quitting chat: persists session
+    /// state to disk, says goodbye to connected peers, and flushes queued
+    /// writes. `rus`'s `/quit` command and its Ctrl+C handler both call this
+    /// same routine rather than each unwinding state their own way.
+    ///
+    /// Note: this codebase has no raw-terminal-mode UI (no `crossterm`/
+    /// `ratatui` usage anywhere in `rus`/`rustalk`), so there's no terminal
+    /// state to restore here even on panic - that part of a "restore the
+    /// terminal" guarantee is inherently out of scope until a TUI front-end
+    /// actually exists.
+    pub async fn end_session(&self) -> Result<()> {
+        {
+            let current_session = self.current_session.read().await;
+            if let Some(session) = current_session.as_ref()
+                && let Err(e) = Self::save_state(session)
+            {
+                log::warn!("Failed to persist session state: {}", e);
+            }
+        }
+
+        {
+            let mut current_session = self.current_session.write().await;
+            *current_session = None;
+        }
+
+        // Sends a Disconnect message to every peer before dropping the
+        // connections - see `NetworkManager::notify_disconnect`.
+        let network = self.network.read().await;
+        network.stop_listening().await?;
+
+        self.write_behind.flush().await;
+
+        Ok(())
+    }
+
+    fn session_state_file() -> Result<std::path::PathBuf> {
+        Ok(crate::config::get_config_dir()?.join("session_state.json"))
+    }
+
+    fn outbox_file() -> Result<std::path::PathBuf> {
+        Ok(crate::config::get_config_dir()?.join("outbox.json"))
+    }
+
+    fn write_outbox_file(outbox: &Outbox) -> Result<()> {
+        let file = Self::outbox_file()?;
+        let contents = serde_json::to_string_pretty(outbox)?;
+        std::fs::write(file, contents)?;
+        Ok(())
+    }
+
+    /// Queues `outbox` to be written to `outbox_file` off the runtime thread,
+    /// so a message that's still queued (not yet delivered, or permanently
+    /// `Failed`) survives a restart instead of only living in memory - called
+    /// after every `Outbox` mutation made from a command handler. The
+    /// background retry loop in `start_outbox_retry_loop` writes synchronously
+    /// instead, since it already runs off the command-handling path.
+    fn persist_outbox(&self, outbox: Outbox) {
+        self.write_behind.enqueue(move || Self::write_outbox_file(&outbox));
+    }
+
+    /// Loads the `Outbox` left behind by a previous process, if any. Missing
+    /// or unreadable state is treated as "nothing queued" rather than an
+    /// error - a fresh outbox should still start.
+    fn restore_outbox() -> Option<Outbox> {
+        let file = Self::outbox_file().ok()?;
+        let contents = std::fs::read_to_string(file).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    fn schedule_file() -> Result<std::path::PathBuf> {
+        Ok(crate::config::get_config_dir()?.join("scheduled.json"))
+    }
+
+    fn write_schedule_file(schedule: &ScheduledQueue) -> Result<()> {
+        let file = Self::schedule_file()?;
+        let contents = serde_json::to_string_pretty(schedule)?;
+        std::fs::write(file, contents)?;
+        Ok(())
+    }
+
+    /// Queues `schedule` to be written to `schedule_file` off the runtime
+    /// thread, same as `persist_outbox` - called after every `ScheduledQueue`
+    /// mutation made from a command handler. `start_schedule_loop` writes
+    /// synchronously instead, since it already runs off the command-handling
+    /// path.
+    fn persist_schedule(&self, schedule: ScheduledQueue) {
+        self.write_behind
+            .enqueue(move || Self::write_schedule_file(&schedule));
+    }
+
+    /// Loads the `ScheduledQueue` left behind by a previous process, if any.
+    /// Missing or unreadable state is treated as "nothing scheduled" rather
+    /// than an error - a fresh queue should still start.
+    fn restore_schedule() -> Option<ScheduledQueue> {
+        let file = Self::schedule_file().ok()?;
+        let contents = std::fs::read_to_string(file).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    /// Persists archives, translation hooks and the read-only flag of
+    /// `session` (not `message_history`, which is `#[serde(skip)]` and lives
+    /// in `message_store`) so a future `start_session` can pick up where this one
+    /// left off - see `restore_saved_state`. Written synchronously since
+    /// shutdown is a one-shot event, not a hot path.
+    fn save_state(session: &ChatSession) -> Result<()> {
+        let file = Self::session_state_file()?;
+        let contents = serde_json::to_string_pretty(session)?;
+        std::fs::write(file, contents)?;
+        Ok(())
+    }
+
+    /// Loads the `ChatSession` persisted by the last `end_session`, if any.
+    /// Missing or unreadable state is treated as "nothing to restore" rather
+    /// than an error - a fresh session should still start.
+    fn restore_saved_state() -> Option<ChatSession> {
+        let file = Self::session_state_file().ok()?;
+        let contents = std::fs::read_to_string(file).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    /// Exports history to `out` in `format` - see `export::write_export` and
+    /// `rus history export`. Filters to messages sent to or from `peer` if
+    /// given. Reads straight from `message_store` (SQLite) rather than the
+    /// live session's in-memory history, so this works with no session
+    /// currently running - e.g. right after installing on a new machine.
+    /// Returns the number of messages written.
+    pub async fn export_history(
+        &self,
+        format: crate::export::ExportFormat,
+        peer: Option<Uuid>,
+        out: &std::path::Path,
+    ) -> Result<usize> {
+        let mut messages = self.message_store.load_messages()?;
+        if let Some(peer_id) = peer {
+            messages.retain(|message| message.sender_id == peer_id || message.recipient_id == Some(peer_id));
+        }
+        let count = messages.len();
+        crate::export::write_export(&messages, format, out)?;
+        Ok(count)
+    }
+
+    /// Imports a JSON history archive written by `export_history` - see
+    /// `rus history import`. Merges by message id: a message already present
+    /// in `message_store` is left untouched rather than overwritten, so
+    /// importing the same archive twice (or importing on the machine history
+    /// was exported from) is a no-op. Everything new is inserted into
+    /// `message_store` and, if a session is currently active, merged into its
+    /// in-memory history via `ChatSession::merge_message`, which resolves
+    /// timestamp ordering and flags anything landing out of order as
+    /// `delivered_late` - the same handling a reconnect catch-up gets.
+    /// Returns the number of messages actually imported.
+    pub async fn import_history(&self, path: &std::path::Path) -> Result<usize> {
+        let incoming = crate::export::read_export(path)?;
+        let existing_ids: HashSet<Uuid> =
+            self.message_store.load_messages()?.into_iter().map(|message| message.id).collect();
+
+        let mut imported = 0;
+        for message in incoming {
+            if existing_ids.contains(&message.id) {
+                continue;
+            }
+            self.message_store.insert_message(&message)?;
+            if let Some(session) = self.current_session.write().await.as_mut() {
+                session.merge_message(message);
+            }
+            imported += 1;
+        }
+
+        Ok(imported)
+    }
+
+    pub async fn list_recent_messages(&self, limit: usize) -> Vec<Message> {
+        if let Some(session) = self.current_session.read().await.as_ref() {
+            let recent: Vec<Message> = session
+                .get_recent_messages(limit)
+                .into_iter()
+                .cloned()
+                .collect();
+            recent
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Publishes our endpoints to the configured `Config::directory_address`,
+    /// generating and persisting a signing key on first use. Fails if no
+    /// directory server is configured - this feature is strictly opt-in.
+    pub async fn publish_to_directory(&self, endpoints: Vec<String>) -> Result<()> {
+        let mut config = crate::config::load_config_cached()?;
+        let directory_address = config
+            .directory_address
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("No directory server configured (Config::directory_address)"))?;
+
+        let signing_key = match &config.directory_signing_key {
+            Some(key) => key.clone(),
+            None => {
+                let key = crate::directory::generate_signing_key();
+                config.directory_signing_key = Some(key.clone());
+                crate::config::set_cached_config(config.clone());
+                self.write_behind
+                    .enqueue(move || crate::config::save_config(&config));
+                key
+            }
+        };
+
+        crate::directory::publish(&directory_address, &signing_key, self.identity.user_id, endpoints).await
+    }
+
+    /// Looks up `user_id`'s published endpoints on the configured
+    /// `Config::directory_address`, verifying the entry's signature.
+    pub async fn lookup_in_directory(&self, user_id: Uuid) -> Result<crate::directory::DirectoryEntry> {
+        let directory_address = crate::config::load_config_cached()?
+            .directory_address
+            .ok_or_else(|| anyhow::anyhow!("No directory server configured (Config::directory_address)"))?;
+
+        crate::directory::lookup(&directory_address, user_id).await
+    }
+
+    pub async fn get_peer_count(&self) -> usize {
+        if let Some(session) = self.current_session.read().await.as_ref() {
+            session.active_peers.len()
+        } else {
+            0
+        }
+    }
+
+    /// Toggles Do Not Disturb, suppressing notification sounds for everything
+    /// except mentions - see `NetworkManager::set_dnd` and `notify::notify`.
+    pub async fn set_dnd(&self, enabled: bool) {
+        self.network.read().await.set_dnd(enabled);
+    }
+
+    pub async fn is_dnd(&self) -> bool {
+        self.network.read().await.is_dnd()
+    }
+
+    /// Per-peer and global traffic/reconnect/RTT counters - see `/stats` and
+    /// `crate::stats::NetworkStats`.
+    pub async fn get_stats(&self) -> crate::stats::NetworkStats {
+        self.network.read().await.get_stats().await
+    }
+
+    /// Per-session summary for `/info` - traffic counters from `get_stats()`
+    /// plus how long the session has been running and how much its peer set
+    /// has churned. Returns `None` if no session is active.
+    pub async fn session_stats(&self) -> Option<crate::stats::SessionStats> {
+        let session = self.current_session.read().await;
+        let session = session.as_ref()?;
+        let network_stats = self.network.read().await.get_stats().await;
+
+        Some(crate::stats::SessionStats {
+            messages_sent: network_stats.global.messages_sent,
+            messages_received: network_stats.global.messages_received,
+            bytes_sent: network_stats.global.bytes_sent,
+            bytes_received: network_stats.global.bytes_received,
+            active_duration_secs: (chrono::Utc::now() - session.started_at).num_seconds(),
+            peers_connected: session.peers_connected,
+            peers_disconnected: session.peers_disconnected,
+        })
+    }
+
+    /// Sets the global default notification sound - `"bell"`, `"none"` to
+    /// disable, or a path to an audio file. See `Config::notification_sound`.
+    pub async fn set_notification_sound(&self, sound: Option<String>) -> Result<()> {
+        let mut config = crate::config::load_config_cached()?;
+        config.notification_sound = sound;
+        crate::config::set_cached_config(config.clone());
+        self.write_behind
+            .enqueue(move || crate::config::save_config(&config));
+        Ok(())
+    }
+
+    /// Overrides the notification sound for one peer - see
+    /// `Config::peer_notification_sounds`.
+    pub async fn set_peer_notification_sound(&self, peer_id: String, sound: String) -> Result<()> {
+        let mut config = crate::config::load_config_cached()?;
+        config.peer_notification_sounds.insert(peer_id, sound);
+        crate::config::set_cached_config(config.clone());
+        self.write_behind
+            .enqueue(move || crate::config::save_config(&config));
+        Ok(())
+    }
+
+    /// Snoozes notifications from `peer_id` for `duration` - see `/mute` and
+    /// `Config::muted_until`. Returns the expiry timestamp so the caller can
+    /// tell the user when it wears off.
+    pub async fn mute_conversation(
+        &self,
+        peer_id: String,
+        duration: chrono::Duration,
+    ) -> Result<chrono::DateTime<chrono::Utc>> {
+        let mut config = crate::config::load_config_cached()?;
+        let until = chrono::Utc::now() + duration;
+        config.muted_until.insert(peer_id, until);
+        crate::config::set_cached_config(config.clone());
+        self.write_behind
+            .enqueue(move || crate::config::save_config(&config));
+        Ok(until)
+    }
+
+    /// Cancels an active `/mute` for `peer_id` early.
+    pub async fn unmute_conversation(&self, peer_id: String) -> Result<()> {
+        let mut config = crate::config::load_config_cached()?;
+        config.muted_until.remove(&peer_id);
+        crate::config::set_cached_config(config.clone());
+        self.write_behind
+            .enqueue(move || crate::config::save_config(&config));
+        Ok(())
+    }
+}
+
+/// Guesses a MIME type from `path`'s extension for `SessionManager::send_image`.
+/// This codebase has no existing precedent for sniffing file contents (even
+/// `file_transfer` never needs a MIME type, only a checksum), so an extension
+/// lookup covering common image formats is enough; anything else falls back
+/// to a generic binary type rather than failing the send. `pub` so
+/// `rustalk::napi_bindings::send_image` can guess the same way without its
+/// own copy of this list.
+pub fn guess_mime(path: &std::path::Path) -> String {
+    match path.extension().and_then(|ext| ext.to_str()).map(|ext| ext.to_lowercase()) {
+        Some(ext) if ext == "png" => "image/png",
+        Some(ext) if ext == "jpg" || ext == "jpeg" => "image/jpeg",
+        Some(ext) if ext == "gif" => "image/gif",
+        Some(ext) if ext == "webp" => "image/webp",
+        Some(ext) if ext == "bmp" => "image/bmp",
+        Some(ext) if ext == "svg" => "image/svg+xml",
+        _ => "application/octet-stream",
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_message(sender_id: Uuid, content: &str) -> Message {
+        Message::new(sender_id, None, MessageType::Text, content.to_string(), "Tester".to_string())
+    }
+
+    // `network::spawn_reader` pins an inbound Edit/Retract's `sender_id` to
+    // the authenticated connection's peer id before it ever reaches these
+    // methods - these tests cover that `apply_edit`/`apply_retraction`
+    // themselves still enforce the check against whatever id they're handed,
+    // i.e. that a forged id (the part `spawn_reader` now prevents reaching
+    // here) would be refused if it ever did.
+
+    #[test]
+    fn apply_edit_refuses_an_editor_that_does_not_own_the_message() {
+        let mut session = ChatSession::new("test".to_string(), 0);
+        let owner = Uuid::new_v4();
+        let attacker = Uuid::new_v4();
+        let message = text_message(owner, "original");
+        let message_id = message.id;
+        session.add_message(message);
+
+        let result = session.apply_edit(attacker, message_id, "forged".to_string());
+
+        assert!(result.is_err());
+        assert_eq!(session.message_history[0].content, "original");
+        assert!(!session.message_history[0].edited);
+    }
+
+    #[test]
+    fn apply_edit_allows_the_original_sender() {
+        let mut session = ChatSession::new("test".to_string(), 0);
+        let owner = Uuid::new_v4();
+        let message = text_message(owner, "original");
+        let message_id = message.id;
+        session.add_message(message);
+
+        session.apply_edit(owner, message_id, "updated".to_string()).expect("owner can edit their own message");
+
+        assert_eq!(session.message_history[0].content, "updated");
+        assert!(session.message_history[0].edited);
+    }
+
+    #[test]
+    fn apply_retraction_refuses_a_requester_that_does_not_own_the_message() {
+        let mut session = ChatSession::new("test".to_string(), 0);
+        let owner = Uuid::new_v4();
+        let attacker = Uuid::new_v4();
+        let message = text_message(owner, "original");
+        let message_id = message.id;
+        session.add_message(message);
+
+        let result = session.apply_retraction(attacker, message_id);
+
+        assert!(result.is_err());
+        assert_eq!(session.message_history[0].content, "original");
+        assert!(!session.message_history[0].retracted);
+    }
+
+    #[test]
+    fn apply_retraction_allows_the_original_sender() {
+        let mut session = ChatSession::new("test".to_string(), 0);
+        let owner = Uuid::new_v4();
+        let message = text_message(owner, "original");
+        let message_id = message.id;
+        session.add_message(message);
+
+        session.apply_retraction(owner, message_id).expect("owner can retract their own message");
+
+        assert!(session.message_history[0].content.is_empty());
+        assert!(session.message_history[0].retracted);
+    }
+
+    #[test]
+    fn apply_delivery_ack_marks_a_sent_message_delivered() {
+        let mut session = ChatSession::new("test".to_string(), 0);
+        let sender = Uuid::new_v4();
+        let message = text_message(sender, "hi");
+        let message_id = message.id;
+        session.add_message(message);
+
+        session.apply_delivery_ack(message_id).expect("message is in history");
+
+        assert_eq!(session.message_history[0].delivery_status, crate::message::DeliveryStatus::Delivered);
+    }
+
+    #[test]
+    fn mark_delivery_failed_overrides_sent_status() {
+        let mut session = ChatSession::new("test".to_string(), 0);
+        let sender = Uuid::new_v4();
+        let message = text_message(sender, "hi");
+        let message_id = message.id;
+        session.add_message(message);
+
+        session.mark_delivery_failed(message_id);
+
+        assert_eq!(session.message_history[0].delivery_status, crate::message::DeliveryStatus::Failed);
     }
 }
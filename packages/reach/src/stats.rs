@@ -0,0 +1,98 @@
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Traffic and reliability counters for one peer, accumulated by
+/// `network::NetworkManager` as messages flow through it. See `NetworkStats`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PeerStats {
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    pub messages_sent: u64,
+    pub messages_received: u64,
+    /// How many times a connection to this peer was (re-)established after
+    /// a prior one existed - see `network::NetworkManager`'s connection setup.
+    pub reconnects: u64,
+    /// Running average of `NetworkManager::ping_peer`'s measured response
+    /// times for this peer, in milliseconds. `None` until it's been pinged
+    /// at least once.
+    pub average_rtt_ms: Option<f64>,
+    /// How many RTT samples `average_rtt_ms` is averaged over.
+    pub rtt_samples: u32,
+}
+
+impl PeerStats {
+    pub(crate) fn record_rtt(&mut self, sample_ms: u64) {
+        self.rtt_samples += 1;
+        let previous_avg = self.average_rtt_ms.unwrap_or(0.0);
+        self.average_rtt_ms =
+            Some(previous_avg + (sample_ms as f64 - previous_avg) / self.rtt_samples as f64);
+    }
+}
+
+/// Snapshot of network activity across every peer this process has talked
+/// to, plus the sum of those as `global`. Returned by
+/// `NetworkManager::get_stats()`/`ReachEngine::network_stats()` - see `/stats`
+/// in the CLI and `getNetworkStats` in the napi bindings.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct NetworkStats {
+    pub global: PeerStats,
+    /// Keyed by peer id (`Peer::id.to_string()`).
+    pub per_peer: HashMap<String, PeerStats>,
+}
+
+impl NetworkStats {
+    pub(crate) fn record_sent(&mut self, peer_id: &str, bytes: u64) {
+        self.global.bytes_sent += bytes;
+        self.global.messages_sent += 1;
+        let peer = self.per_peer.entry(peer_id.to_string()).or_default();
+        peer.bytes_sent += bytes;
+        peer.messages_sent += 1;
+    }
+
+    pub(crate) fn record_received(&mut self, peer_id: &str, bytes: u64) {
+        self.global.bytes_received += bytes;
+        self.global.messages_received += 1;
+        let peer = self.per_peer.entry(peer_id.to_string()).or_default();
+        peer.bytes_received += bytes;
+        peer.messages_received += 1;
+    }
+
+    /// Whether `peer_id` has any recorded activity yet - used to tell a
+    /// fresh connection from a reconnect.
+    pub(crate) fn has_seen(&self, peer_id: &str) -> bool {
+        self.per_peer.contains_key(peer_id)
+    }
+
+    pub(crate) fn record_reconnect(&mut self, peer_id: &str) {
+        self.global.reconnects += 1;
+        self.per_peer.entry(peer_id.to_string()).or_default().reconnects += 1;
+    }
+
+    pub(crate) fn record_rtt(&mut self, peer_id: &str, sample_ms: u64) {
+        self.global.record_rtt(sample_ms);
+        self.per_peer
+            .entry(peer_id.to_string())
+            .or_default()
+            .record_rtt(sample_ms);
+    }
+}
+
+/// Summary of the current chat session, combining `NetworkStats`'s traffic
+/// counters with session-only figures `NetworkManager` doesn't track itself -
+/// see `SessionManager::session_stats()` and `/info`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SessionStats {
+    pub messages_sent: u64,
+    pub messages_received: u64,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    /// Seconds since `ChatSession::started_at` - resets on every
+    /// `start_session`, unlike the traffic counters above which accumulate
+    /// for as long as `NetworkManager` has existed.
+    pub active_duration_secs: i64,
+    /// How many times a peer has connected or disconnected this session - see
+    /// `ChatSession::add_peer`/`remove_peer`. There's no room/membership
+    /// concept in this codebase, so this is the closest analogue to "churn".
+    pub peers_connected: u64,
+    pub peers_disconnected: u64,
+}
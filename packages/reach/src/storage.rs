@@ -0,0 +1,214 @@
+//! SQLite-backed persistence for `ChatSession::message_history` - see
+//! `SessionManager::persist_message`. Everything else about a `ChatSession`
+//! (archived peers, translation hooks, the read-only flag, the topic) still
+//! goes through `session.rs`'s whole-blob `session_state_file()` JSON file;
+//! only message history moves here, since it's the part that grows without
+//! bound and benefits from being appended incrementally instead of rewritten
+//! in full on every `end_session`.
+//!
+//! Each message's JSON is encrypted with AES-256-GCM before it's written -
+//! see `CryptoEngine::derive_storage_key` - so reading `messages.sqlite`
+//! straight off disk doesn't hand over past conversations the way the old
+//! plaintext JSON blob did.
+
+use anyhow::{Context, Result};
+use rusqlite::Connection;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::Message;
+use crate::crypto::CryptoEngine;
+
+/// Schema version this build expects. `migrate` walks a fresh or older
+/// database up to it by applying each numbered step in order; there's only
+/// one step today, but the table exists so a future change has somewhere to
+/// record which step it last applied.
+const SCHEMA_VERSION: i64 = 1;
+
+/// Thin wrapper around a `rusqlite::Connection`. All operations are
+/// blocking - callers run them via `spawn_blocking`, the same way
+/// `persist.rs::WriteBehindQueue` offloads other filesystem writes, rather
+/// than doing SQLite I/O directly on the async runtime.
+pub struct MessageStore {
+    conn: Mutex<Connection>,
+    /// AES-256-GCM key each message's JSON is encrypted under - see
+    /// `CryptoEngine::derive_storage_key`. Not persisted anywhere; derived
+    /// fresh from the identity on every `open` so losing the config dir
+    /// (without the identity file) makes the history unreadable too.
+    key: [u8; 32],
+    /// Kept around so `prune` can check the database file's size on disk for
+    /// `Config::history_max_disk_usage_bytes` - the connection itself has no
+    /// cheap way to ask "how many bytes is this".
+    path: PathBuf,
+}
+
+impl MessageStore {
+    /// Opens (creating if needed) the SQLite database at `path` and brings
+    /// its schema up to date. `key` encrypts/decrypts every message's JSON -
+    /// see `CryptoEngine::derive_storage_key`.
+    pub fn open(path: &Path, key: [u8; 32]) -> Result<Self> {
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open message store at {}", path.display()))?;
+        Self::migrate(&conn)?;
+        Ok(Self { conn: Mutex::new(conn), key, path: path.to_path_buf() })
+    }
+
+    fn migrate(conn: &Connection) -> Result<()> {
+        conn.execute_batch("CREATE TABLE IF NOT EXISTS schema_version (version INTEGER NOT NULL);")?;
+        let current: i64 = conn
+            .query_row("SELECT version FROM schema_version LIMIT 1", [], |row| row.get(0))
+            .unwrap_or(0);
+
+        if current < 1 {
+            conn.execute_batch(
+                "CREATE TABLE IF NOT EXISTS messages (
+                    message_id TEXT PRIMARY KEY,
+                    timestamp INTEGER NOT NULL,
+                    data TEXT NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS messages_timestamp ON messages (timestamp);",
+            )?;
+        }
+
+        conn.execute("DELETE FROM schema_version", [])?;
+        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [SCHEMA_VERSION])?;
+        Ok(())
+    }
+
+    /// Inserts or replaces `message`, keyed by its id - replacing makes this
+    /// safe to call again for a message that was edited/retracted in place
+    /// rather than only ever appended once.
+    pub fn insert_message(&self, message: &Message) -> Result<()> {
+        let data = CryptoEngine::encrypt_message(&serde_json::to_string(message)?, &self.key)?;
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO messages (message_id, timestamp, data) VALUES (?1, ?2, ?3)",
+            rusqlite::params![message.id.to_string(), message.timestamp.timestamp_micros(), data],
+        )?;
+        Ok(())
+    }
+
+    /// Loads every stored message, oldest first - used by `start_session` to
+    /// repopulate `ChatSession::message_history` on startup.
+    pub fn load_messages(&self) -> Result<Vec<Message>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT data FROM messages ORDER BY timestamp ASC")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        let mut messages = Vec::new();
+        for row in rows {
+            let data = CryptoEngine::decrypt_message(&row?, &self.key)?;
+            messages.push(serde_json::from_str(&data)?);
+        }
+        Ok(messages)
+    }
+
+    /// Enforces `Config`'s `history_max_*` retention options, returning how
+    /// many messages were deleted. Each limit is applied independently, in
+    /// the order below, against whatever the previous one left behind:
+    ///
+    /// 1. `max_per_conversation` - for each pair of correspondents, keep only
+    ///    the newest `n` messages between them (a `Broadcast` with no
+    ///    `recipient_id` counts as its own "conversation" keyed by sender).
+    /// 2. `max_age_days` - drop anything older than that, regardless of size.
+    /// 3. `max_disk_usage_bytes` - if the database file is still over budget,
+    ///    delete the oldest remaining messages overall until it isn't, then
+    ///    `VACUUM` to actually shrink the file (SQLite doesn't do this on
+    ///    `DELETE` by itself).
+    ///
+    /// `None` skips a limit entirely, matching the "keep everything" default.
+    pub fn prune(
+        &self,
+        max_per_conversation: Option<usize>,
+        max_age_days: Option<u32>,
+        max_disk_usage_bytes: Option<u64>,
+    ) -> Result<usize> {
+        let mut pruned = 0;
+
+        if let Some(max_per_conversation) = max_per_conversation {
+            pruned += self.prune_per_conversation(max_per_conversation)?;
+        }
+
+        if let Some(max_age_days) = max_age_days {
+            let cutoff = (chrono::Utc::now() - chrono::Duration::days(max_age_days as i64)).timestamp_micros();
+            let conn = self.conn.lock().unwrap();
+            pruned += conn.execute("DELETE FROM messages WHERE timestamp < ?1", rusqlite::params![cutoff])?;
+        }
+
+        if let Some(max_disk_usage_bytes) = max_disk_usage_bytes {
+            pruned += self.prune_to_disk_budget(max_disk_usage_bytes)?;
+        }
+
+        if pruned > 0 {
+            self.conn.lock().unwrap().execute_batch("VACUUM")?;
+        }
+
+        Ok(pruned)
+    }
+
+    /// Groups every stored message by the pair of ids on either end of it
+    /// (see `conversation_key`) and deletes the oldest ones in any group that
+    /// exceeds `max_per_conversation`.
+    fn prune_per_conversation(&self, max_per_conversation: usize) -> Result<usize> {
+        let mut by_conversation: HashMap<String, Vec<(String, i64)>> = HashMap::new();
+        {
+            let conn = self.conn.lock().unwrap();
+            let mut stmt = conn.prepare("SELECT message_id, timestamp, data FROM messages ORDER BY timestamp DESC")?;
+            let rows = stmt.query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, String>(2)?))
+            })?;
+            for row in rows {
+                let (message_id, timestamp, data) = row?;
+                let data = CryptoEngine::decrypt_message(&data, &self.key)?;
+                let message: Message = serde_json::from_str(&data)?;
+                by_conversation.entry(conversation_key(&message)).or_default().push((message_id, timestamp));
+            }
+        }
+
+        let excess: Vec<String> = by_conversation
+            .into_values()
+            .flat_map(|messages| messages.into_iter().skip(max_per_conversation).map(|(message_id, _)| message_id))
+            .collect();
+
+        let conn = self.conn.lock().unwrap();
+        for message_id in &excess {
+            conn.execute("DELETE FROM messages WHERE message_id = ?1", rusqlite::params![message_id])?;
+        }
+        Ok(excess.len())
+    }
+
+    /// Deletes the oldest stored messages, one at a time, until the database
+    /// file's size on disk is back under `max_disk_usage_bytes`.
+    fn prune_to_disk_budget(&self, max_disk_usage_bytes: u64) -> Result<usize> {
+        let mut pruned = 0;
+        while std::fs::metadata(&self.path).map(|metadata| metadata.len()).unwrap_or(0) > max_disk_usage_bytes {
+            let conn = self.conn.lock().unwrap();
+            let oldest: Option<String> = conn
+                .query_row("SELECT message_id FROM messages ORDER BY timestamp ASC LIMIT 1", [], |row| row.get(0))
+                .ok();
+            let Some(oldest) = oldest else { break };
+            conn.execute("DELETE FROM messages WHERE message_id = ?1", rusqlite::params![oldest])?;
+            drop(conn);
+            self.conn.lock().unwrap().execute_batch("VACUUM")?;
+            pruned += 1;
+            // `VACUUM` above is what actually shrinks the file, since without
+            // it SQLite would keep reusing the freed pages and `metadata`
+            // would never report a smaller size for the loop to converge on.
+        }
+        Ok(pruned)
+    }
+}
+
+/// Identifies which "conversation" a message belongs to for
+/// `MessageStore::prune`'s per-conversation limit: the sorted pair of sender
+/// and recipient ids, so both sides of a direct message land in the same
+/// group regardless of who sent which message. A `Broadcast` (no
+/// `recipient_id`) is keyed by sender alone.
+fn conversation_key(message: &Message) -> String {
+    let mut ids = vec![message.sender_id.to_string()];
+    if let Some(recipient_id) = message.recipient_id {
+        ids.push(recipient_id.to_string());
+    }
+    ids.sort();
+    ids.join(":")
+}
@@ -0,0 +1,60 @@
+use sha2::{Digest, Sha256};
+
+/// Small unicode glyphs cycled to build an identicon; chosen for being easy
+/// to tell apart at a glance, not for any deeper meaning.
+const IDENTICON_GLYPHS: [char; 8] = ['■', '▲', '●', '◆', '★', '▶', '✚', '◼'];
+
+/// ANSI foreground colors cycled for per-peer color coding. Bright variants
+/// are mixed in so two peers landing in the same hue bucket still read as
+/// visually distinct.
+const PALETTE: [&str; 8] = [
+    "\x1b[31m",
+    "\x1b[32m",
+    "\x1b[33m",
+    "\x1b[34m",
+    "\x1b[35m",
+    "\x1b[36m",
+    "\x1b[91m",
+    "\x1b[94m",
+];
+
+const RESET: &str = "\x1b[0m";
+
+fn fingerprint_hash(fingerprint: &str) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(fingerprint.as_bytes());
+    hasher.finalize().into()
+}
+
+/// Picks a stable ANSI color escape for a peer's key fingerprint, so the same
+/// sender renders in the same color every time - see `Config::color_coded_peers`.
+pub fn ansi_color(fingerprint: &str) -> &'static str {
+    let hash = fingerprint_hash(fingerprint);
+    PALETTE[hash[0] as usize % PALETTE.len()]
+}
+
+/// Builds a small two-glyph unicode identicon from a peer's key fingerprint,
+/// so different senders are visually distinguishable at a glance in busy rooms.
+pub fn identicon(fingerprint: &str) -> String {
+    let hash = fingerprint_hash(fingerprint);
+    let first = IDENTICON_GLYPHS[hash[1] as usize % IDENTICON_GLYPHS.len()];
+    let second = IDENTICON_GLYPHS[hash[2] as usize % IDENTICON_GLYPHS.len()];
+    format!("{}{}", first, second)
+}
+
+/// Renders `label` (typically a sender's display name) prefixed with its
+/// identicon and wrapped in its color-coded ANSI escapes, derived from
+/// `fingerprint` (a peer's public key, or any other stable per-peer id).
+/// Returns `label` unchanged if `Config::color_coded_peers` is disabled.
+pub fn badge(fingerprint: &str, label: &str, config: &crate::config::Config) -> String {
+    if !config.color_coded_peers {
+        return label.to_string();
+    }
+    format!(
+        "{}{} {}{}",
+        ansi_color(fingerprint),
+        identicon(fingerprint),
+        label,
+        RESET
+    )
+}
@@ -0,0 +1,65 @@
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// A classic token bucket: tokens (bytes) refill continuously at `rate_per_sec`
+/// up to `capacity`, and `consume` sleeps just long enough for enough tokens
+/// to accrue before letting a send/receive through. Used by `PeerConnection`
+/// to cap upload/download throughput per `Config::upload_limit_bytes_per_sec`
+/// / `download_limit_bytes_per_sec`, so one chatty or file-transferring peer
+/// can't saturate the user's link.
+pub struct TokenBucket {
+    rate_per_sec: f64,
+    capacity: f64,
+    state: Mutex<BucketState>,
+}
+
+struct BucketState {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// `rate_bytes_per_sec` is both the steady-state rate and the burst
+    /// capacity (one second's worth of tokens), which keeps the limiter's
+    /// behavior easy to reason about: it never lets through more than one
+    /// second of backlog at once.
+    pub fn new(rate_bytes_per_sec: u64) -> Self {
+        let capacity = rate_bytes_per_sec as f64;
+        TokenBucket {
+            rate_per_sec: capacity,
+            capacity,
+            state: Mutex::new(BucketState {
+                tokens: capacity,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// Blocks until `bytes` worth of tokens are available, refilling based on
+    /// elapsed wall-clock time since the last call.
+    pub async fn consume(&self, bytes: usize) {
+        loop {
+            let wait = {
+                let mut state = self.state.lock().await;
+                let now = Instant::now();
+                let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+                state.tokens = (state.tokens + elapsed * self.rate_per_sec).min(self.capacity);
+                state.last_refill = now;
+
+                let bytes = bytes as f64;
+                if state.tokens >= bytes {
+                    state.tokens -= bytes;
+                    None
+                } else {
+                    let deficit = bytes - state.tokens;
+                    Some(Duration::from_secs_f64(deficit / self.rate_per_sec))
+                }
+            };
+
+            match wait {
+                None => return,
+                Some(duration) => tokio::time::sleep(duration).await,
+            }
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use crate::config::Config;
+use chrono::{DateTime, Local, Utc};
+
+/// Renders `timestamp` per the user's configured display preferences, so the
+/// CLI, TUI, and exports all agree on one format instead of hard-coding a
+/// `chrono` format string per call site.
+pub fn format_timestamp(timestamp: DateTime<Utc>, config: &Config) -> String {
+    if config.relative_timestamps {
+        return format_relative(timestamp);
+    }
+
+    let pattern = if config.use_12_hour_clock {
+        "%Y-%m-%d %I:%M %p"
+    } else {
+        "%Y-%m-%d %H:%M"
+    };
+
+    if config.use_local_time {
+        let local = timestamp.with_timezone(&Local);
+        local.format(pattern).to_string()
+    } else {
+        format!("{} UTC", timestamp.format(pattern))
+    }
+}
+
+fn format_relative(timestamp: DateTime<Utc>) -> String {
+    let delta = Utc::now() - timestamp;
+
+    if delta.num_seconds() < 60 {
+        "just now".to_string()
+    } else if delta.num_minutes() < 60 {
+        format!("{}m ago", delta.num_minutes())
+    } else if delta.num_hours() < 24 {
+        format!("{}h ago", delta.num_hours())
+    } else {
+        format!("{}d ago", delta.num_days())
+    }
+}
@@ -0,0 +1,80 @@
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Pluggable translation hook for a single conversation, configured via `/translate`.
+///
+/// Translation is delegated to an external command so users can plug in whatever
+/// tool they already have (a local model, a cloud CLI, a shell script) instead of
+/// Rustalk bundling a translation engine itself.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranslationHook {
+    pub enabled: bool,
+    pub source_lang: String,
+    pub target_lang: String,
+    /// External command invoked as `<command> <source_lang> <target_lang>`, with the
+    /// original message piped to stdin and the translation read back from stdout.
+    pub command: String,
+}
+
+impl TranslationHook {
+    pub fn new(source_lang: String, target_lang: String, command: String) -> Self {
+        TranslationHook {
+            enabled: true,
+            source_lang,
+            target_lang,
+            command,
+        }
+    }
+
+    /// Parses the `de->en` shorthand used by `/translate on de->en`.
+    pub fn parse_language_pair(pair: &str) -> Result<(String, String)> {
+        let (source, target) = pair
+            .split_once("->")
+            .ok_or_else(|| anyhow!("Expected a language pair like 'de->en', got '{}'", pair))?;
+
+        if source.is_empty() || target.is_empty() {
+            return Err(anyhow!("Expected a language pair like 'de->en', got '{}'", pair));
+        }
+
+        Ok((source.to_string(), target.to_string()))
+    }
+
+    /// Runs the configured external command to translate `content`, returning the
+    /// translated text so it can be stored alongside the original in history.
+    pub fn translate(&self, content: &str) -> Result<String> {
+        if !self.enabled {
+            return Ok(content.to_string());
+        }
+
+        let mut child = Command::new(&self.command)
+            .arg(&self.source_lang)
+            .arg(&self.target_lang)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| anyhow!("Failed to spawn translation command '{}': {}", self.command, e))?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(content.as_bytes())
+                .map_err(|e| anyhow!("Failed to write to translation command: {}", e))?;
+        }
+
+        let output = child
+            .wait_with_output()
+            .map_err(|e| anyhow!("Failed to read translation command output: {}", e))?;
+
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Translation command exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+}
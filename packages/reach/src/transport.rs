@@ -0,0 +1,167 @@
+use anyhow::{Result, anyhow};
+use futures::{SinkExt, StreamExt};
+use std::future::Future;
+use std::pin::Pin;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio_tungstenite::WebSocketStream;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// What `PeerConnection` reads and writes frames over, so the same
+/// handshake/send/receive code in `network.rs` works whether a peer is
+/// reached over raw TCP, a WebSocket upgrade, or (for tests) an in-memory
+/// duplex - only the framing of a "frame" differs between implementations.
+///
+/// QUIC deliberately stays outside this trait - see `quic::QuicTransport` -
+/// it's a connectionless datagram transport addressed per-send rather than a
+/// connection-oriented stream, so it doesn't fit the same shape.
+pub trait Transport: Send + Sync {
+    /// Reads the next frame, returning an error (e.g. "Connection closed")
+    /// once the peer disconnects.
+    fn read_frame(&mut self) -> BoxFuture<'_, Result<Vec<u8>>>;
+
+    /// Writes and flushes one frame.
+    fn write_frame<'a>(&'a mut self, frame: &'a [u8]) -> BoxFuture<'a, Result<()>>;
+}
+
+impl Transport for TcpStream {
+    fn read_frame(&mut self) -> BoxFuture<'_, Result<Vec<u8>>> {
+        Box::pin(async move {
+            let mut buffer = vec![0u8; 4096];
+            let n = self.read(&mut buffer).await?;
+            if n == 0 {
+                return Err(anyhow!("Connection closed"));
+            }
+            buffer.truncate(n);
+            Ok(buffer)
+        })
+    }
+
+    fn write_frame<'a>(&'a mut self, frame: &'a [u8]) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            self.write_all(frame).await?;
+            self.flush().await?;
+            Ok(())
+        })
+    }
+}
+
+impl Transport for WebSocketStream<TcpStream> {
+    fn read_frame(&mut self) -> BoxFuture<'_, Result<Vec<u8>>> {
+        Box::pin(async move {
+            loop {
+                match self.next().await {
+                    Some(Ok(WsMessage::Text(text))) => return Ok(text.as_bytes().to_vec()),
+                    Some(Ok(WsMessage::Binary(data))) => return Ok(data.to_vec()),
+                    Some(Ok(WsMessage::Close(_))) | None => return Err(anyhow!("Connection closed")),
+                    Some(Ok(_)) => continue, // Ping/Pong/Frame - handled by tungstenite, nothing to decode
+                    Some(Err(e)) => return Err(anyhow!("WebSocket error: {}", e)),
+                }
+            }
+        })
+    }
+
+    fn write_frame<'a>(&'a mut self, frame: &'a [u8]) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let text = String::from_utf8_lossy(frame).into_owned();
+            self.send(WsMessage::Text(text)).await?;
+            Ok(())
+        })
+    }
+}
+
+/// Proof-of-concept off-grid transport: frames read/written over a serial
+/// port, so two nearby machines can chat over a null-modem cable or a
+/// Bluetooth RFCOMM link exposed as a serial device (`/dev/rfcommN` on
+/// Linux, a `COMn` port on Windows once paired) with no network at all.
+/// `tokio_serial::SerialStream` already implements `AsyncRead`/`AsyncWrite`,
+/// so framing is identical to the `TcpStream` impl above.
+///
+/// This is a proof of concept, not a production transport: there's no
+/// discovery (the path/port is supplied manually, unlike `mdns`/`directory`
+/// for TCP peers) and no flow control beyond what the UART itself provides.
+#[cfg(feature = "serial")]
+pub struct SerialTransport {
+    port: tokio_serial::SerialStream,
+}
+
+#[cfg(feature = "serial")]
+impl SerialTransport {
+    /// Opens `path` (e.g. `/dev/ttyUSB0`, `/dev/rfcomm0`, `COM3`) at `baud_rate`.
+    pub fn open(path: &str, baud_rate: u32) -> Result<Self> {
+        use tokio_serial::SerialPortBuilderExt;
+        let port = tokio_serial::new(path, baud_rate).open_native_async()?;
+        Ok(SerialTransport { port })
+    }
+
+    /// Wraps an already-open serial stream - e.g. one `noise::handshake` ran
+    /// directly against before a `Transport` existed to hand to `PeerConnection`.
+    pub fn from_stream(port: tokio_serial::SerialStream) -> Self {
+        SerialTransport { port }
+    }
+}
+
+#[cfg(feature = "serial")]
+impl Transport for SerialTransport {
+    fn read_frame(&mut self) -> BoxFuture<'_, Result<Vec<u8>>> {
+        Box::pin(async move {
+            let mut buffer = vec![0u8; 4096];
+            let n = self.port.read(&mut buffer).await?;
+            if n == 0 {
+                return Err(anyhow!("Connection closed"));
+            }
+            buffer.truncate(n);
+            Ok(buffer)
+        })
+    }
+
+    fn write_frame<'a>(&'a mut self, frame: &'a [u8]) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            self.port.write_all(frame).await?;
+            self.port.flush().await?;
+            Ok(())
+        })
+    }
+}
+
+/// In-memory transport standing in for a real socket, so connection-handling
+/// logic can be exercised without binding a port. `pair()` returns both ends
+/// already wired together, each frame sent on one arriving whole on the other.
+pub struct InMemoryTransport {
+    sender: mpsc::UnboundedSender<Vec<u8>>,
+    receiver: mpsc::UnboundedReceiver<Vec<u8>>,
+}
+
+impl InMemoryTransport {
+    pub fn pair() -> (InMemoryTransport, InMemoryTransport) {
+        let (tx_a, rx_a) = mpsc::unbounded_channel();
+        let (tx_b, rx_b) = mpsc::unbounded_channel();
+        (
+            InMemoryTransport { sender: tx_a, receiver: rx_b },
+            InMemoryTransport { sender: tx_b, receiver: rx_a },
+        )
+    }
+}
+
+impl Transport for InMemoryTransport {
+    fn read_frame(&mut self) -> BoxFuture<'_, Result<Vec<u8>>> {
+        Box::pin(async move {
+            self.receiver
+                .recv()
+                .await
+                .ok_or_else(|| anyhow!("Connection closed"))
+        })
+    }
+
+    fn write_frame<'a>(&'a mut self, frame: &'a [u8]) -> BoxFuture<'a, Result<()>> {
+        let frame = frame.to_vec();
+        Box::pin(async move {
+            self.sender
+                .send(frame)
+                .map_err(|_| anyhow!("Connection closed"))
+        })
+    }
+}
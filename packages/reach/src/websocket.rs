@@ -0,0 +1,19 @@
+use anyhow::{Result, anyhow};
+use tokio::net::TcpStream;
+use tokio_tungstenite::WebSocketStream;
+
+/// Accepts an inbound TCP connection and upgrades it to a WebSocket,
+/// for use by `NetworkManager::start_websocket_listener`.
+pub async fn accept(stream: TcpStream) -> Result<WebSocketStream<TcpStream>> {
+    Ok(tokio_tungstenite::accept_async(stream).await?)
+}
+
+/// Dials `ws://host:port`, for use by `NetworkManager::connect_via_websocket`.
+pub async fn connect(address: &str) -> Result<WebSocketStream<TcpStream>> {
+    let host_port = address
+        .strip_prefix("ws://")
+        .ok_or_else(|| anyhow!("Expected a ws:// address, got {}", address))?;
+    let stream = TcpStream::connect(host_port).await?;
+    let (ws_stream, _response) = tokio_tungstenite::client_async(address, stream).await?;
+    Ok(ws_stream)
+}
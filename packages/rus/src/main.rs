@@ -15,6 +15,16 @@ use user_manager::*;
 struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
+    /// Use this directory instead of the platform config directory, for
+    /// portable installs or running multiple instances side by side.
+    /// Overrides `RUSTALK_HOME` and `--profile` when given.
+    #[arg(long, global = true, value_name = "DIR")]
+    config_dir: Option<std::path::PathBuf>,
+    /// Run under a named profile - its own config, user registry, peers, and
+    /// history, isolated from the default profile and every other named
+    /// one. Shorthand for `--config-dir <platform dir>/profiles/<name>`.
+    #[arg(long, global = true, value_name = "NAME")]
+    profile: Option<String>,
 }
 
 #[derive(Subcommand)]
@@ -23,15 +33,23 @@ enum Commands {
     Setup,
     /// Start chatting
     Chat {
-        /// Port to listen on
+        /// Port to listen on, or 0 to let the OS assign one
         #[arg(short, long, default_value = "5000")]
         port: u16,
+        /// Interface to bind the listener to, e.g. `127.0.0.1` to keep it
+        /// off the LAN. Overrides `Config::bind_address` for this session.
+        #[arg(long, value_name = "ADDRESS")]
+        bind: Option<String>,
     },
     /// Connect to a peer directly
     Connect {
-        /// Peer address (IP:PORT)
-        address: String,
-        /// Port to listen on
+        /// Peer address (IP:PORT). Omit if using --id instead.
+        address: Option<String>,
+        /// Connect by user ID instead of address, resolved via
+        /// Config::directory_address (see `rus rendezvous`)
+        #[arg(long)]
+        id: Option<String>,
+        /// Port to listen on, or 0 to let the OS assign one
         #[arg(short, long, default_value = "5000")]
         port: u16,
     },
@@ -47,6 +65,16 @@ enum Commands {
     },
     /// List connected peers
     Peers,
+    /// List conversations (requires an active chat session)
+    Conversations {
+        /// Show archived conversations instead of active ones
+        #[arg(long)]
+        archived: bool,
+    },
+    /// List starred messages across conversations (requires an active chat session)
+    Starred,
+    /// List messages stuck in the outbox (requires an active chat session)
+    Outbox,
     /// Set display name
     Nick {
         /// New display name
@@ -66,6 +94,42 @@ enum Commands {
         #[command(subcommand)]
         action: UserCommands,
     },
+    /// Message history management
+    History {
+        #[command(subcommand)]
+        action: HistoryCommands,
+    },
+    /// Local contact roster management
+    Contacts {
+        #[command(subcommand)]
+        action: ContactCommands,
+    },
+    /// Read or change individual config settings without hand-editing
+    /// config.toml
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommands,
+    },
+    /// Move an identity (keys, user id, display name, contacts) to or from
+    /// another machine as a password-protected archive
+    Identity {
+        #[command(subcommand)]
+        action: IdentityCommands,
+    },
+    /// Run as a relay, forwarding encrypted frames between peers who can't
+    /// connect directly. The relay never sees decrypted content.
+    Relay {
+        /// Port to listen on
+        #[arg(short, long, default_value = "6000")]
+        port: u16,
+    },
+    /// Run a rendezvous/directory server: peers publish their signed address
+    /// here so others can resolve them by user ID (see `rus connect --id`).
+    Rendezvous {
+        /// Port to listen on
+        #[arg(short, long, default_value = "7000")]
+        port: u16,
+    },
 }
 
 #[derive(Subcommand)]
@@ -80,6 +144,40 @@ enum PathCommands {
     Status,
 }
 
+#[derive(Subcommand)]
+enum ContactCommands {
+    /// Add or update a contact
+    Add {
+        /// Peer ID
+        peer_id: String,
+        /// Display name to show instead of the peer's advertised name
+        name: String,
+        /// Email address
+        #[arg(long)]
+        email: Option<String>,
+        /// Public key, if known ahead of connecting
+        #[arg(long)]
+        public_key: Option<String>,
+        /// Free-text notes
+        #[arg(long)]
+        notes: Option<String>,
+    },
+    /// List every contact
+    List,
+    /// Remove a contact
+    Remove {
+        /// Peer ID
+        peer_id: String,
+    },
+    /// Rename an existing contact
+    Rename {
+        /// Peer ID
+        peer_id: String,
+        /// New display name
+        name: String,
+    },
+}
+
 #[derive(Subcommand)]
 enum UserCommands {
     /// List all registered users
@@ -96,13 +194,104 @@ enum UserCommands {
     },
     /// Show current user
     Current,
+    /// Export every registered user to one JSON file per user in <dir>
+    ExportAll {
+        /// Destination directory
+        dir: String,
+    },
+    /// Import user JSON files from <dir>, overwriting matching user IDs
+    Import {
+        /// Source directory
+        dir: String,
+    },
+    /// Remove users inactive longer than the given duration (e.g. 90d)
+    Prune {
+        /// Inactivity threshold, e.g. "90d"
+        #[arg(long)]
+        inactive: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Print the current value of one setting
+    Get {
+        /// Setting name, e.g. default_port
+        key: String,
+    },
+    /// Validate and write a new value for one setting
+    Set {
+        /// Setting name, e.g. default_port
+        key: String,
+        /// New value - "null" clears an optional setting
+        value: String,
+    },
+    /// List every top-level setting and its current value
+    List,
+    /// Encrypt the config file at rest with a master passphrase
+    Encrypt {
+        /// Passphrase to encrypt with - prompted if omitted. Can also come
+        /// from RUSTALK_CONFIG_PASSPHRASE for unattended use.
+        passphrase: Option<String>,
+    },
+    /// Decrypt the config file back to plain TOML
+    Decrypt,
+}
+
+#[derive(Subcommand)]
+enum IdentityCommands {
+    /// Write the current identity and contacts to a password-protected file
+    Export {
+        /// Archive path to write
+        file: std::path::PathBuf,
+        /// Passphrase to protect the archive with - prompted if omitted
+        passphrase: Option<String>,
+    },
+    /// Restore an identity and contacts from an archive made by `export`
+    Import {
+        /// Archive path to read
+        file: std::path::PathBuf,
+        /// Archive's passphrase - prompted if omitted
+        passphrase: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum HistoryCommands {
+    /// Export message history to a file
+    Export {
+        /// Output format
+        #[arg(long, default_value = "json")]
+        format: String,
+        /// Only export messages to/from this peer ID
+        #[arg(long)]
+        peer: Option<String>,
+        /// Destination file
+        #[arg(long)]
+        out: String,
+    },
+    /// Import a JSON history archive previously written by `export`
+    Import {
+        /// Source file
+        #[arg(long)]
+        file: String,
+    },
+    /// Apply the retention policy from the config (history_max_* options) now,
+    /// instead of waiting for the background pruning task's next run
+    Prune,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    env_logger::init();
-
     let cli = Cli::parse();
+    if let Some(config_dir) = cli.config_dir.clone() {
+        reach::config::set_config_dir_override(config_dir);
+    } else if let Some(profile) = cli.profile.as_deref() {
+        reach::config::set_config_dir_override(reach::config::profile_dir(profile)?);
+    }
+
+    reach::logging::init();
+    install_panic_hook();
 
     match cli.command {
         Some(Commands::Setup) => {
@@ -118,9 +307,13 @@ async fn main() -> Result<()> {
                 }
             }
         }
-        Some(Commands::Chat { port }) => {
-            println!("🚀 Starting chat session on port {}...", port);
-            match CliOperations::start_chat_session(port).await {
+        Some(Commands::Chat { port, bind }) => {
+            if port == 0 {
+                println!("🚀 Starting chat session on an OS-assigned port...");
+            } else {
+                println!("🚀 Starting chat session on port {}...", port);
+            }
+            match CliOperations::start_chat_session(port, bind.as_deref()).await {
                 Ok(session_manager) => {
                     start_interactive_chat(session_manager).await?;
                 }
@@ -130,9 +323,24 @@ async fn main() -> Result<()> {
                 }
             }
         }
-        Some(Commands::Connect { address, port }) => {
+        Some(Commands::Connect { address, id, port }) => {
+            let address = match (address, id) {
+                (Some(address), _) => address,
+                (None, Some(id)) => match resolve_by_id(&id).await {
+                    Ok(address) => address,
+                    Err(e) => {
+                        eprintln!("❌ Failed to resolve user ID {}: {}", id, e);
+                        std::process::exit(1);
+                    }
+                },
+                (None, None) => {
+                    eprintln!("❌ Provide either an address or --id <uuid>");
+                    std::process::exit(1);
+                }
+            };
+
             println!("🔗 Starting chat and connecting to {}...", address);
-            match CliOperations::start_chat_session(port).await {
+            match CliOperations::start_chat_session(port, None).await {
                 Ok(session_manager) => {
                     if let Err(e) = session_manager.connect_to_peer(&address).await {
                         eprintln!("⚠️  Failed to connect to {}: {}", address, e);
@@ -162,6 +370,23 @@ async fn main() -> Result<()> {
             println!("👥 Peer list functionality requires an active chat session");
             println!("💡 Use 'rus chat' to see connected peers");
         }
+        Some(Commands::Conversations { archived }) => {
+            if archived {
+                println!("📥 Archived conversation listing requires an active chat session");
+                println!("💡 Use 'rus chat' then type '/archive <peer_id>' to archive one");
+            } else {
+                println!("💬 Conversation listing functionality requires an active chat session");
+                println!("💡 Use 'rus chat' to see active conversations");
+            }
+        }
+        Some(Commands::Starred) => {
+            println!("⭐ Starred message review requires an active chat session");
+            println!("💡 Use 'rus chat' then type '/star <message_id>' to flag one");
+        }
+        Some(Commands::Outbox) => {
+            println!("📭 Outbox listing requires an active chat session");
+            println!("💡 Use 'rus chat' then type '/outbox' to see queued, retrying, and failed messages");
+        }
         Some(Commands::Nick { name }) => {
             println!("👤 Nickname functionality requires an active chat session");
             println!("💡 Use 'rus chat' then type '/nick {}' in the chat", name);
@@ -249,7 +474,163 @@ async fn main() -> Result<()> {
                     std::process::exit(1);
                 }
             },
+            UserCommands::ExportAll { dir } => match export_all_users(&dir) {
+                Ok(()) => {}
+                Err(e) => {
+                    eprintln!("❌ Failed to export users: {}", e);
+                    std::process::exit(1);
+                }
+            },
+            UserCommands::Import { dir } => match import_users(&dir) {
+                Ok(()) => {}
+                Err(e) => {
+                    eprintln!("❌ Failed to import users: {}", e);
+                    std::process::exit(1);
+                }
+            },
+            UserCommands::Prune { inactive } => match prune_inactive_users(&inactive) {
+                Ok(()) => {}
+                Err(e) => {
+                    eprintln!("❌ Failed to prune users: {}", e);
+                    std::process::exit(1);
+                }
+            },
+        },
+        Some(Commands::History { action }) => match action {
+            HistoryCommands::Export { format, peer, out } => {
+                match CliOperations::export_history(&format, peer, std::path::Path::new(&out)).await {
+                    Ok(count) => println!("✅ Exported {} message(s) to {}", count, out),
+                    Err(e) => {
+                        eprintln!("❌ Failed to export history: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            HistoryCommands::Import { file } => {
+                match CliOperations::import_history(std::path::Path::new(&file)).await {
+                    Ok(count) => println!("✅ Imported {} new message(s) from {}", count, file),
+                    Err(e) => {
+                        eprintln!("❌ Failed to import history: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            HistoryCommands::Prune => match CliOperations::prune_history().await {
+                Ok(count) => println!("🧹 Pruned {} message(s) from history", count),
+                Err(e) => {
+                    eprintln!("❌ Failed to prune history: {}", e);
+                    std::process::exit(1);
+                }
+            },
         },
+        Some(Commands::Contacts { action }) => match action {
+            ContactCommands::Add { peer_id, name, email, public_key, notes } => {
+                match add_contact(&peer_id, &name, email, public_key, notes) {
+                    Ok(()) => println!("✅ Saved contact '{}' for peer {}", name, peer_id),
+                    Err(e) => {
+                        eprintln!("❌ Failed to save contact: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            ContactCommands::List => {
+                if let Err(e) = list_contacts() {
+                    eprintln!("❌ Failed to list contacts: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            ContactCommands::Remove { peer_id } => match remove_contact(&peer_id) {
+                Ok(()) => println!("✅ Removed contact for peer {}", peer_id),
+                Err(e) => {
+                    eprintln!("❌ Failed to remove contact: {}", e);
+                    std::process::exit(1);
+                }
+            },
+            ContactCommands::Rename { peer_id, name } => match rename_contact(&peer_id, name.clone()) {
+                Ok(()) => println!("✅ Renamed contact for peer {} to '{}'", peer_id, name),
+                Err(e) => {
+                    eprintln!("❌ Failed to rename contact: {}", e);
+                    std::process::exit(1);
+                }
+            },
+        },
+        Some(Commands::Config { action }) => match action {
+            ConfigCommands::Get { key } => match CliOperations::get_config_value(&key).await {
+                Ok(value) => println!("{} = {}", key, value),
+                Err(e) => {
+                    eprintln!("❌ {}", e);
+                    std::process::exit(1);
+                }
+            },
+            ConfigCommands::Set { key, value } => match CliOperations::set_config_value(&key, &value).await {
+                Ok(()) => println!("✅ Set {} = {}", key, value),
+                Err(e) => {
+                    eprintln!("❌ Failed to set {}: {}", key, e);
+                    std::process::exit(1);
+                }
+            },
+            ConfigCommands::List => match CliOperations::list_config_values().await {
+                Ok(values) => {
+                    for (key, value) in values {
+                        println!("{} = {}", key, value);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("❌ Failed to list config: {}", e);
+                    std::process::exit(1);
+                }
+            },
+            ConfigCommands::Encrypt { passphrase } => match CliOperations::encrypt_config(passphrase).await {
+                Ok(()) => println!("🔒 Config encrypted. Set RUSTALK_CONFIG_PASSPHRASE to unlock it unattended."),
+                Err(e) => {
+                    eprintln!("❌ Failed to encrypt config: {}", e);
+                    std::process::exit(1);
+                }
+            },
+            ConfigCommands::Decrypt => match CliOperations::decrypt_config().await {
+                Ok(()) => println!("🔓 Config decrypted."),
+                Err(e) => {
+                    eprintln!("❌ Failed to decrypt config: {}", e);
+                    std::process::exit(1);
+                }
+            },
+        },
+        Some(Commands::Identity { action }) => match action {
+            IdentityCommands::Export { file, passphrase } => {
+                match CliOperations::export_identity(&file, passphrase).await {
+                    Ok(()) => println!("📦 Identity exported to {}", file.display()),
+                    Err(e) => {
+                        eprintln!("❌ Failed to export identity: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            IdentityCommands::Import { file, passphrase } => {
+                match CliOperations::import_identity(&file, passphrase).await {
+                    Ok(message) => println!("📥 {}", message),
+                    Err(e) => {
+                        eprintln!("❌ Failed to import identity: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+        },
+        Some(Commands::Relay { port }) => {
+            println!("🔀 Starting relay on port {}...", port);
+            println!("   Forwards encrypted frames between peers; never holds decryption keys.");
+            if let Err(e) = reach::RelayServer::new(port).run().await {
+                eprintln!("❌ Relay failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Rendezvous { port }) => {
+            println!("📇 Starting rendezvous/directory server on port {}...", port);
+            println!("   Peers publish their signed address here to be resolved by user ID.");
+            if let Err(e) = reach::DirectoryServer::new(port).run().await {
+                eprintln!("❌ Rendezvous server failed: {}", e);
+                std::process::exit(1);
+            }
+        }
         None => {
             show_interactive_help().await;
         }
@@ -258,10 +639,47 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// Resolves a user ID to a dialable address via `Config::directory_address`,
+/// for `rus connect --id <uuid>` - see `reach::directory::lookup`.
+async fn resolve_by_id(id: &str) -> Result<String> {
+    let user_id = uuid::Uuid::parse_str(id).map_err(|_| anyhow!("'{}' is not a valid UUID", id))?;
+    let directory_address = reach::config::load_config_cached()?
+        .directory_address
+        .ok_or_else(|| anyhow!("No directory server configured (Config::directory_address)"))?;
+
+    let entry = reach::directory::lookup(&directory_address, user_id).await?;
+    entry
+        .endpoints
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("User {} has no published endpoints", user_id))
+}
+
+/// Prints panic messages to a flushed stderr instead of letting them race
+/// buffered stdout output from the chat loop.
+///
+/// Note: `rus` has no raw-mode/alternate-screen UI - `crossterm` and
+/// `ratatui` are declared dependencies in this workspace but unused here -
+/// so there's no terminal state for a panic hook to restore. If a TUI
+/// front-end is ever added, its setup should install a `Drop` guard there
+/// that disables raw mode and leaves the alternate screen before this hook
+/// runs, the same way `SessionManager::end_session` is the one place that
+/// sends a graceful network goodbye.
+fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        use std::io::Write;
+        let _ = std::io::stdout().flush();
+        default_hook(info);
+        let _ = std::io::stderr().flush();
+    }));
+}
+
 async fn start_interactive_chat(session_manager: SessionManager) -> Result<()> {
-    use std::io::{self, Write};
+    use std::io::Write;
+    use tokio::io::{AsyncBufReadExt, BufReader};
 
-    println!("💬 Chat session started! Type '/help' for commands or '/quit' to exit");
+    println!("💬 Chat session started! Type '/help' for commands, '/watch' to stream session events, or '/quit' to exit");
 
     if let Some((session_id, port, peer_count)) = session_manager.get_session_info().await {
         println!(
@@ -270,19 +688,35 @@ async fn start_interactive_chat(session_manager: SessionManager) -> Result<()> {
         );
     }
 
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+
     loop {
         print!("> ");
-        io::stdout().flush()?;
+        std::io::stdout().flush()?;
 
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
+        // Reading the next line and waiting for Ctrl+C race each other, so an
+        // interrupt ends the session through the same graceful shutdown as
+        // `/quit` instead of the process just dying mid-read.
+        let input = tokio::select! {
+            biased;
+            _ = tokio::signal::ctrl_c() => {
+                println!();
+                break;
+            }
+            line = lines.next_line() => match line? {
+                Some(line) => line,
+                None => break,
+            },
+        };
         let input = input.trim();
 
         if input.is_empty() {
             continue;
         }
 
-        if input.starts_with('/') {
+        if input == "/watch" {
+            watch_session_events(&session_manager).await;
+        } else if input.starts_with('/') {
             match handle_chat_command(&session_manager, input).await {
                 Ok(should_quit) => {
                     if should_quit {
@@ -308,76 +742,390 @@ async fn start_interactive_chat(session_manager: SessionManager) -> Result<()> {
     Ok(())
 }
 
+/// `/watch` mode: prints `SessionEvent`s as they happen instead of waiting
+/// for the next typed command - see `SessionManager::subscribe`. Runs until
+/// Ctrl+C, then returns to the normal prompt.
+async fn watch_session_events(session_manager: &SessionManager) {
+    use reach::SessionEvent;
+
+    println!("👀 Watching session events... press Ctrl+C to stop");
+    let mut events = session_manager.subscribe();
+
+    loop {
+        tokio::select! {
+            biased;
+            _ = tokio::signal::ctrl_c() => {
+                println!();
+                break;
+            }
+            event = events.recv() => match event {
+                Ok(SessionEvent::MessageReceived(message)) => {
+                    println!("📨 Message received from {}: {}", message.sender_id, message.render_content());
+                }
+                Ok(SessionEvent::PeerConnected(peer)) => {
+                    println!("🔗 Peer connected: {} ({})", peer.display_name, peer.id);
+                }
+                Ok(SessionEvent::PeerDisconnected(peer_id)) => {
+                    println!("🔌 Peer disconnected: {}", peer_id);
+                }
+                Ok(SessionEvent::DeliveryUpdated { message_id, read_at }) => {
+                    println!("✅ Message {} read at {}", message_id, read_at);
+                }
+                Ok(SessionEvent::MessageDelivered { message_id }) => {
+                    println!("📬 Message {} delivered", message_id);
+                }
+                Ok(SessionEvent::ConfigReloaded { changed_fields }) => {
+                    println!("🔧 Config reloaded ({})", changed_fields.join(", "));
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    println!("⚠️  Missed {} event(s) (fell behind)", skipped);
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            },
+        }
+    }
+
+    println!("👀 Stopped watching");
+}
+
 async fn handle_chat_command(session_manager: &SessionManager, command: &str) -> Result<bool> {
-    let parts: Vec<&str> = command.split_whitespace().collect();
+    use reach::commands::CommandResult;
+
+    let command = match reach::commands::parse(command) {
+        Ok(command) => command,
+        Err(e) => {
+            println!("❌ {}", e);
+            return Ok(false);
+        }
+    };
+
+    let result = reach::commands::execute(command, session_manager).await;
+    let should_quit = matches!(result, CommandResult::Quit);
+    render_command_result(result).await;
+    Ok(should_quit)
+}
 
-    match parts.get(0) {
-        Some(&"/help") => {
+/// Renders a `reach::commands::CommandResult` as plain-text console output.
+/// This is the only place `rus` knows how a command result looks on screen -
+/// a future TUI or the napi bindings would render the same `CommandResult`
+/// their own way instead of duplicating this formatting.
+async fn render_command_result(result: reach::commands::CommandResult) {
+    use reach::commands::CommandResult;
+
+    match result {
+        CommandResult::Help(lines) => {
             println!("💡 Available commands:");
-            println!("   /connect <ip:port>  - Connect to a peer");
-            println!("   /peers              - List connected peers");
-            println!("   /info               - Show session info");
-            println!("   /history [limit]    - Show recent messages");
-            println!("   /quit               - Exit chat");
-            println!("   /help               - Show this help");
+            for (usage, description) in lines {
+                println!("   {:<40} - {}", usage, description);
+            }
             println!();
             println!("💬 Just type normally to send messages!");
         }
-        Some(&"/connect") => {
-            if let Some(address) = parts.get(1) {
-                match session_manager.connect_to_peer(address).await {
-                    Ok(()) => println!("✅ Connected to {}", address),
-                    Err(e) => eprintln!("❌ Failed to connect: {}", e),
-                }
+        CommandResult::Connected { address } => println!("✅ Connected to {}", address),
+        CommandResult::PeerList(peers) => {
+            if peers.is_empty() {
+                println!("👤 No connected peers");
             } else {
-                println!("❌ Usage: /connect <ip:port>");
+                let config = reach::config::load_config_cached().unwrap_or_default();
+                println!("👥 Connected peers ({}):", peers.len());
+                for (peer, unread, label) in peers {
+                    let name = label.as_deref().unwrap_or(&peer.display_name);
+                    let badge = reach::theme::badge(&peer.public_key, name, &config);
+                    let unread_badge = if unread > 0 {
+                        format!(" [{} unread]", unread)
+                    } else {
+                        String::new()
+                    };
+                    println!("   • {} ({}){}", badge, peer.id, unread_badge);
+                }
             }
         }
-        Some(&"/peers") => {
-            let peers = session_manager.get_active_peers().await;
-            if peers.is_empty() {
-                println!("� No connected peers");
+        CommandResult::ConversationList(conversations) => {
+            if conversations.is_empty() {
+                println!("💬 No conversations yet");
             } else {
-                println!("👥 Connected peers ({}):", peers.len());
-                for peer in peers {
-                    println!("   • {} ({})", peer.display_name, peer.id);
+                println!("💬 Conversations ({}):", conversations.len());
+                for conversation in conversations {
+                    let draft = if conversation.draft.is_empty() {
+                        String::new()
+                    } else {
+                        " (draft pending)".to_string()
+                    };
+                    let muted = if conversation.settings.muted { " 🔕" } else { "" };
+                    println!(
+                        "   • {} - {} message(s), {} unread{}{}",
+                        conversation.peer_id,
+                        conversation.history.len(),
+                        conversation.unread_count,
+                        draft,
+                        muted
+                    );
                 }
             }
         }
-        Some(&"/info") => {
-            if let Some((session_id, port, peer_count)) = session_manager.get_session_info().await {
-                println!("📡 Session Info:");
-                println!("   ID: {}", session_id);
-                println!("   Port: {}", port);
-                println!("   Connected peers: {}", peer_count);
+        CommandResult::PeerCapabilities { peer_id, capabilities } => {
+            let names = capabilities.names();
+            if names.is_empty() {
+                println!("🧩 Peer {} advertised no capabilities", peer_id);
+            } else {
+                println!("🧩 Peer {} supports: {}", peer_id, names.join(", "));
             }
         }
-        Some(&"/history") => {
-            let limit = parts
-                .get(1)
-                .and_then(|s| s.parse::<usize>().ok())
-                .unwrap_or(10);
-
-            let messages = session_manager.list_recent_messages(limit).await;
+        CommandResult::SessionInfo { session_id, port, peer_count, topic, stats } => {
+            println!("📡 Session Info:");
+            println!("   ID: {}", session_id);
+            println!("   Port: {}", port);
+            println!("   Connected peers: {}", peer_count);
+            println!("   Topic: {}", topic.as_deref().unwrap_or("(none)"));
+            println!("   Active for: {}s", stats.active_duration_secs);
+            println!(
+                "   Messages: {} sent, {} received ({} bytes sent, {} bytes received)",
+                stats.messages_sent, stats.messages_received, stats.bytes_sent, stats.bytes_received
+            );
+            println!("   Peer churn: {} connected, {} disconnected", stats.peers_connected, stats.peers_disconnected);
+        }
+        CommandResult::Topic { topic: Some(topic) } => println!("📌 Topic: {}", topic),
+        CommandResult::Topic { topic: None } => println!("📌 No topic set"),
+        CommandResult::HistoryPage(messages) => {
             if messages.is_empty() {
                 println!("📜 No message history");
             } else {
+                let config = reach::config::load_config_cached().unwrap_or_default();
                 println!("📜 Recent messages ({}):", messages.len());
                 for msg in messages {
-                    println!("   [{}] {}: {}", msg.timestamp, msg.sender(), msg.content);
+                    let star = if msg.starred { "⭐ " } else { "" };
+                    let pin = if msg.pinned { "📌 " } else { "" };
+                    let late = if msg.delivered_late { " (delivered late)" } else { "" };
+                    let edited = if msg.edited { " (edited)" } else { "" };
+                    let forwarded = match &msg.forwarded_from {
+                        Some(original) => format!(" (forwarded from {})", original.sender_name),
+                        None => String::new(),
+                    };
+                    let read = match msg.read_at {
+                        Some(read_at) => format!(" (read at {})", reach::format_timestamp(read_at, &config)),
+                        None => String::new(),
+                    };
+                    let delivery = if msg.sender_id == config.identity.user_id {
+                        match msg.delivery_status {
+                            reach::DeliveryStatus::Sent => "",
+                            reach::DeliveryStatus::Delivered => " (delivered)",
+                            reach::DeliveryStatus::Failed => " (failed to deliver)",
+                        }
+                    } else {
+                        ""
+                    };
+                    let sender = reach::theme::badge(&msg.sender_id.to_string(), msg.sender(), &config);
+                    let rendered = msg.render_content();
+                    let content = if msg.retracted { "[message deleted]" } else { &rendered };
+                    let reactions = if msg.reactions.is_empty() {
+                        String::new()
+                    } else {
+                        let mut summary: Vec<String> = msg
+                            .reactions
+                            .iter()
+                            .map(|(emoji, reactors)| format!("{}x{}", emoji, reactors.len()))
+                            .collect();
+                        summary.sort();
+                        format!(" [{}]", summary.join(" "))
+                    };
+                    println!(
+                        "   {}{}[{}] ({}) {}: {}{}{}{}{}{}{}",
+                        star,
+                        pin,
+                        reach::format_timestamp(msg.timestamp, &config),
+                        msg.id,
+                        sender,
+                        content,
+                        edited,
+                        late,
+                        forwarded,
+                        read,
+                        delivery,
+                        reactions
+                    );
+                }
+            }
+        }
+        CommandResult::Starred { message_id } => println!("⭐ Starred message {}", message_id),
+        CommandResult::Pinned { message_id } => println!("📌 Pinned message {}", message_id),
+        CommandResult::Unpinned { message_id } => println!("📌 Unpinned message {}", message_id),
+        CommandResult::PinnedList(messages) => {
+            if messages.is_empty() {
+                println!("📌 No pinned messages");
+            } else {
+                let config = reach::config::load_config_cached().unwrap_or_default();
+                println!("📌 Pinned messages ({}):", messages.len());
+                for msg in messages {
+                    let sender = reach::theme::badge(&msg.sender_id.to_string(), msg.sender(), &config);
+                    let rendered = msg.render_content();
+                    let content = if msg.retracted { "[message deleted]" } else { &rendered };
+                    println!(
+                        "   [{}] ({}) {}: {}",
+                        reach::format_timestamp(msg.timestamp, &config),
+                        msg.id,
+                        sender,
+                        content
+                    );
+                }
+            }
+        }
+        CommandResult::TemplateSaved { name } => println!("✅ Saved template '{}'", name),
+        CommandResult::TemplateSent { name, content } => {
+            println!("📤 Sent template '{}': {}", name, content)
+        }
+        CommandResult::Archived { peer_id } => println!("📥 Archived conversation with {}", peer_id),
+        CommandResult::Unarchived { peer_id } => println!("📤 Unarchived conversation with {}", peer_id),
+        CommandResult::PendingList(peers) => {
+            if peers.is_empty() {
+                println!("🔔 No connections awaiting approval");
+            } else {
+                println!("🔔 Connections awaiting approval ({}):", peers.len());
+                for peer in peers {
+                    println!("   • {} ({})", peer.display_name, peer.id);
+                }
+            }
+        }
+        CommandResult::Accepted { peer_id } => println!("✅ Accepted connection from {}", peer_id),
+        CommandResult::Rejected { peer_id } => println!("🚫 Rejected connection from {}", peer_id),
+        CommandResult::Translating { source_lang, target_lang } => {
+            println!("🌐 Translating {} -> {}", source_lang, target_lang)
+        }
+        CommandResult::TranslationDisabled => println!("🌐 Translation disabled"),
+        CommandResult::Published => println!("✅ Published to directory"),
+        CommandResult::LookupResult(entry) => {
+            println!("📇 {} -> {}", entry.user_id, entry.endpoints.join(", "))
+        }
+        CommandResult::Dnd { enabled } => {
+            if enabled {
+                println!("🔕 Do Not Disturb is on (mentions still notify)");
+            } else {
+                println!("🔔 Do Not Disturb is off");
+            }
+        }
+        CommandResult::ReadOnly { enabled } => {
+            if enabled {
+                println!("🔒 Read-only mode is on");
+            } else {
+                println!("🔓 Read-only mode is off");
+            }
+        }
+        CommandResult::Stats(stats) => {
+            println!("📊 Network stats:");
+            println!(
+                "   global: sent {}B/{} msgs, received {}B/{} msgs, {} reconnects",
+                stats.global.bytes_sent,
+                stats.global.messages_sent,
+                stats.global.bytes_received,
+                stats.global.messages_received,
+                stats.global.reconnects
+            );
+            match stats.global.average_rtt_ms {
+                Some(rtt) => println!("   average RTT: {:.1}ms ({} samples)", rtt, stats.global.rtt_samples),
+                None => println!("   average RTT: n/a (no pings yet)"),
+            }
+            if stats.per_peer.is_empty() {
+                println!("   (no per-peer activity yet)");
+            } else {
+                for (peer_id, peer_stats) in &stats.per_peer {
+                    println!(
+                        "   {}: sent {}B/{} msgs, received {}B/{} msgs, {} reconnects",
+                        peer_id,
+                        peer_stats.bytes_sent,
+                        peer_stats.messages_sent,
+                        peer_stats.bytes_received,
+                        peer_stats.messages_received,
+                        peer_stats.reconnects
+                    );
+                }
+            }
+        }
+        CommandResult::Broadcast { delivered } => {
+            println!("📣 Broadcast sent to {} peer(s)", delivered)
+        }
+        CommandResult::OutboxList(entries) => {
+            if entries.is_empty() {
+                println!("📭 Outbox is empty");
+            } else {
+                println!("📭 Outbox ({}):", entries.len());
+                for entry in entries {
+                    println!(
+                        "   [{}] -> {} ({:?}, {} attempts): {}",
+                        entry.id, entry.peer_id, entry.status, entry.attempts, entry.content
+                    );
+                    println!("       last error: {}", entry.last_error);
+                }
+            }
+        }
+        CommandResult::OutboxRetried { id } => println!("🔁 Retried outbox entry {}", id),
+        CommandResult::OutboxDiscarded { id } => println!("🗑️  Discarded outbox entry {}", id),
+        CommandResult::NotifySoundSet { sound } => {
+            println!("🔔 Notification sound set to '{}'", sound.as_deref().unwrap_or("none"))
+        }
+        CommandResult::NotifyPeerSoundSet { peer_id, sound } => {
+            println!("🔔 Notification sound for {} set to '{}'", peer_id, sound)
+        }
+        CommandResult::Muted { peer_id, until } => {
+            let config = reach::config::load_config_cached().unwrap_or_default();
+            println!("🔇 Muted {} until {}", peer_id, reach::format_timestamp(until, &config))
+        }
+        CommandResult::Unmuted { peer_id } => println!("🔔 Unmuted {}", peer_id),
+        CommandResult::TypingSent { peer_id } => println!("⌨️  Typing notice sent to {}", peer_id),
+        CommandResult::FileOffered { peer_id, transfer_id } => {
+            println!("📤 Offered file to {} as transfer {}", peer_id, transfer_id)
+        }
+        CommandResult::FileAccepted { transfer_id, dest_path } => {
+            println!("📥 Accepted transfer {}, writing to {}", transfer_id, dest_path)
+        }
+        CommandResult::FileRejected { transfer_id } => println!("🚫 Rejected transfer {}", transfer_id),
+        CommandResult::ConversationRead { peer_id, count } => {
+            println!("✅ Marked {} message(s) from {} as read", count, peer_id)
+        }
+        CommandResult::MessageEdited { message_id } => println!("✏️  Edited message {}", message_id),
+        CommandResult::MessageRetracted { message_id } => println!("🗑️  Retracted message {}", message_id),
+        CommandResult::Reacted { message_id, emoji } => {
+            println!("{} Reacted to message {}", emoji, message_id)
+        }
+        CommandResult::Unreacted { message_id, emoji } => {
+            println!("Removed {} reaction from message {}", emoji, message_id)
+        }
+        CommandResult::Scheduled { id, deliver_at } => {
+            let config = reach::config::load_config_cached().unwrap_or_default();
+            println!("⏰ Scheduled [{}] for {}", id, reach::format_timestamp(deliver_at, &config))
+        }
+        CommandResult::ScheduledList(entries) => {
+            if entries.is_empty() {
+                println!("⏰ No scheduled messages");
+            } else {
+                let config = reach::config::load_config_cached().unwrap_or_default();
+                println!("⏰ Scheduled ({}):", entries.len());
+                for entry in entries {
+                    println!(
+                        "   [{}] at {}: {}",
+                        entry.id,
+                        reach::format_timestamp(entry.deliver_at, &config),
+                        entry.content
+                    );
                 }
             }
         }
-        Some(&"/quit") | Some(&"/exit") => {
-            return Ok(true);
+        CommandResult::Unscheduled { id } => println!("🚫 Cancelled scheduled message {}", id),
+        CommandResult::MarkdownSent => println!("📝 Markdown message sent"),
+        CommandResult::CodeSent => println!("💻 Code snippet sent"),
+        CommandResult::ImageSent { peer_id } => println!("🖼️  Image sent to {}", peer_id),
+        CommandResult::Forwarded { message_id, peer_id } => {
+            println!("↪️  Forwarded message {} to {}", message_id, peer_id)
         }
-        _ => {
-            println!("❌ Unknown command: {}", command);
-            println!("💡 Type '/help' for available commands");
+        CommandResult::Verified { message_id, valid } => {
+            if valid {
+                println!("✅ Message {} is signed by its claimed sender", message_id)
+            } else {
+                println!("⚠️  Message {} failed signature verification", message_id)
+            }
         }
+        CommandResult::LogLevel { level } => println!("🪵 Log level is now '{}'", level),
+        CommandResult::Quit => {}
+        CommandResult::Error(message) => eprintln!("❌ {}", message),
     }
-
-    Ok(false)
 }
 
 async fn show_interactive_help() {
@@ -419,6 +1167,52 @@ async fn show_interactive_help() {
     println!("   • User management and session persistence");
 }
 
+fn add_contact(
+    peer_id: &str,
+    name: &str,
+    email: Option<String>,
+    public_key: Option<String>,
+    notes: Option<String>,
+) -> Result<()> {
+    reach::ContactBook::new()?.add(peer_id, name, email, public_key, notes)?;
+    Ok(())
+}
+
+fn remove_contact(peer_id: &str) -> Result<()> {
+    reach::ContactBook::new()?.remove(peer_id)
+}
+
+fn rename_contact(peer_id: &str, name: String) -> Result<()> {
+    reach::ContactBook::new()?.rename(peer_id, name)?;
+    Ok(())
+}
+
+fn list_contacts() -> Result<()> {
+    let contacts = reach::ContactBook::new()?.list()?;
+
+    if contacts.is_empty() {
+        println!("📇 No contacts found.");
+        println!("💡 Run 'rus contacts add <peer_id> <name>' to add one.");
+        return Ok(());
+    }
+
+    println!("📇 Contacts ({} total):", contacts.len());
+    println!();
+
+    for contact in contacts {
+        let verified = if contact.verified { " ✅ verified" } else { "" };
+        println!("• {} ({}){}", contact.display_name, contact.peer_id, verified);
+        if let Some(email) = &contact.email {
+            println!("   📧 {}", email);
+        }
+        if let Some(notes) = &contact.notes {
+            println!("   📝 {}", notes);
+        }
+    }
+
+    Ok(())
+}
+
 fn list_all_users() -> Result<()> {
     let registry = UserRegistry::load()?;
     let users = registry.list_users();
@@ -496,3 +1290,32 @@ fn remove_user(user_id: &str) -> Result<()> {
 
     Ok(())
 }
+
+fn export_all_users(dir: &str) -> Result<()> {
+    let registry = UserRegistry::load()?;
+    let count = registry.export_all(std::path::Path::new(dir))?;
+    println!("✅ Exported {} user(s) to {}", count, dir);
+    Ok(())
+}
+
+fn import_users(dir: &str) -> Result<()> {
+    let mut registry = UserRegistry::load()?;
+    let count = registry.import_from(std::path::Path::new(dir))?;
+    println!("✅ Imported {} user(s) from {}", count, dir);
+    Ok(())
+}
+
+fn prune_inactive_users(spec: &str) -> Result<()> {
+    let days = parse_inactive_days(spec)?;
+    let mut registry = UserRegistry::load()?;
+    let removed = registry.prune_inactive(chrono::Duration::days(days))?;
+    println!("🧹 Pruned {} inactive user(s) (older than {})", removed, spec);
+    Ok(())
+}
+
+fn parse_inactive_days(spec: &str) -> Result<i64> {
+    spec.strip_suffix('d')
+        .ok_or_else(|| anyhow!("Expected a duration like '90d', got '{}'", spec))?
+        .parse::<i64>()
+        .map_err(|_| anyhow!("Expected a duration like '90d', got '{}'", spec))
+}
@@ -2,10 +2,11 @@ use anyhow::{Result, anyhow};
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::process::Command;
 
 #[cfg(windows)]
 use std::os::windows::process::CommandExt;
+#[cfg(windows)]
+use std::process::Command;
 
 pub struct PathManager {
     binary_path: PathBuf,
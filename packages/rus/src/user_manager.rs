@@ -3,6 +3,7 @@ use reach::get_config_file;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
+use std::path::Path;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserInfo {
@@ -14,21 +15,12 @@ pub struct UserInfo {
     pub last_active: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct UserRegistry {
     pub users: HashMap<String, UserInfo>,
     pub current_user: Option<String>,
 }
 
-impl Default for UserRegistry {
-    fn default() -> Self {
-        UserRegistry {
-            users: HashMap::new(),
-            current_user: None,
-        }
-    }
-}
-
 impl UserRegistry {
     pub fn load() -> Result<Self> {
         let config_file = get_config_file()?;
@@ -88,7 +80,7 @@ impl UserRegistry {
     pub fn remove_user(&mut self, user_id: &str) -> Result<()> {
         if self.users.remove(user_id).is_some() {
             // If we removed the current user, clear the current user
-            if self.current_user.as_ref().map(|s| s.as_str()) == Some(user_id) {
+            if self.current_user.as_deref() == Some(user_id) {
                 self.current_user = None;
             }
             self.save()
@@ -100,12 +92,84 @@ impl UserRegistry {
     pub fn get_user(&self, user_id: &str) -> Option<&UserInfo> {
         self.users.get(user_id)
     }
+
+    /// Writes one JSON file per registered user into `dir`, for admins
+    /// migrating or backing up identities across lab machines.
+    pub fn export_all(&self, dir: &Path) -> Result<usize> {
+        fs::create_dir_all(dir)
+            .map_err(|e| anyhow!("Failed to create export directory: {}", e))?;
+
+        for user in self.users.values() {
+            let path = dir.join(format!("{}.json", user.user_id));
+            let json = serde_json::to_string_pretty(user)
+                .map_err(|e| anyhow!("Failed to serialize user {}: {}", user.user_id, e))?;
+            fs::write(&path, json)
+                .map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))?;
+        }
+
+        Ok(self.users.len())
+    }
+
+    /// Imports every `*.json` user file in `dir`, overwriting any existing
+    /// entry with the same user ID.
+    pub fn import_from(&mut self, dir: &Path) -> Result<usize> {
+        let mut imported = 0;
+
+        for entry in
+            fs::read_dir(dir).map_err(|e| anyhow!("Failed to read {}: {}", dir.display(), e))?
+        {
+            let path = entry.map_err(|e| anyhow!("Failed to read directory entry: {}", e))?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+
+            let content = fs::read_to_string(&path)
+                .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+            let user: UserInfo = serde_json::from_str(&content)
+                .map_err(|e| anyhow!("Failed to parse {}: {}", path.display(), e))?;
+
+            self.users.insert(user.user_id.clone(), user);
+            imported += 1;
+        }
+
+        self.save()?;
+        Ok(imported)
+    }
+
+    /// Removes users whose `last_active` is older than `max_age`. The
+    /// current user is never pruned, even if stale, to avoid locking an
+    /// admin out of their own machine.
+    pub fn prune_inactive(&mut self, max_age: chrono::Duration) -> Result<usize> {
+        let cutoff = chrono::Utc::now() - max_age;
+        let current = self.current_user.clone();
+
+        let stale_ids: Vec<String> = self
+            .users
+            .iter()
+            .filter(|(id, user)| {
+                Some(id.as_str()) != current.as_deref()
+                    && chrono::DateTime::parse_from_rfc3339(&user.last_active)
+                        .map(|dt| dt.with_timezone(&chrono::Utc) < cutoff)
+                        .unwrap_or(false)
+            })
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        for id in &stale_ids {
+            self.users.remove(id);
+        }
+
+        self.save()?;
+        Ok(stale_ids.len())
+    }
 }
 
-// Helper function to format timestamps for display
+// Helper function to format timestamps for display, honoring the user's
+// configured timezone/clock/relative-time preferences.
 pub fn format_timestamp(timestamp: &str) -> String {
     if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(timestamp) {
-        dt.format("%Y-%m-%d %H:%M UTC").to_string()
+        let config = reach::config::load_config_cached().unwrap_or_default();
+        reach::format_timestamp(dt.with_timezone(&chrono::Utc), &config)
     } else {
         timestamp.to_string()
     }
@@ -3,3 +3,6 @@
 
 pub use reach; // Re-export reach (P2P core)
 pub use rus; // Re-export rus (CLI operations)
+
+mod napi_bindings; // Node.js native addon bindings (napi)
+pub use napi_bindings::*;
@@ -9,6 +9,12 @@ use std::process::Command;
 struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
+    /// Forwarded to `rus --config-dir` - see its help for details.
+    #[arg(long, global = true, value_name = "DIR")]
+    config_dir: Option<std::path::PathBuf>,
+    /// Forwarded to `rus --profile` - see its help for details.
+    #[arg(long, global = true, value_name = "NAME")]
+    profile: Option<String>,
 }
 
 #[derive(Subcommand)]
@@ -85,28 +91,28 @@ fn main() -> Result<()> {
 
     // All commands delegate to rus CLI with appropriate arguments
     let mut cmd = Command::new("rus");
-    
+
     match cli.command {
         Some(Commands::Setup) => {
             cmd.arg("setup");
         }
         Some(Commands::Chat { port }) => {
-            cmd.args(&["chat", "--port", &port.to_string()]);
+            cmd.args(["chat", "--port", &port.to_string()]);
         }
         Some(Commands::Info) => {
             cmd.arg("info");
         }
         Some(Commands::Connect { address, port }) => {
-            cmd.args(&["connect", &address, "--port", &port.to_string()]);
+            cmd.args(["connect", &address, "--port", &port.to_string()]);
         }
         Some(Commands::Send { message }) => {
-            cmd.args(&["send", &message]);
+            cmd.args(["send", &message]);
         }
         Some(Commands::Peers) => {
             cmd.arg("peers");
         }
         Some(Commands::Nick { name }) => {
-            cmd.args(&["nick", &name]);
+            cmd.args(["nick", &name]);
         }
         Some(Commands::Reset) => {
             cmd.arg("reset");
@@ -123,7 +129,7 @@ fn main() -> Result<()> {
             cmd.arg("users");
             match action {
                 UsersCommands::List => cmd.arg("list"),
-                UsersCommands::Switch { id } => cmd.args(&["switch", &id]),
+                UsersCommands::Switch { id } => cmd.args(["switch", &id]),
                 UsersCommands::Current => cmd.arg("current"),
             };
         }
@@ -133,6 +139,13 @@ fn main() -> Result<()> {
         }
     }
 
+    if let Some(config_dir) = &cli.config_dir {
+        cmd.arg("--config-dir").arg(config_dir);
+    }
+    if let Some(profile) = &cli.profile {
+        cmd.args(["--profile", profile]);
+    }
+
     // Execute the rus command
     let status = cmd.status()?;
     if !status.success() {
@@ -0,0 +1,334 @@
+// Node.js (napi) bindings exposed when this crate is built as the
+// `rustalk_lib` cdylib and loaded as a native addon.
+
+use napi_derive::napi;
+use uuid::Uuid;
+
+/// Per-peer traffic/reconnect/RTT counters - mirrors `reach::stats::PeerStats`.
+#[napi(object)]
+pub struct PeerStats {
+    pub bytes_sent: f64,
+    pub bytes_received: f64,
+    pub messages_sent: f64,
+    pub messages_received: f64,
+    pub reconnects: f64,
+    pub average_rtt_ms: Option<f64>,
+    pub rtt_samples: f64,
+}
+
+impl From<&reach::PeerStats> for PeerStats {
+    fn from(stats: &reach::PeerStats) -> Self {
+        PeerStats {
+            bytes_sent: stats.bytes_sent as f64,
+            bytes_received: stats.bytes_received as f64,
+            messages_sent: stats.messages_sent as f64,
+            messages_received: stats.messages_received as f64,
+            reconnects: stats.reconnects as f64,
+            average_rtt_ms: stats.average_rtt_ms,
+            rtt_samples: stats.rtt_samples as f64,
+        }
+    }
+}
+
+/// Network activity snapshot - mirrors `reach::stats::NetworkStats`.
+#[napi(object)]
+pub struct NetworkStats {
+    pub global: PeerStats,
+    pub per_peer: std::collections::HashMap<String, PeerStats>,
+}
+
+/// Returns a network stats snapshot for the logged-in user's identity.
+///
+/// There's no long-running engine process shared between this binding and a
+/// `rus`/`rustalk` CLI session - each call builds its own short-lived
+/// `NetworkManager` from the config file (`reach::load_config`), so it only reflects activity from
+/// that `NetworkManager`'s own (empty, for a freshly constructed one)
+/// lifetime, not a session running elsewhere. That matches how the rest of
+/// this codebase works today: there's no persisted stats store or IPC to a
+/// running session to read from instead.
+#[napi]
+pub async fn get_network_stats() -> napi::Result<NetworkStats> {
+    let config = reach::load_config()
+        .map_err(|e| napi::Error::from_reason(format!("Failed to load config: {}", e)))?;
+
+    let network = reach::NetworkManager::new(config.identity)
+        .await
+        .map_err(|e| napi::Error::from_reason(format!("Failed to create network manager: {}", e)))?;
+
+    let stats = network.get_stats().await;
+
+    Ok(NetworkStats {
+        global: PeerStats::from(&stats.global),
+        per_peer: stats
+            .per_peer
+            .iter()
+            .map(|(peer_id, peer_stats)| (peer_id.clone(), PeerStats::from(peer_stats)))
+            .collect(),
+    })
+}
+
+/// Connects to `address`, offers `path`, and waits up to `timeout_secs`
+/// (default 300) for the transfer to finish, returning whether the
+/// receiver's checksum verification passed.
+///
+/// Same short-lived `NetworkManager` caveat as `get_network_stats`: this
+/// binding has no persistent session, so there's nothing on this end to
+/// auto-accept a transfer in the other direction. The peer at `address` must
+/// be a running `rus`/`rustalk` session whose user runs `/file accept
+/// <transfer_id> <dest_path>` - this call just waits on the `FileComplete`/
+/// `FileReject` reply that produces, via the same `receive_messages` queue
+/// `spawn_reader` delivers into for every other message type.
+#[napi]
+pub async fn send_file(address: String, path: String, timeout_secs: Option<u32>) -> napi::Result<bool> {
+    let config = reach::load_config()
+        .map_err(|e| napi::Error::from_reason(format!("Failed to load config: {}", e)))?;
+
+    let network = reach::NetworkManager::new(config.identity)
+        .await
+        .map_err(|e| napi::Error::from_reason(format!("Failed to create network manager: {}", e)))?;
+
+    let peer = network
+        .connect_to_peer(&address)
+        .await
+        .map_err(|e| napi::Error::from_reason(format!("Failed to connect to {}: {}", address, e)))?;
+
+    let transfer_id = network
+        .offer_file(&peer.id.to_string(), std::path::Path::new(&path))
+        .await
+        .map_err(|e| napi::Error::from_reason(format!("Failed to offer file: {}", e)))?;
+
+    let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(timeout_secs.unwrap_or(300) as u64);
+    loop {
+        let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+        if remaining.is_zero() {
+            return Err(napi::Error::from_reason(format!(
+                "Transfer {} timed out waiting for peer {} to accept/finish",
+                transfer_id, address
+            )));
+        }
+
+        let message = match tokio::time::timeout(remaining, network.receive_messages()).await {
+            Ok(Some(message)) => message,
+            Ok(None) => {
+                return Err(napi::Error::from_reason(format!(
+                    "Connection to {} closed before transfer {} completed",
+                    address, transfer_id
+                )));
+            }
+            Err(_) => {
+                return Err(napi::Error::from_reason(format!(
+                    "Transfer {} timed out waiting for peer {} to accept/finish",
+                    transfer_id, address
+                )));
+            }
+        };
+
+        match message.message_type {
+            reach::MessageType::FileComplete if message.content.starts_with(&transfer_id.to_string()) => {
+                let checksum_ok = message
+                    .content
+                    .split_once(' ')
+                    .is_some_and(|(_, ok)| ok == "true");
+                return Ok(checksum_ok);
+            }
+            reach::MessageType::FileReject if message.content.trim() == transfer_id.to_string() => {
+                return Err(napi::Error::from_reason(format!("Peer {} rejected transfer {}", address, transfer_id)));
+            }
+            _ => continue,
+        }
+    }
+}
+
+/// Connects to `address` and adds (or, with `add: false`, removes) our
+/// `emoji` reaction on `message_id`.
+///
+/// Same short-lived `NetworkManager` caveat as `get_network_stats`/`send_file`:
+/// there's no persistent session here to look the message up in, so this
+/// trusts the caller that `message_id` is one `address` actually has in its
+/// own history - `ChatSession::apply_reaction` on their end is what actually
+/// validates and records it.
+#[napi]
+pub async fn set_reaction(address: String, message_id: String, emoji: String, add: bool) -> napi::Result<()> {
+    let message_id = Uuid::parse_str(&message_id)
+        .map_err(|e| napi::Error::from_reason(format!("Invalid message id '{}': {}", message_id, e)))?;
+
+    let config = reach::load_config()
+        .map_err(|e| napi::Error::from_reason(format!("Failed to load config: {}", e)))?;
+
+    let network = reach::NetworkManager::new(config.identity)
+        .await
+        .map_err(|e| napi::Error::from_reason(format!("Failed to create network manager: {}", e)))?;
+
+    let peer = network
+        .connect_to_peer(&address)
+        .await
+        .map_err(|e| napi::Error::from_reason(format!("Failed to connect to {}: {}", address, e)))?;
+
+    network
+        .send_reaction(&peer.id.to_string(), message_id, emoji, add)
+        .await
+        .map_err(|e| napi::Error::from_reason(format!("Failed to send reaction: {}", e)))
+}
+
+/// Connects to `address` and sends `text` as a `MessageType::Markdown` message -
+/// see `reach::network::NetworkManager::send_markdown`.
+///
+/// Same short-lived `NetworkManager` caveat as `get_network_stats`.
+#[napi]
+pub async fn send_markdown(address: String, text: String) -> napi::Result<()> {
+    let config = reach::load_config()
+        .map_err(|e| napi::Error::from_reason(format!("Failed to load config: {}", e)))?;
+
+    let network = reach::NetworkManager::new(config.identity)
+        .await
+        .map_err(|e| napi::Error::from_reason(format!("Failed to create network manager: {}", e)))?;
+
+    let peer = network
+        .connect_to_peer(&address)
+        .await
+        .map_err(|e| napi::Error::from_reason(format!("Failed to connect to {}: {}", address, e)))?;
+
+    network
+        .send_markdown(&peer.id.to_string(), text)
+        .await
+        .map_err(|e| napi::Error::from_reason(format!("Failed to send markdown: {}", e)))
+}
+
+/// Connects to `address` and sends `lang`/`text` as a `MessageType::Code`
+/// message - see `reach::network::NetworkManager::send_code`.
+///
+/// Same short-lived `NetworkManager` caveat as `get_network_stats`.
+#[napi]
+pub async fn send_code(address: String, lang: String, text: String) -> napi::Result<()> {
+    let config = reach::load_config()
+        .map_err(|e| napi::Error::from_reason(format!("Failed to load config: {}", e)))?;
+
+    let network = reach::NetworkManager::new(config.identity)
+        .await
+        .map_err(|e| napi::Error::from_reason(format!("Failed to create network manager: {}", e)))?;
+
+    let peer = network
+        .connect_to_peer(&address)
+        .await
+        .map_err(|e| napi::Error::from_reason(format!("Failed to connect to {}: {}", address, e)))?;
+
+    network
+        .send_code(&peer.id.to_string(), lang, text)
+        .await
+        .map_err(|e| napi::Error::from_reason(format!("Failed to send code: {}", e)))
+}
+
+/// Connects to `address` and sends the image file at `path` inline - see
+/// `reach::network::NetworkManager::send_image`. The MIME type is guessed
+/// from `path`'s extension the same way `SessionManager::send_image` does,
+/// since there's no separate channel here to pass one in explicitly.
+///
+/// Same short-lived `NetworkManager` caveat as `get_network_stats`/`send_file`.
+#[napi]
+pub async fn send_image(address: String, path: String) -> napi::Result<()> {
+    let config = reach::load_config()
+        .map_err(|e| napi::Error::from_reason(format!("Failed to load config: {}", e)))?;
+
+    let network = reach::NetworkManager::new(config.identity)
+        .await
+        .map_err(|e| napi::Error::from_reason(format!("Failed to create network manager: {}", e)))?;
+
+    let peer = network
+        .connect_to_peer(&address)
+        .await
+        .map_err(|e| napi::Error::from_reason(format!("Failed to connect to {}: {}", address, e)))?;
+
+    let data = std::fs::read(&path)
+        .map_err(|e| napi::Error::from_reason(format!("Failed to read {}: {}", path, e)))?;
+    let path_ref = std::path::Path::new(&path);
+    let mime = reach::guess_mime(path_ref);
+    let filename = path_ref
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "image".to_string());
+
+    network
+        .send_image(&peer.id.to_string(), filename, mime, data)
+        .await
+        .map_err(|e| napi::Error::from_reason(format!("Failed to send image: {}", e)))
+}
+
+/// A contact roster entry - mirrors `reach::contacts::Contact`.
+#[napi(object)]
+pub struct Contact {
+    pub peer_id: String,
+    pub display_name: String,
+    pub email: Option<String>,
+    pub public_key: Option<String>,
+    pub notes: Option<String>,
+    pub verified: bool,
+}
+
+impl From<reach::Contact> for Contact {
+    fn from(contact: reach::Contact) -> Self {
+        Contact {
+            peer_id: contact.peer_id,
+            display_name: contact.display_name,
+            email: contact.email,
+            public_key: contact.public_key,
+            notes: contact.notes,
+            verified: contact.verified,
+        }
+    }
+}
+
+/// Adds or updates a contact in the local roster - see `reach::ContactBook::add`.
+#[napi]
+pub fn add_contact(
+    peer_id: String,
+    display_name: String,
+    email: Option<String>,
+    public_key: Option<String>,
+    notes: Option<String>,
+) -> napi::Result<Contact> {
+    let book = reach::ContactBook::new()
+        .map_err(|e| napi::Error::from_reason(format!("Failed to open contact book: {}", e)))?;
+    book.add(&peer_id, &display_name, email, public_key, notes)
+        .map(Contact::from)
+        .map_err(|e| napi::Error::from_reason(format!("Failed to save contact: {}", e)))
+}
+
+/// Renames an existing contact - see `reach::ContactBook::rename`.
+#[napi]
+pub fn rename_contact(peer_id: String, display_name: String) -> napi::Result<Contact> {
+    let book = reach::ContactBook::new()
+        .map_err(|e| napi::Error::from_reason(format!("Failed to open contact book: {}", e)))?;
+    book.rename(&peer_id, display_name)
+        .map(Contact::from)
+        .map_err(|e| napi::Error::from_reason(format!("Failed to rename contact: {}", e)))
+}
+
+/// Removes a contact - see `reach::ContactBook::remove`.
+#[napi]
+pub fn remove_contact(peer_id: String) -> napi::Result<()> {
+    let book = reach::ContactBook::new()
+        .map_err(|e| napi::Error::from_reason(format!("Failed to open contact book: {}", e)))?;
+    book.remove(&peer_id)
+        .map_err(|e| napi::Error::from_reason(format!("Failed to remove contact: {}", e)))
+}
+
+/// Lists every contact in the local roster - see `reach::ContactBook::list`.
+#[napi]
+pub fn list_contacts() -> napi::Result<Vec<Contact>> {
+    let book = reach::ContactBook::new()
+        .map_err(|e| napi::Error::from_reason(format!("Failed to open contact book: {}", e)))?;
+    book.list()
+        .map(|contacts| contacts.into_iter().map(Contact::from).collect())
+        .map_err(|e| napi::Error::from_reason(format!("Failed to list contacts: {}", e)))
+}
+
+// No `acceptPeer`/`rejectPeer` binding here: `NetworkManager::accept_pending`
+// (see `reach::session::SessionManager::accept_peer`) approves a connection
+// held in-memory by whichever `NetworkManager` answered the handshake. Every
+// binding in this file builds its own short-lived `NetworkManager` per call
+// instead of sharing one with a running `rus`/`rustalk` session (see
+// `get_network_stats`'s doc comment), so there's no pending connection for a
+// call from here to ever find - that only works from the CLI session that
+// actually accepted the handshake. Exposing a no-op or always-erroring
+// function for the sake of matching a shape wouldn't give Node callers
+// anything they could use.